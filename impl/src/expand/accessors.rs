@@ -0,0 +1,79 @@
+//! Per-variant payload accessors (`#[enum_group(accessors)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+
+use super::Context;
+use crate::util::snake_ident;
+
+/// Generates `as_<variant>()` and `into_<variant>()` for every single-payload variant when
+/// requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  if !cx.input.options.accessors {
+    return TokenStream2::new();
+  }
+  let vis = &cx.input.vis;
+  let wire_ty = &cx.wire_ty;
+  let (impl_generics, where_clause) = cx.impl_generics();
+
+  // Methods generated on the wire enum whatever its variants are named
+  let method = cx.input.options.method.clone().unwrap_or_else(|| format_ident!("into_group"));
+  let mut taken = vec![method];
+  if cx.input.options.as_group {
+    taken.extend([format_ident!("as_group"), format_ident!("as_group_mut")]);
+  }
+  if cx.input.options.payload_any {
+    taken.push(format_ident!("into_payload_any"));
+  }
+
+  let mut accessors = Vec::new();
+  for (_, v) in cx.variants() {
+    let Some(ty) = v.payload() else { continue };
+    let snake = snake_ident(&v.name).unraw();
+    let (as_method, into_method) = (format_ident!("as_{}", snake), format_ident!("into_{}", snake));
+    if let Some(clash) = taken.iter().find(|m| **m == as_method || **m == into_method) {
+      return syn::Error::new_spanned(
+        &v.name,
+        format!("the accessors of `{}` clash with the generated `{}` method", v.name, clash),
+      )
+      .to_compile_error();
+    }
+    let (v_name, cfg) = (&v.name, v.cfg());
+    let borrowed = if v.options.boxed {
+      quote! { &**v }
+    } else {
+      quote! { v }
+    };
+    let owned = v.unbox_payload(quote! { v });
+    let as_doc = format!("Returns the payload if this is a `{}` message.", v_name);
+    let into_doc = format!("Converts into the payload if this is a `{}` message.", v_name);
+    accessors.push(quote! {
+        #[doc = #as_doc]
+        #cfg
+        #vis fn #as_method(&self) -> ::core::option::Option<&#ty> {
+            match self {
+                Self::#v_name(v) => ::core::option::Option::Some(#borrowed),
+                #[allow(unreachable_patterns)]
+                _ => ::core::option::Option::None,
+            }
+        }
+
+        #[doc = #into_doc]
+        #cfg
+        #vis fn #into_method(self) -> ::core::option::Option<#ty> {
+            match self {
+                Self::#v_name(v) => ::core::option::Option::Some(#owned),
+                #[allow(unreachable_patterns)]
+                _ => ::core::option::Option::None,
+            }
+        }
+    });
+  }
+
+  quote! {
+      impl #impl_generics #wire_ty #where_clause {
+          #(#accessors)*
+      }
+  }
+}
@@ -0,0 +1,100 @@
+//! Avro schema and datum encoding (`#[enum_group(avro)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+
+use super::Context;
+
+/// Generates `WireMsg::avro_schema()`, `WireMsg::to_avro()` and `WireMsg::from_avro()` when
+/// requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+
+  let Some(predicate) = &cx.input.options.avro else {
+    return TokenStream2::new();
+  };
+  let cfg = predicate.as_ref().map(|p| quote! { #[cfg(#p)] });
+
+  // Every record holds a single payload
+  let payloads: syn::Result<Vec<_>> =
+    cx.variants().map(|(g, v)| Ok((g, v, v.require_payload("avro")?))).collect();
+  let payloads = match payloads {
+    Ok(payloads) => payloads,
+    Err(error) => return error.to_compile_error(),
+  };
+
+  let records = payloads.iter().map(|(g, v, ty)| {
+    let name = format!("{}.{}.{}", wire.unraw(), g.name.unraw(), v.name.unraw());
+    let cfg = v.cfg();
+    quote! { #cfg { builder = builder.variant::<#ty>(#name); } }
+  });
+
+  // The union index of a record is the variant's kind, which skips the variants configured
+  // out just like the schema does
+  let kind = &cx.kind;
+  let (encode_arms, decode_arms): (Vec<_>, Vec<_>) = cx
+    .variants()
+    .map(|(_, v)| {
+      let (v_name, cfg) = (&v.name, v.cfg());
+      let index = quote! { #kind::#v_name as u32 };
+      let encode = quote! {
+          #cfg
+          Self::#v_name(v) => ::enum_group_macros::__private::avro::encode(schema, #index, v)
+      };
+      let decode = quote! {
+          #cfg
+          index if index == #index => {
+              ::enum_group_macros::__private::apache_avro::from_value(&payload).map(Self::#v_name)
+          }
+      };
+      (encode, decode)
+    })
+    .unzip();
+
+  quote! {
+      #cfg
+      impl #wire {
+          /// The Avro schema of this enum: a union with one record per variant, named
+          /// `Wire.Group.Variant`, whose `payload` field holds the payload's schema.
+          #vis fn avro_schema() -> ::enum_group_macros::__private::apache_avro::Schema {
+              let mut builder = ::enum_group_macros::__private::avro::UnionBuilder::default();
+              #(#records)*
+              builder.build()
+          }
+
+          /// Encodes this message as a single Avro datum of [`Self::avro_schema`].
+          #vis fn to_avro(
+              &self,
+          ) -> ::core::result::Result<
+              ::std::vec::Vec<u8>,
+              ::enum_group_macros::__private::apache_avro::Error,
+          > {
+              static SCHEMA: ::std::sync::OnceLock<
+                  ::enum_group_macros::__private::apache_avro::Schema,
+              > = ::std::sync::OnceLock::new();
+              let schema = SCHEMA.get_or_init(Self::avro_schema);
+              match self {
+                  #(#encode_arms),*
+              }
+          }
+
+          /// Decodes a single Avro datum of [`Self::avro_schema`], as written by
+          /// [`Self::to_avro`].
+          #vis fn from_avro(
+              bytes: &[u8],
+          ) -> ::core::result::Result<Self, ::enum_group_macros::__private::apache_avro::Error> {
+              static SCHEMA: ::std::sync::OnceLock<
+                  ::enum_group_macros::__private::apache_avro::Schema,
+              > = ::std::sync::OnceLock::new();
+              let schema = SCHEMA.get_or_init(Self::avro_schema);
+              let (index, payload) = ::enum_group_macros::__private::avro::decode(schema, bytes)?;
+              match index {
+                  #(#decode_arms,)*
+                  _ => ::core::unreachable!("union index out of range of the schema"),
+              }
+          }
+      }
+  }
+}
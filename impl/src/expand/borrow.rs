@@ -0,0 +1,319 @@
+//! Borrowed views of a message (`#[enum_group(as_group)]`): the group enums and the
+//! dispatch enum holding shared or mutable references into the wire enum.
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, ToTokens};
+use syn::{GenericParam, Generics, Ident, Lifetime, LifetimeParam};
+
+use super::Context;
+use crate::parse::{ParsedGroup, ParsedVariant, VariantFields};
+use crate::util::{doc_comments, has_derive};
+
+/// How a view borrows the message.
+#[derive(Clone, Copy)]
+enum Borrow {
+  /// `{Name}Ref<'msg>`, from `as_group(&self)`.
+  Shared,
+  /// `{Name}Mut<'msg>`, from `as_group_mut(&mut self)`.
+  Mut,
+}
+
+impl Borrow {
+  const ALL: [Borrow; 2] = [Borrow::Shared, Borrow::Mut];
+
+  /// The borrowed counterpart of the enum `name`, `{name}Ref` or `{name}Mut`.
+  fn ident(self, name: &Ident) -> Ident {
+    match self {
+      Borrow::Shared => format_ident!("{}Ref", name),
+      Borrow::Mut => format_ident!("{}Mut", name),
+    }
+  }
+
+  /// A reference to `ty` for `lifetime`.
+  fn reference(self, lifetime: &Lifetime, ty: &dyn ToTokens) -> TokenStream2 {
+    match self {
+      Borrow::Shared => quote! { &#lifetime #ty },
+      Borrow::Mut => quote! { &#lifetime mut #ty },
+    }
+  }
+}
+
+/// Generates `{Group}Ref<'msg>` and `{Group}Mut<'msg>` for `group` and its nested groups
+/// when requested, next to their owned enums and with the same visibility `vis`.
+pub(crate) fn group_enums(cx: &Context, group: &ParsedGroup, vis: &TokenStream2) -> TokenStream2 {
+  if !cx.input.options.as_group {
+    return TokenStream2::new();
+  }
+  let enums = Borrow::ALL.into_iter().map(|borrow| borrowed_group_enums(cx, group, vis, borrow));
+  quote! { #(#enums)* }
+}
+
+/// The `borrow` counterparts of the enums of `group` and its nested groups.
+fn borrowed_group_enums(
+  cx: &Context,
+  group: &ParsedGroup,
+  vis: &TokenStream2,
+  borrow: Borrow,
+) -> TokenStream2 {
+  let lifetime = lifetime(cx);
+  let top = (&group.name, 0, group.variants.iter().collect(), cx.group_generics(group));
+  let nested = group.nested_groups().into_iter().map(|n| {
+    let generics = cx.nested_generics(&n);
+    (n.name, n.depth, n.variants, generics)
+  });
+  let enums = std::iter::once(top).chain(nested).map(
+    |(name, depth, variants, generics): (_, _, Vec<_>, _)| {
+      let members = members(cx, group, variants.iter().copied(), depth, &lifetime, borrow);
+      let doc = match borrow {
+        Borrow::Shared => format!("A borrowed [`{}`], holding references into the message.", name),
+        Borrow::Mut => {
+          format!("A mutably borrowed [`{}`], holding mutable references into the message.", name)
+        }
+      };
+      let generics = with_lifetime(&lifetime, generics, borrows(variants.iter().copied()));
+      declare(cx, &borrow.ident(name), &doc, vis, generics, &members, borrow)
+    },
+  );
+  quote! { #(#enums)* }
+}
+
+/// Generates `{Dispatch}Ref<'msg>` with `WireMsg::as_group()` and `{Dispatch}Mut<'msg>`
+/// with `WireMsg::as_group_mut()` when requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  if !cx.input.options.as_group {
+    return TokenStream2::new();
+  }
+  let views = Borrow::ALL.into_iter().map(|borrow| view(cx, borrow));
+  quote! { #(#views)* }
+}
+
+/// The `borrow` counterpart of the dispatch enum and the method borrowing a message in it.
+fn view(cx: &Context, borrow: Borrow) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let (wire, wire_ty) = (cx.wire, &cx.wire_ty);
+  let (impl_generics, where_clause) = cx.impl_generics();
+  let lifetime = lifetime(cx);
+  let dispatch_ref = borrow.ident(&cx.dispatch);
+
+  let mut variants: Vec<_> = cx
+    .dispatched_groups()
+    .map(|g| {
+      let (g_name, docs) = (&g.name, doc_comments(&g.attrs));
+      let g_ref = group_ref_ty(cx, g, &lifetime, borrow);
+      quote! { #(#docs)* #g_name(#g_ref) }
+    })
+    .collect();
+  if let Some(undispatched) = cx.undispatched() {
+    let msg = borrow.reference(&lifetime, wire_ty);
+    variants.push(quote! {
+        /// Messages of the groups left out of dispatch with `skip_dispatch`.
+        #undispatched(#msg)
+    });
+  }
+  let (method, doc) = match borrow {
+    Borrow::Shared => (
+      format_ident!("as_group"),
+      format!("A borrowed [`{}`], as returned by [`{}::as_group`].", cx.dispatch, wire),
+    ),
+    Borrow::Mut => (
+      format_ident!("as_group_mut"),
+      format!("A mutably borrowed [`{}`], as returned by [`{}::as_group_mut`].", cx.dispatch, wire),
+    ),
+  };
+  // `Undispatched` borrows the whole message
+  let borrows =
+    cx.undispatched().is_some() || borrows(cx.dispatched_groups().flat_map(|g| &g.variants));
+  let generics = with_lifetime(&lifetime, cx.input.generics.clone(), borrows);
+  let dispatch_enum =
+    declare(cx, &dispatch_ref, &doc, &quote! { #vis }, generics, &variants, borrow);
+
+  // Legacy variants are viewed as they are, since converting them needs ownership
+  let arms = cx.variants().map(|(g, v)| {
+    let (v_name, cfg) = (&v.name, v.cfg());
+    match cx.undispatched().filter(|_| g.options.skip_dispatch) {
+      Some(undispatched) => quote! {
+          #cfg msg @ Self::#v_name { .. } => #dispatch_ref::#undispatched(msg)
+      },
+      None => {
+        let binding = v.fields_binding(&format_ident!("v"));
+        let inner = match (v.options.boxed, borrow) {
+          (true, Borrow::Shared) => quote! { (&**v) },
+          (true, Borrow::Mut) => quote! { (&mut **v) },
+          (false, _) => binding.clone(),
+        };
+        let g_name = &g.name;
+        let value = wrap(cx, g, v, inner, borrow);
+        quote! { #cfg Self::#v_name #binding => #dispatch_ref::#g_name(#value) }
+      }
+    }
+  });
+  let return_ty = {
+    let elided = Lifetime::new("'_", Span::call_site());
+    let generics = with_lifetime(&elided, cx.input.generics.clone(), borrows);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    quote! { #dispatch_ref #ty_generics }
+  };
+
+  let method = match borrow {
+    Borrow::Shared => quote! {
+        /// Borrows this message in the enum of its group, to branch on the group without
+        /// moving or cloning the payload.
+        #vis fn #method(&self) -> #return_ty
+    },
+    Borrow::Mut => quote! {
+        /// Mutably borrows this message in the enum of its group, to modify the payload in
+        /// place while branching on the group.
+        #vis fn #method(&mut self) -> #return_ty
+    },
+  };
+  quote! {
+      #dispatch_enum
+
+      impl #impl_generics #wire_ty #where_clause {
+          #method {
+              match self {
+                  #(#arms),*
+              }
+          }
+      }
+  }
+}
+
+/// The borrowed enum `name`, deriving `Debug`, and `PartialEq` as the wire enum does. A
+/// shared view is `Copy` whatever its type parameters.
+fn declare(
+  cx: &Context,
+  name: &Ident,
+  doc: &str,
+  vis: &TokenStream2,
+  generics: Generics,
+  variants: &[TokenStream2],
+  borrow: Borrow,
+) -> TokenStream2 {
+  let partial_eq =
+    has_derive(&cx.input.attrs, "PartialEq").then(|| quote! { ::core::cmp::PartialEq, });
+  let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+  let copy = matches!(borrow, Borrow::Shared).then(|| {
+    quote! {
+        impl #impl_generics ::core::clone::Clone for #name #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl #impl_generics ::core::marker::Copy for #name #ty_generics #where_clause {}
+    }
+  });
+  quote! {
+      #[doc = #doc]
+      #[derive(::core::fmt::Debug, #partial_eq)]
+      #vis enum #name #generics #where_clause {
+          #(#variants),*
+      }
+
+      #copy
+  }
+}
+
+/// The variants of the borrowed enum at `depth` holding `variants`, with references in
+/// place of the fields, and one per nested group, wrapping the nested group's.
+fn members<'v>(
+  cx: &Context,
+  group: &ParsedGroup,
+  variants: impl IntoIterator<Item = &'v ParsedVariant>,
+  depth: usize,
+  lifetime: &Lifetime,
+  borrow: Borrow,
+) -> Vec<TokenStream2> {
+  let nested = group.nested_groups();
+  let mut seen = Vec::new();
+  let mut members = Vec::new();
+  for v in variants {
+    match v.nesting.get(depth) {
+      None => {
+        let (v_name, cfg, docs) = (v.group_ident(), v.cfg(), doc_comments(&v.attrs));
+        let fields = match &v.fields {
+          VariantFields::Payload { ty, .. } => {
+            let ty = borrow.reference(lifetime, ty);
+            quote! { (#ty) }
+          }
+          VariantFields::Named(named) => {
+            let fields = named.named.iter().map(|f| {
+              let (name, ty) = (&f.ident, borrow.reference(lifetime, &f.ty));
+              quote! { #name: #ty }
+            });
+            quote! { { #(#fields),* } }
+          }
+          VariantFields::Unit => TokenStream2::new(),
+        };
+        members.push(quote! { #cfg #(#docs)* #v_name #fields });
+      }
+      Some(name) if !seen.contains(&name) => {
+        seen.push(name);
+        let n = nested.iter().find(|n| n.name == name).expect("nested group of a variant");
+        let borrows = borrows(n.variants.iter().copied());
+        let generics = with_lifetime(lifetime, cx.nested_generics(n), borrows);
+        let (_, ty_generics, _) = generics.split_for_impl();
+        let (nested_ref, docs) = (borrow.ident(name), n.docs);
+        members.push(quote! { #(#docs)* #name(#nested_ref #ty_generics) });
+      }
+      Some(_) => {}
+    }
+  }
+  members
+}
+
+/// The `borrow` counterpart of the enum of `group` as a type, e.g. `ProtocolRef<'msg, T>`.
+fn group_ref_ty(
+  cx: &Context,
+  group: &ParsedGroup,
+  lifetime: &Lifetime,
+  borrow: Borrow,
+) -> TokenStream2 {
+  let module = cx.groups_module(group).map(|m| quote! { #m:: });
+  let name = borrow.ident(&group.name);
+  let generics = with_lifetime(lifetime, cx.group_generics(group), borrows(&group.variants));
+  let (_, ty_generics, _) = generics.split_for_impl();
+  quote! { #module #name #ty_generics }
+}
+
+/// `variant` built from the fields `inner` on the borrowed enum of `group`, wrapped in
+/// the borrowed enums of its nested groups, like [`ParsedGroup::wrap_variant`].
+fn wrap(
+  cx: &Context,
+  group: &ParsedGroup,
+  variant: &ParsedVariant,
+  inner: TokenStream2,
+  borrow: Borrow,
+) -> TokenStream2 {
+  let enums: Vec<Ident> =
+    std::iter::once(&group.name).chain(&variant.nesting).map(|e| borrow.ident(e)).collect();
+  let module = cx.groups_module(group).map(|m| quote! { #m:: });
+  let (innermost, v_name) = (&enums[enums.len() - 1], variant.group_ident());
+  let mut value = quote! { #module #innermost::#v_name #inner };
+  for (outer, nested) in enums.iter().zip(&variant.nesting).rev() {
+    value = quote! { #module #outer::#nested(#value) };
+  }
+  value
+}
+
+/// The lifetime of the borrowed message, `'msg` unless the wire enum declares one of that
+/// name.
+fn lifetime(cx: &Context) -> Lifetime {
+  let taken = cx.input.generics.lifetimes().any(|l| l.lifetime.ident == "msg");
+  Lifetime::new(if taken { "'__msg" } else { "'msg" }, Span::call_site())
+}
+
+/// Whether the borrowed enum holding `variants` borrows anything. One of unit variants
+/// only would leave the lifetime unused.
+fn borrows<'v>(variants: impl IntoIterator<Item = &'v ParsedVariant>) -> bool {
+  variants.into_iter().any(|v| !matches!(v.fields, VariantFields::Unit))
+}
+
+/// `generics` with `lifetime` in front if the enum `borrows`.
+fn with_lifetime(lifetime: &Lifetime, mut generics: Generics, borrows: bool) -> Generics {
+  if borrows {
+    generics.params.insert(0, GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())));
+  }
+  generics
+}
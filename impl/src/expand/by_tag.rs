@@ -0,0 +1,227 @@
+//! Deserialization from a tag and a payload given separately
+//! (`#[enum_group(deserialize_by_tag)]` and `#[enum_group(decode_by_tag)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use super::Context;
+use crate::parse::{ParsedVariant, VariantFields};
+
+/// Generates `WireMsg::deserialize_by_tag()` and the `TryFrom<(&str, &[u8])>` impl, each
+/// when requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let deserialize = deserialize_by_tag(cx);
+  let decode = decode_by_tag(cx);
+  quote! {
+      #deserialize
+
+      #decode
+  }
+}
+
+/// `Err` for untagged enums, whose variants have no tag to look up, and for variants with
+/// named fields, which have no payload type to decode.
+fn check_supported(cx: &Context, option: &str) -> syn::Result<()> {
+  if cx.serde.untagged {
+    return Err(syn::Error::new(
+      cx.wire.span(),
+      format!("`{option}` is not supported for untagged enums"),
+    ));
+  }
+  for (_, v) in cx.variants() {
+    if !matches!(v.fields, VariantFields::Unit) {
+      v.require_payload(option)?;
+    }
+  }
+  Ok(())
+}
+
+/// `(tag, variant)` of every variant with a tag, sorted by tag for the binary search.
+fn sorted_by_tag<'a>(cx: &Context<'a>) -> Vec<(String, &'a ParsedVariant)> {
+  let mut entries: Vec<_> = cx.tagged_variants().map(|(_, v)| (cx.tag(v), v)).collect();
+  entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+  entries
+}
+
+/// `#[allow(unreachable_code)]` and the statement returning the `#[unknown]` catch-all built
+/// by `value`, gated like the catch-all, if there is one.
+fn unknown_fallback(
+  cx: &Context,
+  value: impl FnOnce(&ParsedVariant) -> TokenStream2,
+) -> (TokenStream2, TokenStream2) {
+  match cx.unknown() {
+    Some(v) => {
+      let (cfg, value) = (v.cfg(), value(v));
+      (quote! { #[allow(unreachable_code)] }, quote! { #cfg { return #value; } })
+    }
+    None => (TokenStream2::new(), TokenStream2::new()),
+  }
+}
+
+/// `WireMsg::deserialize_by_tag()`.
+fn deserialize_by_tag(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+
+  let Some(predicate) = &cx.input.options.deserialize_by_tag else {
+    return TokenStream2::new();
+  };
+  if let Err(error) = check_supported(cx, "deserialize_by_tag") {
+    return error.to_compile_error();
+  }
+  let cfg = predicate.as_ref().map(|p| quote! { #[cfg(#p)] });
+
+  let entries = sorted_by_tag(cx);
+  // Unit variants are complete with their tag
+  let entries = entries.iter().map(|(tag, v)| {
+    let (v_name, cfg) = (&v.name, v.cfg());
+    match v.payload() {
+      Some(ty) => {
+        let stored = v.box_payload(quote! { v });
+        quote! {
+            #cfg
+            (#tag, |payload| {
+                <#ty as ::enum_group_macros::__private::serde::Deserialize>::deserialize(payload)
+                    .map(|v| #wire::#v_name(#stored))
+            })
+        }
+      }
+      None => quote! { #cfg (#tag, |_| ::core::result::Result::Ok(#wire::#v_name)) },
+    }
+  });
+  let tags = cx.tagged_variants().map(|(_, v)| {
+    let (tag, cfg) = (cx.tag(v), v.cfg());
+    quote! { #cfg #tag }
+  });
+  // A payload catch-all captures the message as deserializing it whole would
+  let field = |field: &Option<String>| match field {
+    Some(field) => quote! { ::core::option::Option::Some(#field) },
+    None => quote! { ::core::option::Option::None },
+  };
+  let (tag_field, content_field) = (field(&cx.serde.tag), field(&cx.serde.content));
+  let (allow_unreachable, unknown) = unknown_fallback(cx, |v| {
+    let v_name = &v.name;
+    match v.payload() {
+      Some(ty) => {
+        let stored = v.box_payload(quote! { v });
+        quote! {
+            <#ty as ::enum_group_macros::__private::serde::Deserialize>::deserialize(
+                &::enum_group_macros::__private::by_tag::message(
+                    tag,
+                    payload,
+                    #tag_field,
+                    #content_field,
+                ),
+            )
+            .map(|v| #wire::#v_name(#stored))
+        }
+      }
+      None => quote! { ::core::result::Result::Ok(#wire::#v_name) },
+    }
+  });
+
+  quote! {
+      #cfg
+      impl #wire {
+          /// Deserializes the message whose serde tag is `tag` from its payload alone, as
+          /// when a transport carries them in separate fields.
+          ///
+          /// Looks the payload's deserializer up in a static table instead of assembling a
+          /// tagged value for serde's enum machinery.
+          #allow_unreachable
+          #vis fn deserialize_by_tag(
+              tag: &str,
+              payload: &::enum_group_macros::__private::serde_json::Value,
+          ) -> ::core::result::Result<Self, ::enum_group_macros::__private::serde_json::Error> {
+              type Deserializer = fn(
+                  &::enum_group_macros::__private::serde_json::Value,
+              ) -> ::core::result::Result<
+                  #wire,
+                  ::enum_group_macros::__private::serde_json::Error,
+              >;
+              static TABLE: &[(&str, Deserializer)] = &[#(#entries),*];
+              match TABLE.binary_search_by(|(t, _)| (*t).cmp(tag)) {
+                  ::core::result::Result::Ok(index) => (TABLE[index].1)(payload),
+                  ::core::result::Result::Err(_) => {
+                      #unknown
+                      ::core::result::Result::Err(
+                          ::enum_group_macros::__private::by_tag::unknown_tag(
+                              tag,
+                              &[#(#tags),*],
+                          ),
+                      )
+                  }
+              }
+          }
+      }
+  }
+}
+
+/// `impl TryFrom<(&str, &[u8])> for WireMsg`.
+fn decode_by_tag(cx: &Context) -> TokenStream2 {
+  let wire = cx.wire;
+  let Some(settings) = &cx.input.options.decode_by_tag else {
+    return TokenStream2::new();
+  };
+  if let Err(error) = check_supported(cx, "decode_by_tag") {
+    return error.to_compile_error();
+  }
+  let cfg = settings.cfg.as_ref().map(|p| quote! { #[cfg(#p)] });
+  let (with, error) = match &settings.with {
+    Some((with, error)) => (quote! { #with }, quote! { #error }),
+    None => (
+      quote! { ::enum_group_macros::__private::serde_json::from_slice },
+      quote! { ::enum_group_macros::__private::serde_json::Error },
+    ),
+  };
+
+  let entries = sorted_by_tag(cx);
+  let entries = entries.iter().map(|(tag, v)| {
+    let (v_name, cfg) = (&v.name, v.cfg());
+    match v.fields {
+      VariantFields::Unit => {
+        quote! { #cfg (#tag, |_| ::core::result::Result::Ok(#wire::#v_name)) }
+      }
+      _ => quote! { #cfg (#tag, |bytes| #with(bytes).map(#wire::#v_name)) },
+    }
+  });
+  // The tag arrives separately, so a payload catch-all is decoded from the payload alone
+  let (allow_unreachable, unknown) = unknown_fallback(cx, |v| {
+    let v_name = &v.name;
+    match v.fields {
+      VariantFields::Unit => quote! { ::core::result::Result::Ok(#wire::#v_name) },
+      _ => quote! {
+          #with(bytes)
+              .map(#wire::#v_name)
+              .map_err(::enum_group_macros::TagDecodeError::Payload)
+      },
+    }
+  });
+
+  quote! {
+      #cfg
+      impl<'a> ::core::convert::TryFrom<(&'a str, &'a [u8])> for #wire {
+          type Error = ::enum_group_macros::TagDecodeError<#error>;
+
+          /// Decodes the payload `bytes` of the message whose serde tag is `tag`, as when a
+          /// framing protocol carries the tag in a header.
+          #allow_unreachable
+          fn try_from(
+              (tag, bytes): (&'a str, &'a [u8]),
+          ) -> ::core::result::Result<Self, Self::Error> {
+              type Decoder = fn(&[u8]) -> ::core::result::Result<#wire, #error>;
+              static TABLE: &[(&str, Decoder)] = &[#(#entries),*];
+              match TABLE.binary_search_by(|(t, _)| (*t).cmp(tag)) {
+                  ::core::result::Result::Ok(index) => (TABLE[index].1)(bytes)
+                      .map_err(::enum_group_macros::TagDecodeError::Payload),
+                  ::core::result::Result::Err(_) => {
+                      #unknown
+                      ::core::result::Result::Err(::enum_group_macros::TagDecodeError::UnknownTag(
+                          ::std::string::ToString::to_string(tag),
+                      ))
+                  }
+              }
+          }
+      }
+  }
+}
@@ -0,0 +1,121 @@
+//! Closure-based partial handlers (`#[enum_group(handlers)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+
+use super::Context;
+use crate::util::snake_ident;
+
+/// Generates `{Wire}Handlers` when requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  if !cx.input.options.handlers {
+    return TokenStream2::new();
+  }
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let dispatch = &cx.dispatch;
+  let handlers = format_ident!("{}Handlers", wire);
+
+  let groups: Vec<_> = cx.dispatched_groups().map(|g| &g.name).collect();
+  let fields: Vec<_> = groups.iter().map(|g| snake_ident(g)).collect();
+  let setters: Vec<_> =
+    groups.iter().map(|g| format_ident!("on_{}", snake_ident(g).unraw())).collect();
+  let group_tys: Vec<_> = cx.dispatched_groups().map(|g| cx.group_ty(g)).collect();
+  let setter_docs: Vec<_> = cx
+    .dispatched_groups()
+    .map(|g| {
+      let link = cx.group_link(g);
+      format!("Handles {} messages with `handler`, replacing any previous one.", link)
+    })
+    .collect();
+  let into_group = cx.dispatch_expr(quote! { msg });
+  let msg = format_ident!("msg");
+  // Reports `msg` to the `on_unexpected` hook before handing it to the fallback
+  let fallback = |reason: TokenStream2| {
+    quote! {{
+        if let ::core::option::Option::Some(hook) = &mut self.__on_unexpected {
+            hook(&#msg, ::enum_group_macros::UnexpectedReason::#reason);
+        }
+        (self.__fallback)(#msg)
+    }}
+  };
+  let no_handler = fallback(quote! { NoHandler });
+  let undispatched_arm = cx.undispatched_arm(&msg, fallback(quote! { SkippedGroup }));
+
+  let doc = format!(
+    "Optional closures handling [`{}`] messages of some groups, with a fallback for the \
+     others.",
+    wire
+  );
+
+  quote! {
+      #[doc = #doc]
+      #vis struct #handlers<'a, T = ()> {
+          #(#fields: ::core::option::Option<::std::boxed::Box<dyn FnMut(#group_tys) -> T + 'a>>,)*
+          __fallback: ::std::boxed::Box<dyn FnMut(#wire) -> T + 'a>,
+          __on_unexpected: ::core::option::Option<
+              ::std::boxed::Box<dyn FnMut(&#wire, ::enum_group_macros::UnexpectedReason) + 'a>,
+          >,
+      }
+
+      impl<'a, T: ::core::default::Default> ::core::default::Default for #handlers<'a, T> {
+          fn default() -> Self {
+              Self::otherwise(|_| T::default())
+          }
+      }
+
+      impl<'a, T: ::core::default::Default> #handlers<'a, T> {
+          /// Creates handlers ignoring every message, returning `T::default()`.
+          #vis fn new() -> Self {
+              ::core::default::Default::default()
+          }
+      }
+
+      impl<'a, T> #handlers<'a, T> {
+          /// Creates handlers passing every message to `fallback`.
+          #vis fn otherwise(fallback: impl FnMut(#wire) -> T + 'a) -> Self {
+              Self {
+                  #(#fields: ::core::option::Option::None,)*
+                  __fallback: ::std::boxed::Box::new(fallback),
+                  __on_unexpected: ::core::option::Option::None,
+              }
+          }
+
+          /// Sets a hook seeing every message before it goes to the fallback, together with
+          /// the reason, e.g. for logging or metrics.
+          #vis fn on_unexpected(
+              mut self,
+              hook: impl FnMut(&#wire, ::enum_group_macros::UnexpectedReason) + 'a,
+          ) -> Self {
+              self.__on_unexpected = ::core::option::Option::Some(::std::boxed::Box::new(hook));
+              self
+          }
+
+          #(
+              #[doc = #setter_docs]
+              #vis fn #setters(mut self, handler: impl FnMut(#group_tys) -> T + 'a) -> Self {
+                  self.#fields = ::core::option::Option::Some(::std::boxed::Box::new(handler));
+                  self
+              }
+          )*
+
+          /// Hands `msg` to the closure of its group, or to the fallback if that group has
+          /// none or is left out of dispatch.
+          #vis fn handle(&mut self, msg: #wire) -> T {
+              match #into_group {
+                  #(
+                      #dispatch::#groups(group) => match &mut self.#fields {
+                          ::core::option::Option::Some(handler) => handler(group),
+                          ::core::option::Option::None => {
+                              let #msg = ::enum_group_macros::GroupOf::into_wire(group);
+                              #no_handler
+                          }
+                      },
+                  )*
+                  #undispatched_arm
+              }
+          }
+      }
+  }
+}
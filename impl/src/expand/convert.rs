@@ -0,0 +1,165 @@
+//! Conversions between the wire enum, the group enums and the payload types.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::Visibility;
+
+use super::Context;
+use crate::parse::{ParsedVariant, VariantFields};
+use crate::util::{mentions, used_generics};
+
+/// Generates `impl GroupOf<Wire>` and `impl TryFrom<Wire>` for every group enum, nested
+/// ones included, and `impl VariantOf<Wire>`, `impl PayloadOf<Wire>`, `impl From<Payload>
+/// for Wire` and `impl TryFrom<Wire> for Payload` for every payload type backing a single
+/// variant. Variants with named fields have no payload type of their own, and
+/// payloads using the generics of the wire enum are left out.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let wire = cx.wire;
+  let wire_ty = &cx.wire_ty;
+  let (impl_generics, where_clause) = cx.impl_generics();
+
+  // Every group enum, nested ones included, with the variants it holds at any depth
+  let group_enums = cx.input.groups.iter().flat_map(|g| {
+    let top = (cx.group_ty(g), g, 0, g.variants.iter().collect());
+    let nested =
+      g.nested_groups().into_iter().map(move |n| (cx.nested_ty(g, &n), g, n.depth, n.variants));
+    std::iter::once(top).chain(nested)
+  });
+  let group_of_impls = group_enums.map(|(g_ty, g, depth, variants): (_, _, _, Vec<_>)| {
+    let v_names: Vec<_> = variants.iter().map(|v| &v.name).collect();
+    let cfgs: Vec<_> = variants.iter().map(|v| v.cfg()).collect();
+    let bindings: Vec<_> = variants.iter().map(|v| v.fields_binding(&format_ident!("v"))).collect();
+    let module = cx.groups_module(g);
+    let values: Vec<_> = variants
+      .iter()
+      .zip(&bindings)
+      .map(|(v, b)| g.wrap_variant(v, depth, module, b.clone()))
+      .collect();
+    quote! {
+        impl #impl_generics ::enum_group_macros::GroupOf<#wire_ty> for #g_ty #where_clause {
+            fn try_from_wire(wire: #wire_ty) -> ::core::result::Result<Self, #wire_ty> {
+                // The fallback is unreachable when this is the only non-empty group
+                #[allow(unreachable_patterns)]
+                match wire {
+                    #(
+                        #cfgs
+                        #wire::#v_names #bindings => ::core::result::Result::Ok(#values),
+                    )*
+                    other => ::core::result::Result::Err(other),
+                }
+            }
+
+            fn into_wire(self) -> #wire_ty {
+                match self {
+                    #(#cfgs #values => #wire::#v_names #bindings),*
+                }
+            }
+        }
+
+        impl #impl_generics ::core::convert::TryFrom<#wire_ty> for #g_ty #where_clause {
+            type Error = #wire_ty;
+
+            fn try_from(wire: #wire_ty) -> ::core::result::Result<Self, #wire_ty> {
+                ::enum_group_macros::GroupOf::try_from_wire(wire)
+            }
+        }
+    }
+  });
+
+  // Payloads built from the wire enum's generics could overlap with another variant's for
+  // some arguments, or be the bare parameter the orphan rules reject
+  let unique_payloads = cx
+    .variants()
+    .filter(|(_, v)| cx.has_unique_payload(v))
+    .filter_map(|(g, v)| Some((g, v, v.payload()?)))
+    .filter(|(_, _, ty)| used_generics(&cx.input.generics, [*ty]).params.is_empty());
+  // `TryFrom<Wire>` for a payload holding the generated enums could overlap with the
+  // standard blanket impls (`Wrapped(Box<WireMsg>)`) or with a group enum's own impl
+  let generated: Vec<_> = std::iter::once(wire.to_string())
+    .chain(cx.input.groups.iter().flat_map(|g| {
+      let nested = g.nested_groups().into_iter().map(|n| n.name.to_string());
+      std::iter::once(g.name.to_string()).chain(nested).collect::<Vec<_>>()
+    }))
+    .collect();
+  let variant_of_impls = unique_payloads.map(|(g, v, ty)| {
+    let (v_name, cfg) = (&v.name, v.cfg());
+    let (payload, stored) = (v.unbox_payload(quote! { v }), v.box_payload(quote! { self }));
+    // A `#[from]` on the field asks another derive (`thiserror`, `derive_more`) for the impl
+    let from_impl = (!has_from_attr(v)).then(|| {
+      let stored = v.box_payload(quote! { payload });
+      quote! {
+          #cfg
+          impl #impl_generics ::core::convert::From<#ty> for #wire_ty #where_clause {
+              fn from(payload: #ty) -> Self {
+                  #wire::#v_name(#stored)
+              }
+          }
+      }
+    });
+    let try_from_impl = (!mentions(ty, &generated)).then(|| {
+      quote! {
+          #cfg
+          impl #impl_generics ::core::convert::TryFrom<#wire_ty> for #ty #where_clause {
+              type Error = #wire_ty;
+
+              fn try_from(wire: #wire_ty) -> ::core::result::Result<Self, #wire_ty> {
+                  ::enum_group_macros::VariantOf::try_from_wire(wire)
+              }
+          }
+      }
+    });
+    // A group less visible than the wire enum cannot be named by the public impl
+    let visible = matches!(g.vis, None | Some(Visibility::Public(_)));
+    let payload_of_impl = visible.then(|| {
+      let (g_ty, module) = (cx.group_ty(g), cx.groups_module(g));
+      let grouped = g.wrap_variant(v, 0, module, quote! { (#stored) });
+      quote! {
+          #cfg
+          impl #impl_generics ::enum_group_macros::PayloadOf<#wire_ty> for #ty #where_clause {
+              type Group = #g_ty;
+
+              fn into_group(self) -> #g_ty {
+                  #grouped
+              }
+          }
+      }
+    });
+    quote! {
+        #from_impl
+
+        #try_from_impl
+
+        #payload_of_impl
+
+        #cfg
+        impl #impl_generics ::enum_group_macros::VariantOf<#wire_ty> for #ty #where_clause {
+            fn try_from_wire(wire: #wire_ty) -> ::core::result::Result<Self, #wire_ty> {
+                // The fallback is unreachable for single-variant wire enums
+                #[allow(unreachable_patterns)]
+                match wire {
+                    #wire::#v_name(v) => ::core::result::Result::Ok(#payload),
+                    other => ::core::result::Result::Err(other),
+                }
+            }
+
+            fn into_wire(self) -> #wire_ty {
+                #wire::#v_name(#stored)
+            }
+        }
+    }
+  });
+
+  quote! {
+      #(#group_of_impls)*
+
+      #(#variant_of_impls)*
+  }
+}
+
+/// Whether the payload field of `variant` carries `#[from]`.
+fn has_from_attr(variant: &ParsedVariant) -> bool {
+  match &variant.fields {
+    VariantFields::Payload { attrs, .. } => attrs.iter().any(|a| a.path().is_ident("from")),
+    VariantFields::Named(_) | VariantFields::Unit => false,
+  }
+}
@@ -0,0 +1,78 @@
+//! Stable group identifiers from explicit discriminants (`Group = N { ... }`).
+
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+
+use super::Context;
+
+/// Generates `group_discriminant()` on the dispatch and wire enums when the groups have
+/// explicit discriminants.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let (wire_ty, dispatch_ty) = (&cx.wire_ty, &cx.dispatch_ty);
+  let (impl_generics, where_clause) = cx.impl_generics();
+  let group_kind = &cx.group_kind;
+
+  if cx.input.groups.is_empty() || cx.input.groups.iter().any(|g| g.discriminant.is_none()) {
+    return TokenStream2::new();
+  }
+  let groups: Vec<_> = cx.input.groups.iter().map(|g| &g.name).collect();
+  let discriminants: Vec<_> = cx.input.groups.iter().map(|g| &g.discriminant).collect();
+  let dispatched: Vec<_> = cx.dispatched_groups().map(|g| &g.name).collect();
+  let dispatched_discriminants: Vec<_> = cx.dispatched_groups().map(|g| &g.discriminant).collect();
+
+  // The wire message of an `Undispatched` group knows its discriminant, though not in a
+  // const context
+  let msg = format_ident!("msg");
+  let undispatched_arm = cx.undispatched_arm(&msg, quote! { #msg.group_discriminant() });
+  let constness = cx.undispatched().is_none().then(|| quote! { const });
+
+  quote! {
+      impl #impl_generics #dispatch_ty #where_clause {
+          /// The explicit discriminant of this message's group, stable across reorderings
+          /// and insertions of groups.
+          #vis #constness fn group_discriminant(&self) -> u16 {
+              match self {
+                  #(Self::#dispatched(_) => #dispatched_discriminants,)*
+                  #undispatched_arm
+              }
+          }
+      }
+
+      impl #impl_generics #wire_ty #where_clause {
+          /// The explicit discriminant of this message's group, stable across reorderings
+          /// and insertions of groups.
+          #vis fn group_discriminant(&self) -> u16 {
+              match ::enum_group_macros::EnumGroupKind::group_kind(self) {
+                  #(#group_kind::#groups => #discriminants),*
+              }
+          }
+      }
+  }
+}
+
+/// The discriminant of the dispatch enum's `Undispatched` variant: one past the largest
+/// explicit one, so that it never collides with a group's.
+pub(crate) fn undispatched(cx: &Context) -> syn::Result<Option<TokenStream2>> {
+  let mut max: Option<(u16, &syn::LitInt)> = None;
+  for discriminant in cx.input.groups.iter().filter_map(|g| g.discriminant.as_ref()) {
+    let value = discriminant.base10_parse::<u16>()?;
+    if !matches!(max, Some((max, _)) if value <= max) {
+      max = Some((value, discriminant));
+    }
+  }
+  let Some((max, lit)) = max else {
+    return Ok(None);
+  };
+  match max.checked_add(1) {
+    Some(next) => {
+      let next = Literal::u16_unsuffixed(next);
+      Ok(Some(quote! { = #next }))
+    }
+    None => Err(syn::Error::new_spanned(
+      lit,
+      "no discriminant is left for the `Undispatched` variant; keep group discriminants below \
+       `u16::MAX` when using `skip_dispatch`",
+    )),
+  }
+}
@@ -0,0 +1,93 @@
+//! Example instances built from `Default` payloads (`#[enum_group(examples)]` and
+//! `#[enum_group(sample_json)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+
+use super::Context;
+use crate::parse::{ParsedVariant, VariantFields};
+
+/// Generates `WireMsg::examples()` and `WireMsg::sample_json()` when requested, each behind
+/// its requested `cfg` if any.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let kind = &cx.kind;
+  let options = &cx.input.options;
+
+  let examples_fn = options.examples.as_ref().map(|predicate| {
+    let cfg = predicate.as_ref().map(|p| quote! { #[cfg(#p)] });
+    let examples = cx.variants().map(|(_, v)| {
+      let (cfg, instance) = (v.cfg(), default_instance(cx, v));
+      quote! { #cfg #instance }
+    });
+    quote! {
+        #cfg
+        impl #wire {
+            /// One instance of every variant, in declaration order, each holding its
+            /// payload's `Default` value.
+            #vis fn examples() -> ::std::vec::Vec<#wire> {
+                ::std::vec![#(#examples),*]
+            }
+        }
+    }
+  });
+
+  let sample_json_fn = options.sample_json.as_ref().map(|predicate| {
+    let cfg = predicate.as_ref().map(|p| quote! { #[cfg(#p)] });
+    let arms = cx.variants().map(|(_, v)| {
+      let (v_name, cfg) = (&v.name, v.cfg());
+      let instance = default_instance(cx, v);
+      quote! { #cfg #kind::#v_name => #instance }
+    });
+    quote! {
+        #cfg
+        impl #wire {
+            /// The JSON serialization of a message of the given kind holding its payload's
+            /// `Default` value, or `None` if serialization fails.
+            ///
+            /// Snapshotting these for every kind catches accidental changes of the
+            /// serialized layout.
+            #vis fn sample_json(kind: #kind) -> ::core::option::Option<::std::string::String> {
+                let msg = match kind {
+                    #(#arms),*
+                };
+                ::enum_group_macros::__private::serde_json::to_string(&msg).ok()
+            }
+        }
+    }
+  });
+
+  quote! {
+      #examples_fn
+
+      #sample_json_fn
+  }
+}
+
+/// `Wire::Variant(<Payload as Default>::default())`, or the same for each named field,
+/// spanned on the field type so that every type lacking `Default` gets its own error
+/// pointing at it.
+fn default_instance(cx: &Context, v: &ParsedVariant) -> TokenStream2 {
+  let wire = cx.wire;
+  let v_name = &v.name;
+  let default = |ty: &syn::Type| {
+    quote_spanned! {ty.span()=> <#ty as ::core::default::Default>::default() }
+  };
+  match &v.fields {
+    VariantFields::Payload { ty, .. } => {
+      let value = v.box_payload(default(ty));
+      quote! { #wire::#v_name(#value) }
+    }
+    VariantFields::Named(named) => {
+      let fields = named.named.iter().map(|f| {
+        let name = &f.ident;
+        let value = default(&f.ty);
+        quote! { #name: #value }
+      });
+      quote! { #wire::#v_name { #(#fields),* } }
+    }
+    VariantFields::Unit => quote! { #wire::#v_name },
+  }
+}
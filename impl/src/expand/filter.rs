@@ -0,0 +1,70 @@
+//! Subscription filters parsed at runtime (`#[enum_group(filter)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+
+use super::Context;
+
+/// Generates `{Wire}Filter` and `WireMsg::parse_filter()` when requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  if !cx.input.options.filter {
+    return TokenStream2::new();
+  }
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let kind = &cx.kind;
+  let filter = format_ident!("{}Filter", wire);
+
+  let names = cx.variants().map(|(g, v)| {
+    let group = g.name.unraw().to_string();
+    let (variant, cfg) = (v.name.unraw().to_string(), v.cfg());
+    quote! { #cfg (#group, #variant) }
+  });
+
+  let doc = format!(
+    "A set of [`{}`] kinds selected by a filter expression such as `\"Protocol.*, !Protocol.B\"`.",
+    wire
+  );
+
+  quote! {
+      #[doc = #doc]
+      #[derive(
+          ::core::fmt::Debug, ::core::clone::Clone, ::core::cmp::PartialEq, ::core::cmp::Eq,
+          ::core::hash::Hash
+      )]
+      #vis struct #filter {
+          selected: ::std::vec::Vec<bool>,
+      }
+
+      impl #filter {
+          /// Whether `msg` is selected by this filter.
+          #vis fn matches(&self, msg: &#wire) -> bool {
+              self.matches_kind(::enum_group_macros::EnumGroupKind::kind(msg))
+          }
+
+          /// Whether messages of `kind` are selected by this filter.
+          #vis fn matches_kind(&self, kind: #kind) -> bool {
+              self.selected[kind as usize]
+          }
+      }
+
+      impl ::core::str::FromStr for #filter {
+          type Err = ::enum_group_macros::FilterError;
+
+          fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+              let selected = ::enum_group_macros::__private::filter::parse(s, &[#(#names),*])?;
+              ::core::result::Result::Ok(#filter { selected })
+          }
+      }
+
+      impl #wire {
+          /// Parses a subscription filter over the groups and variants of this enum.
+          #vis fn parse_filter(
+              s: &str,
+          ) -> ::core::result::Result<#filter, ::enum_group_macros::FilterError> {
+              s.parse()
+          }
+      }
+  }
+}
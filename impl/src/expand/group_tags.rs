@@ -0,0 +1,40 @@
+//! Zero-sized type-level tags of the groups (`#[enum_group(group_tags)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+
+use super::Context;
+
+/// Generates `{Group}Tag` and its `GroupTag` impl for every group when requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  if !cx.input.options.group_tags {
+    return TokenStream2::new();
+  }
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+
+  // A shared group's tag would be defined once per wire enum
+  let tags = cx.input.groups.iter().filter(|g| !g.shared).map(|g| {
+    let g_name = &g.name;
+    // The tag names its group enum in a public impl, so it can be no more visible
+    let vis = g.vis.as_ref().unwrap_or(vis);
+    let tag = format_ident!("{}Tag", g_name);
+    let (g_ty, link) = (cx.group_ty(g), cx.group_link(g));
+    let doc = format!("Type-level tag of the {} group of [`{}`].", link, wire);
+    quote! {
+        #[doc = #doc]
+        #[derive(
+            ::core::fmt::Debug, ::core::clone::Clone, ::core::marker::Copy,
+            ::core::default::Default, ::core::cmp::PartialEq, ::core::cmp::Eq, ::core::hash::Hash
+        )]
+        #vis struct #tag;
+
+        impl ::enum_group_macros::GroupTag for #tag {
+            type Wire = #wire;
+            type Group = #g_ty;
+        }
+    }
+  });
+
+  quote! { #(#tags)* }
+}
@@ -0,0 +1,426 @@
+//! Handler traits with one method per group (`#[enum_group(handler)]`), one defaulted
+//! method per group (`#[enum_group(partial_handler)]`) or one method per variant
+//! (`#[enum_group(variant_handler)]`) and the matching dispatchers.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{parse_quote, Generics};
+
+use super::Context;
+use crate::parse::{ParsedVariant, VariantFields};
+use crate::util::snake_ident;
+
+/// Generates the group-level handler traits, the partial handler trait and the
+/// variant-level handler trait, each when requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let group_handlers = cx.input.options.handler.then(|| group_handlers(cx));
+  let partial_handler = cx.input.options.partial_handler.then(|| partial_handler(cx));
+  let variant_handler = cx.input.options.variant_handler.then(|| variant_handler(cx));
+  quote! {
+      #group_handlers
+
+      #partial_handler
+
+      #variant_handler
+  }
+}
+
+/// The generics of a handler trait: those of the wire enum, followed by the `Ctx` context
+/// parameter with `handler(context)`.
+fn trait_generics(cx: &Context, context: bool) -> Generics {
+  let mut generics = cx.input.generics.clone();
+  if context {
+    generics.params.push(parse_quote!(Ctx));
+  }
+  generics
+}
+
+/// `{Wire}PartialHandler`, whose group methods default to the required `handle_unhandled`,
+/// and `dispatch_partial`.
+fn partial_handler(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let wire_ty = &cx.wire_ty;
+  let dispatch = &cx.dispatch;
+  let handler = format_ident!("{}PartialHandler", wire);
+  let (impl_generics, impl_where) = cx.impl_generics();
+  let generics = trait_generics(cx, false);
+  let (trait_generics, trait_ty_generics, trait_where) = generics.split_for_impl();
+
+  let groups: Vec<_> = cx.dispatched_groups().map(|g| &g.name).collect();
+  let group_tys: Vec<_> = cx.dispatched_groups().map(|g| cx.group_ty(g)).collect();
+  let methods: Vec<_> =
+    groups.iter().map(|g| format_ident!("handle_{}", snake_ident(g).unraw())).collect();
+  let method_docs: Vec<_> = cx
+    .dispatched_groups()
+    .map(|g| format!("Handles a {} message. Defaults to `handle_unhandled`.", cx.group_link(g)))
+    .collect();
+  let dispatch_expr = cx.dispatch_expr(quote! { self });
+  let msg = format_ident!("msg");
+  let undispatched_arm = cx.undispatched_arm(
+    &msg,
+    quote! {{
+        handler.on_unexpected(&#msg, ::enum_group_macros::UnexpectedReason::SkippedGroup);
+        handler.handle_unhandled(#msg)
+    }},
+  );
+
+  let doc = format!(
+    "Handles [`{}`] messages with one method per group, each defaulting to \
+     `handle_unhandled`, so implementors override only the groups they care about.",
+    wire
+  );
+
+  quote! {
+      #[doc = #doc]
+      #vis trait #handler #trait_generics #trait_where {
+          /// The value every handler method returns.
+          type Output;
+
+          /// Handles a message of a group whose method is not overridden, or that is left
+          /// out of dispatch.
+          fn handle_unhandled(&mut self, msg: #wire_ty) -> Self::Output;
+
+          /// Called with every message before it goes to `handle_unhandled`, together with
+          /// the reason, e.g. for logging or metrics. Defaults to doing nothing.
+          fn on_unexpected(&mut self, msg: &#wire_ty, reason: ::enum_group_macros::UnexpectedReason) {
+              let _ = (msg, reason);
+          }
+
+          #(
+              #[doc = #method_docs]
+              fn #methods(&mut self, msg: #group_tys) -> Self::Output {
+                  let msg: #wire_ty = ::enum_group_macros::GroupOf::into_wire(msg);
+                  self.on_unexpected(&msg, ::enum_group_macros::UnexpectedReason::NoHandler);
+                  self.handle_unhandled(msg)
+              }
+          )*
+      }
+
+      impl #impl_generics #wire_ty #impl_where {
+          /// Hands this message to the method of `handler` for its group.
+          #vis fn dispatch_partial<H: #handler #trait_ty_generics + ?Sized>(
+              self,
+              handler: &mut H,
+          ) -> H::Output {
+              match #dispatch_expr {
+                  #(#dispatch::#groups(group) => handler.#methods(group),)*
+                  #undispatched_arm
+              }
+          }
+      }
+  }
+}
+
+/// The parameters of a variant's handler method, the pattern binding the variant's fields
+/// and the arguments passing them: a payload is passed as `msg`, named fields as one
+/// argument each and unit variants take none.
+fn variant_signature(v: &ParsedVariant) -> (TokenStream2, TokenStream2, TokenStream2) {
+  match &v.fields {
+    VariantFields::Payload { ty, .. } => {
+      (quote! { msg: #ty }, quote! { (payload) }, v.unbox_payload(quote! { payload }))
+    }
+    VariantFields::Named(named) => {
+      let idents: Vec<_> = named.named.iter().map(|f| &f.ident).collect();
+      let tys = named.named.iter().map(|f| &f.ty);
+      let locals: Vec<_> = (0..idents.len()).map(|i| format_ident!("field_{}", i)).collect();
+      (quote! { #(#idents: #tys),* }, quote! { { #(#idents: #locals),* } }, quote! { #(#locals),* })
+    }
+    VariantFields::Unit => (TokenStream2::new(), TokenStream2::new(), TokenStream2::new()),
+  }
+}
+
+/// `{Wire}VariantHandler`, with exactly one required method per variant, and
+/// `dispatch_variant`.
+fn variant_handler(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let wire_ty = &cx.wire_ty;
+  let handler = format_ident!("{}VariantHandler", wire);
+  let (impl_generics, impl_where) = cx.impl_generics();
+  let generics = trait_generics(cx, false);
+  let (trait_generics, trait_ty_generics, trait_where) = generics.split_for_impl();
+
+  let names: Vec<_> = cx.variants().map(|(_, v)| &v.name).collect();
+  let cfgs: Vec<_> = cx.variants().map(|(_, v)| v.cfg()).collect();
+  let signatures: Vec<_> = cx.variants().map(|(_, v)| variant_signature(v)).collect();
+  let params = signatures.iter().map(|(params, _, _)| params);
+  let patterns = signatures.iter().map(|(_, pattern, _)| pattern);
+  let args = signatures.iter().map(|(_, _, args)| args);
+  let methods: Vec<_> =
+    names.iter().map(|v| format_ident!("handle_{}", snake_ident(v).unraw())).collect();
+  let method_docs: Vec<_> = names
+    .iter()
+    .map(|v| format!("Handles the payload of a [`{}::{}`] message.", wire, v))
+    .collect();
+
+  let doc = format!(
+    "Handles [`{}`] messages with one method per variant. The methods have no defaults, so \
+     adding a variant breaks every implementor until it handles the new one.",
+    wire
+  );
+
+  quote! {
+      #[doc = #doc]
+      #vis trait #handler #trait_generics #trait_where {
+          /// The value every handler method returns.
+          type Output;
+
+          #(
+              #cfgs
+              #[doc = #method_docs]
+              fn #methods(&mut self, #params) -> Self::Output;
+          )*
+      }
+
+      impl #impl_generics #wire_ty #impl_where {
+          /// Hands the payload of this message to the method of `handler` for its variant.
+          #vis fn dispatch_variant<H: #handler #trait_ty_generics + ?Sized>(
+              self,
+              handler: &mut H,
+          ) -> H::Output {
+              match self {
+                  #(#cfgs Self::#names #patterns => handler.#methods(#args)),*
+              }
+          }
+      }
+  }
+}
+
+/// `{Wire}Handler`, `{Wire}AsyncHandler`, their fallible `Try` counterparts and the
+/// matching dispatchers.
+fn group_handlers(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let dispatch = &cx.dispatch;
+  let handler = format_ident!("{}Handler", wire);
+  let async_handler = format_ident!("{}AsyncHandler", wire);
+  let try_handler = format_ident!("{}TryHandler", wire);
+  let async_try_handler = format_ident!("{}AsyncTryHandler", wire);
+  let group_kind = &cx.group_kind;
+  let wire_ty = &cx.wire_ty;
+  let (impl_generics, impl_where) = cx.impl_generics();
+
+  let groups: Vec<_> = cx.dispatched_groups().map(|g| &g.name).collect();
+  let group_tys: Vec<_> = cx.dispatched_groups().map(|g| cx.group_ty(g)).collect();
+  let methods: Vec<_> =
+    groups.iter().map(|g| format_ident!("handle_{}", snake_ident(g).unraw())).collect();
+  let method_docs: Vec<_> =
+    cx.dispatched_groups().map(|g| format!("Handles a {} message.", cx.group_link(g))).collect();
+  let dispatch_expr = cx.dispatch_expr(quote! { self });
+
+  // With `handler(context)`, every trait is generic over a context passed by dispatch
+  let context = cx.input.options.handler_context;
+  let generics = trait_generics(cx, context);
+  let (trait_generics, trait_ty_generics, trait_where) = generics.split_for_impl();
+  let ctx_decl = context.then(|| quote! { Ctx, });
+  let ctx_param = context.then(|| quote! { ctx: &mut Ctx, });
+  let ctx_arg = context.then(|| quote! { ctx, });
+
+  // Messages of groups left out of dispatch go to one more method, taking the wire message
+  let undispatched = cx.undispatched().map(|undispatched| {
+    let method = format_ident!("handle_{}", snake_ident(&undispatched).unraw());
+    (undispatched, method)
+  });
+  let undispatched_doc = quote! {
+      /// Handles a message of a group left out of dispatch with `skip_dispatch`.
+  };
+  let undispatched_method = |output: TokenStream2| {
+    undispatched.as_ref().map(|(_, method)| {
+      quote! {
+          #undispatched_doc
+          fn #method(&mut self, #ctx_param msg: #wire_ty) -> #output;
+      }
+    })
+  };
+  let undispatched_sync = undispatched_method(quote! { Self::Output });
+  let undispatched_async = undispatched_method(quote! {
+      impl ::core::future::Future<Output = Self::Output> + Send
+  });
+  let undispatched_try = undispatched_method(quote! {
+      ::core::result::Result<Self::Output, Self::Error>
+  });
+  let undispatched_async_try = undispatched_method(quote! {
+      impl ::core::future::Future<
+          Output = ::core::result::Result<Self::Output, Self::Error>,
+      > + Send
+  });
+  let undispatched_arm = |call: &dyn Fn(&TokenStream2) -> TokenStream2| {
+    undispatched.as_ref().map(|(undispatched, method)| {
+      let call = call(&quote! { handler.#method(#ctx_arg msg) });
+      quote! { #dispatch::#undispatched(msg) => #call, }
+    })
+  };
+  let dispatch_undispatched = undispatched_arm(&|call| quote! { #call });
+  let dispatch_async_undispatched = undispatched_arm(&|call| quote! { #call.await });
+  let try_dispatch_undispatched = undispatched_arm(&|call| {
+    quote! {{
+        let group = ::enum_group_macros::EnumGroupKind::group_kind(&msg);
+        #call.map_err(|e| handler.on_error(group, e))
+    }}
+  });
+  let try_dispatch_async_undispatched = undispatched_arm(&|call| {
+    quote! {{
+        let group = ::enum_group_macros::EnumGroupKind::group_kind(&msg);
+        #call.await.map_err(|e| handler.on_error(group, e))
+    }}
+  });
+
+  let handler_doc = format!("Handles [`{}`] messages with one method per group.", wire);
+  let async_handler_doc = format!("Async counterpart of [`{}`].", handler);
+  let try_handler_doc = format!(
+    "Fallible counterpart of [`{}`]: every method returns `Result<Self::Output, Self::Error>`.",
+    handler
+  );
+  let async_try_handler_doc = format!("Async counterpart of [`{}`].", try_handler);
+  let on_error = quote! {
+      /// Called with every error a handler method returns, together with the group it was
+      /// handling, before the dispatcher returns it. Defaults to returning it unchanged.
+      fn on_error(&mut self, group: #group_kind, error: Self::Error) -> Self::Error {
+          let _ = group;
+          error
+      }
+  };
+
+  quote! {
+      #[doc = #handler_doc]
+      #vis trait #handler #trait_generics #trait_where {
+          /// The value every handler method returns.
+          type Output;
+
+          #(
+              #[doc = #method_docs]
+              fn #methods(&mut self, #ctx_param msg: #group_tys) -> Self::Output;
+          )*
+
+          #undispatched_sync
+      }
+
+      #[doc = #async_handler_doc]
+      #vis trait #async_handler #trait_generics #trait_where {
+          /// The value every handler method resolves to.
+          type Output;
+
+          #(
+              #[doc = #method_docs]
+              fn #methods(
+                  &mut self,
+                  #ctx_param
+                  msg: #group_tys,
+              ) -> impl ::core::future::Future<Output = Self::Output> + Send;
+          )*
+
+          #undispatched_async
+      }
+
+      #[doc = #try_handler_doc]
+      ///
+      /// Group-specific errors convert into the single `Error` type with `?` inside the
+      /// methods; `on_error` sees them all in one place.
+      #vis trait #try_handler #trait_generics #trait_where {
+          /// The value every handler method returns on success.
+          type Output;
+          /// The error type shared by all handler methods.
+          type Error;
+
+          #(
+              #[doc = #method_docs]
+              fn #methods(
+                  &mut self,
+                  #ctx_param
+                  msg: #group_tys,
+              ) -> ::core::result::Result<Self::Output, Self::Error>;
+          )*
+
+          #undispatched_try
+
+          #on_error
+      }
+
+      #[doc = #async_try_handler_doc]
+      #vis trait #async_try_handler #trait_generics #trait_where {
+          /// The value every handler method resolves to on success.
+          type Output;
+          /// The error type shared by all handler methods.
+          type Error;
+
+          #(
+              #[doc = #method_docs]
+              fn #methods(
+                  &mut self,
+                  #ctx_param
+                  msg: #group_tys,
+              ) -> impl ::core::future::Future<
+                  Output = ::core::result::Result<Self::Output, Self::Error>,
+              > + Send;
+          )*
+
+          #undispatched_async_try
+
+          #on_error
+      }
+
+      impl #impl_generics #wire_ty #impl_where {
+          /// Hands this message to the method of `handler` for its group.
+          #vis fn dispatch<#ctx_decl H: #handler #trait_ty_generics + ?Sized>(
+              self,
+              handler: &mut H,
+              #ctx_param
+          ) -> H::Output {
+              match #dispatch_expr {
+                  #(#dispatch::#groups(group) => handler.#methods(#ctx_arg group),)*
+                  #dispatch_undispatched
+              }
+          }
+
+          /// Hands this message to the method of `handler` for its group and awaits it.
+          #vis async fn dispatch_async<#ctx_decl H: #async_handler #trait_ty_generics>(
+              self,
+              handler: &mut H,
+              #ctx_param
+          ) -> H::Output {
+              match #dispatch_expr {
+                  #(#dispatch::#groups(group) => handler.#methods(#ctx_arg group).await,)*
+                  #dispatch_async_undispatched
+              }
+          }
+
+          /// Hands this message to the method of the fallible `handler` for its group,
+          /// passing any error through its `on_error` hook.
+          #vis fn try_dispatch<#ctx_decl H: #try_handler #trait_ty_generics + ?Sized>(
+              self,
+              handler: &mut H,
+              #ctx_param
+          ) -> ::core::result::Result<H::Output, H::Error> {
+              match #dispatch_expr {
+                  #(
+                      #dispatch::#groups(group) => handler
+                          .#methods(#ctx_arg group)
+                          .map_err(|e| handler.on_error(#group_kind::#groups, e)),
+                  )*
+                  #try_dispatch_undispatched
+              }
+          }
+
+          /// Async counterpart of `try_dispatch`.
+          #vis async fn try_dispatch_async<#ctx_decl H: #async_try_handler #trait_ty_generics>(
+              self,
+              handler: &mut H,
+              #ctx_param
+          ) -> ::core::result::Result<H::Output, H::Error> {
+              match #dispatch_expr {
+                  #(
+                      #dispatch::#groups(group) => match handler.#methods(#ctx_arg group).await {
+                          ::core::result::Result::Err(e) => {
+                              ::core::result::Result::Err(handler.on_error(#group_kind::#groups, e))
+                          }
+                          ok => ok,
+                      },
+                  )*
+                  #try_dispatch_async_undispatched
+              }
+          }
+      }
+  }
+}
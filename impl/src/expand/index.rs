@@ -0,0 +1,80 @@
+//! Branch-free group lookup (`#[enum_group(group_index_table)]`).
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Ident, Meta, Token};
+
+use super::Context;
+
+const INT_REPRS: &[&str] =
+  &["u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize"];
+
+/// The integer representation of the wire enum, required by `group_index_table` to read the
+/// discriminant without matching. `Ok(None)` when the option is not set.
+pub(crate) fn wire_repr(
+  cx: &Context,
+  wire_attrs: &[Attribute],
+  wire_extra: &[Meta],
+) -> syn::Result<Option<Ident>> {
+  let Some(span) = cx.input.options.group_index_table else {
+    return Ok(None);
+  };
+  let metas = wire_attrs.iter().map(|attr| &attr.meta).chain(wire_extra);
+  for meta in metas.filter(|meta| meta.path().is_ident("repr")) {
+    let Meta::List(list) = meta else { continue };
+    let args = list.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?;
+    if let Some(repr) = args.into_iter().find(|arg| INT_REPRS.iter().any(|int| arg == int)) {
+      return Ok(Some(repr));
+    }
+  }
+  Err(syn::Error::new(
+    span,
+    "`group_index_table` requires an integer representation such as `#[repr(u16)]` on the \
+     wire enum",
+  ))
+}
+
+/// Generates `GROUP_INDEX_TABLE` and `group_index_fast()` when requested.
+pub(crate) fn generate(cx: &Context, repr: Option<&Ident>) -> TokenStream2 {
+  let Some(repr) = repr else {
+    return TokenStream2::new();
+  };
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+
+  let index_ty = if cx.input.groups.len() <= usize::from(u8::MAX) + 1 {
+    Ident::new("u8", Span::call_site())
+  } else {
+    Ident::new("u16", Span::call_site())
+  };
+  let entries = cx.input.groups.iter().enumerate().flat_map(|(index, g)| {
+    let index = proc_macro2::Literal::usize_unsuffixed(index);
+    g.variants.iter().map(move |_| index.clone())
+  });
+  let len = cx.variants().count();
+
+  quote! {
+      impl #wire {
+          /// Index of the group of every variant, in declaration order like
+          /// `GroupKind::ALL`, indexed by the discriminant of the variant.
+          #vis const GROUP_INDEX_TABLE: [#index_ty; #len] = [#(#entries),*];
+
+          /// Returns the index of this message's group in declaration order, like
+          /// `GroupKind::ALL`, by reading the discriminant and looking it up in
+          /// [`Self::GROUP_INDEX_TABLE`] instead of matching on the variant.
+          ///
+          /// Only worth it in the hottest routing loops; prefer `group_kind()` elsewhere.
+          #[inline]
+          #vis fn group_index_fast(&self) -> usize {
+              // SAFETY: the wire enum has a primitive representation, so it starts with its
+              // discriminant (RFC 2195), and the discriminants are the variant indices
+              // `0..GROUP_INDEX_TABLE.len()` assigned by the macro.
+              unsafe {
+                  let discriminant = *(self as *const Self).cast::<#repr>();
+                  *Self::GROUP_INDEX_TABLE.get_unchecked(discriminant as usize) as usize
+              }
+          }
+      }
+  }
+}
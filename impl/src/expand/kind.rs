@@ -0,0 +1,274 @@
+//! Fieldless kind enums identifying variants and groups without their payloads.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+
+use super::{python, Context};
+use crate::util::{doc_comments, snake_ident};
+
+/// Generates `{Wire}Kind`, `{Wire}GroupKind`, the static mapping between them, the group
+/// constants, the variant and group names, the group predicates, the `EnumGroupKind` impl,
+/// the comparisons between messages and kinds and, when requested, the `Display` impl.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let wire_ty = &cx.wire_ty;
+  let (impl_generics, where_clause) = cx.impl_generics();
+  let kind = &cx.kind;
+  let group_kind = &cx.group_kind;
+  let kind_extra = &cx.input.options.extra_attrs.kind;
+  let group_kind_extra = &cx.input.options.extra_attrs.group_kind;
+  let pyclass = python::pyclass_attr(cx);
+
+  let kind_variants: Vec<_> = cx.variants().map(|(_, v)| &v.name).collect();
+  let kind_cfgs: Vec<_> = cx.variants().map(|(_, v)| v.cfg()).collect();
+  let group_variants: Vec<_> = cx.input.groups.iter().map(|g| &g.name).collect();
+  // The kinds carry the doc comments of what they identify
+  let kind_docs: Vec<_> = cx.variants().map(|(_, v)| doc_comments(&v.attrs)).collect();
+  let group_kind_docs: Vec<_> = cx.input.groups.iter().map(|g| doc_comments(&g.attrs)).collect();
+
+  let group_of_kind_arms = cx.variants().map(|(g, v)| {
+    let (v_name, cfg) = (&v.name, v.cfg());
+    let g_name = &g.name;
+    quote! { #cfg #kind::#v_name => #group_kind::#g_name }
+  });
+
+  let kind_arms = cx.variants().map(|(_, v)| {
+    let (v_name, cfg) = (&v.name, v.cfg());
+    quote! { #cfg Self::#v_name { .. } => #kind::#v_name }
+  });
+
+  let kind_names = cx.variants().map(|(_, v)| {
+    let (v_name, cfg) = (&v.name, v.cfg());
+    let name = v_name.unraw().to_string();
+    quote! { #cfg #kind::#v_name => #name }
+  });
+
+  // Only the names are printed, so payloads need not implement `Display`
+  let display = cx.input.options.display.then(|| {
+    quote! {
+        impl #impl_generics ::core::fmt::Display for #wire_ty #where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::core::write!(f, "{}::{}", self.group_name(), self.variant_name())
+            }
+        }
+    }
+  });
+
+  // A shared group's method would be defined once per wire enum
+  let group_enums = cx.input.groups.iter().filter(|g| !g.shared).flat_map(|g| {
+    let top = (cx.group_ty(g), cx.group_generics(g), g, 0, g.variants.iter().collect());
+    let nested = g
+      .nested_groups()
+      .into_iter()
+      .map(move |n| (cx.nested_ty(g, &n), cx.nested_generics(&n), g, n.depth, n.variants));
+    std::iter::once(top).chain(nested)
+  });
+  let group_variant_names =
+    group_enums.map(|(g_ty, generics, g, depth, variants): (_, _, _, _, Vec<_>)| {
+      let (impl_generics, _, where_clause) = generics.split_for_impl();
+      let module = cx.groups_module(g);
+      let arms = variants.iter().map(|v| {
+        let pattern = g.wrap_variant(v, depth, module, quote! { { .. } });
+        let (name, cfg) = (v.name.unraw().to_string(), v.cfg());
+        quote! { #cfg #pattern => #name }
+      });
+      quote! {
+          impl #impl_generics #g_ty #where_clause {
+              /// Returns the name of this message's variant of the wire enum, as declared.
+              #vis fn variant_name(&self) -> &'static str {
+                  match *self {
+                      #(#arms),*
+                  }
+              }
+          }
+      }
+    });
+
+  let variant_names: Vec<_> = cx
+    .variants()
+    .map(|(_, v)| {
+      let (name, cfg) = (v.name.unraw().to_string(), v.cfg());
+      quote! { #cfg #name }
+    })
+    .collect();
+  // Counted through a slice, which leaves out the variants configured away
+  let variant_count = quote! {
+      {
+          let names: &[&str] = &[#(#variant_names),*];
+          names.len()
+      }
+  };
+
+  let group_predicates = cx.input.groups.iter().map(|g| {
+    let g_name = &g.name;
+    let method = format_ident!("is_{}", snake_ident(g_name).unraw());
+    let doc = format!("Whether this message belongs to the {} group.", cx.group_link(g));
+    quote! {
+        #[doc = #doc]
+        #vis fn #method(&self) -> bool {
+            ::enum_group_macros::EnumGroupKind::group_kind(self) == #group_kind::#g_name
+        }
+    }
+  });
+
+  let group_count = group_variants.len();
+  let group_names: Vec<_> = group_variants.iter().map(|g| g.unraw().to_string()).collect();
+
+  let group_variants_arms = cx.input.groups.iter().map(|g| {
+    let g_name = &g.name;
+    let members = g.variants.iter().map(|v| {
+      let (v_name, cfg) = (&v.name, v.cfg());
+      quote! { #cfg #kind::#v_name }
+    });
+    quote! { #group_kind::#g_name => &[#(#members),*] }
+  });
+
+  let kind_doc = format!("Fieldless identifier of a [`{}`] variant.", wire);
+  let group_kind_doc = format!("Fieldless identifier of a [`{}`] group.", wire);
+
+  quote! {
+      #[doc = #kind_doc]
+      #[derive(
+          ::core::fmt::Debug, ::core::clone::Clone, ::core::marker::Copy, ::core::cmp::PartialEq,
+          ::core::cmp::Eq, ::core::cmp::PartialOrd, ::core::cmp::Ord, ::core::hash::Hash
+      )]
+      #(#[#kind_extra])*
+      #pyclass
+      #vis enum #kind {
+          #(#kind_cfgs #(#kind_docs)* #kind_variants),*
+      }
+
+      impl #kind {
+          /// All variants, in declaration order.
+          #vis const ALL: &'static [#kind] = &[#(#kind_cfgs #kind::#kind_variants),*];
+
+          /// Iterates over all variants, in declaration order.
+          #vis fn iter() -> ::core::iter::Copied<::core::slice::Iter<'static, #kind>> {
+              Self::ALL.iter().copied()
+          }
+
+          /// Returns the name of this variant, as declared.
+          #vis const fn name(self) -> &'static str {
+              match self {
+                  #(#kind_names),*
+              }
+          }
+      }
+
+      #[doc = #group_kind_doc]
+      #[derive(
+          ::core::fmt::Debug, ::core::clone::Clone, ::core::marker::Copy, ::core::cmp::PartialEq,
+          ::core::cmp::Eq, ::core::cmp::PartialOrd, ::core::cmp::Ord, ::core::hash::Hash
+      )]
+      #(#[#group_kind_extra])*
+      #pyclass
+      #vis enum #group_kind {
+          #(#(#group_kind_docs)* #group_variants),*
+      }
+
+      impl #group_kind {
+          /// All groups, in declaration order.
+          #vis const ALL: &'static [#group_kind] = &[#(#group_kind::#group_variants),*];
+
+          /// Iterates over all groups, in declaration order.
+          #vis fn iter() -> ::core::iter::Copied<::core::slice::Iter<'static, #group_kind>> {
+              Self::ALL.iter().copied()
+          }
+
+          /// Returns the name of this group, as declared.
+          #vis const fn name(self) -> &'static str {
+              match self {
+                  #(#group_kind::#group_variants => #group_names),*
+              }
+          }
+
+          /// Returns the kinds of all variants in this group, in declaration order.
+          #vis const fn variants(self) -> &'static [#kind] {
+              match self {
+                  #(#group_variants_arms),*
+              }
+          }
+      }
+
+      impl #impl_generics #wire_ty #where_clause {
+          /// Number of variants.
+          #vis const VARIANT_COUNT: usize = #variant_count;
+
+          /// The names of all variants, in declaration order.
+          #vis const VARIANT_NAMES: [&'static str; #variant_count] = [#(#variant_names),*];
+
+          /// Number of groups.
+          #vis const GROUP_COUNT: usize = #group_count;
+
+          /// The names of all groups, in declaration order.
+          #vis const GROUP_NAMES: [&'static str; #group_count] = [#(#group_names),*];
+
+          /// Returns the names of all groups, in declaration order.
+          #vis const fn group_names() -> &'static [&'static str] {
+              &[#(#group_names),*]
+          }
+
+          /// Returns the group that variants of the given kind belong to.
+          #vis const fn group_of_kind(kind: #kind) -> #group_kind {
+              match kind {
+                  #(#group_of_kind_arms),*
+              }
+          }
+
+          /// Returns the kind of this message's variant.
+          #vis fn kind(&self) -> #kind {
+              match *self {
+                  #(#kind_arms),*
+              }
+          }
+
+          /// Returns the kind of the group this message belongs to.
+          #vis fn group_kind(&self) -> #group_kind {
+              Self::group_of_kind(self.kind())
+          }
+
+          /// Returns the name of this message's variant, as declared.
+          #vis fn variant_name(&self) -> &'static str {
+              self.kind().name()
+          }
+
+          /// Returns the name of the group this message belongs to, as declared.
+          #vis fn group_name(&self) -> &'static str {
+              self.group_kind().name()
+          }
+
+          #(#group_predicates)*
+      }
+
+      impl #impl_generics ::enum_group_macros::EnumGroupKind for #wire_ty #where_clause {
+          type Kind = #kind;
+          type GroupKind = #group_kind;
+
+          fn kind(&self) -> #kind {
+              #wire::kind(self)
+          }
+
+          fn group_kind(&self) -> #group_kind {
+              #wire::group_kind(self)
+          }
+      }
+
+      impl #impl_generics ::core::cmp::PartialEq<#kind> for #wire_ty #where_clause {
+          fn eq(&self, kind: &#kind) -> bool {
+              self.kind() == *kind
+          }
+      }
+
+      impl #impl_generics ::core::cmp::PartialEq<#wire_ty> for #kind #where_clause {
+          fn eq(&self, msg: &#wire_ty) -> bool {
+              *self == msg.kind()
+          }
+      }
+
+      #(#group_variant_names)*
+
+      #display
+  }
+}
@@ -0,0 +1,108 @@
+//! Array-backed maps keyed by variant kind (`#[enum_group(kind_map)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+
+use super::Context;
+
+/// Generates `{Wire}KindMap<V>` when requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  if !cx.input.options.kind_map {
+    return TokenStream2::new();
+  }
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let kind = &cx.kind;
+  let map = format_ident!("{}KindMap", wire);
+
+  let kinds: Vec<_> = cx.variants().map(|(_, v)| &v.name).collect();
+  let cfgs: Vec<_> = cx.variants().map(|(_, v)| v.cfg()).collect();
+  // Counted by the compiler, after the variants configured out are gone
+  let len = quote! { [#(#cfgs ()),*].len() };
+
+  let doc =
+    format!("A map from [`{}`] to `V`, stored inline in an array indexed by the variant.", kind);
+
+  quote! {
+      #[doc = #doc]
+      #[derive(::core::clone::Clone, ::core::cmp::PartialEq, ::core::cmp::Eq)]
+      #vis struct #map<V> {
+          entries: [::core::option::Option<V>; #len],
+      }
+
+      impl<V> ::core::default::Default for #map<V> {
+          fn default() -> Self {
+              Self::new()
+          }
+      }
+
+      impl<V> #map<V> {
+          /// Kinds in declaration order, which is the index of their entry.
+          const KINDS: [#kind; #len] = [#(#cfgs #kind::#kinds),*];
+
+          /// Creates an empty map.
+          #vis fn new() -> Self {
+              Self { entries: ::core::array::from_fn(|_| ::core::option::Option::None) }
+          }
+
+          /// Returns the value stored for `kind`, if any.
+          #vis fn get(&self, kind: #kind) -> ::core::option::Option<&V> {
+              self.entries[kind as usize].as_ref()
+          }
+
+          /// Returns the value stored for `kind` mutably, if any.
+          #vis fn get_mut(&mut self, kind: #kind) -> ::core::option::Option<&mut V> {
+              self.entries[kind as usize].as_mut()
+          }
+
+          /// Stores `value` for `kind`, returning the value it replaces.
+          #vis fn insert(&mut self, kind: #kind, value: V) -> ::core::option::Option<V> {
+              self.entries[kind as usize].replace(value)
+          }
+
+          /// Removes and returns the value stored for `kind`, if any.
+          #vis fn remove(&mut self, kind: #kind) -> ::core::option::Option<V> {
+              self.entries[kind as usize].take()
+          }
+
+          /// Whether a value is stored for `kind`.
+          #vis fn contains_key(&self, kind: #kind) -> bool {
+              self.entries[kind as usize].is_some()
+          }
+
+          /// Number of kinds with a stored value.
+          #vis fn len(&self) -> usize {
+              self.entries.iter().filter(|entry| entry.is_some()).count()
+          }
+
+          /// Whether no value is stored.
+          #vis fn is_empty(&self) -> bool {
+              self.entries.iter().all(::core::option::Option::is_none)
+          }
+
+          /// Iterates over the stored values with their kinds, in declaration order.
+          #vis fn iter(&self) -> impl ::core::iter::Iterator<Item = (#kind, &V)> + '_ {
+              Self::KINDS
+                  .iter()
+                  .zip(self.entries.iter())
+                  .filter_map(|(kind, entry)| entry.as_ref().map(|value| (*kind, value)))
+          }
+      }
+
+      impl<V: ::core::fmt::Debug> ::core::fmt::Debug for #map<V> {
+          fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+              f.debug_map().entries(self.iter()).finish()
+          }
+      }
+
+      impl<V> ::core::iter::FromIterator<(#kind, V)> for #map<V> {
+          fn from_iter<I: ::core::iter::IntoIterator<Item = (#kind, V)>>(iter: I) -> Self {
+              let mut map = Self::new();
+              for (kind, value) in iter {
+                  map.insert(kind, value);
+              }
+              map
+          }
+      }
+  }
+}
@@ -0,0 +1,40 @@
+//! Normalization of variants superseded through `#[variant(legacy_of = ...)]`.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use super::Context;
+
+/// Generates `WireMsg::normalize()` when at least one variant is marked `legacy_of`.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let wire_ty = &cx.wire_ty;
+  let (impl_generics, where_clause) = cx.impl_generics();
+
+  let arms: Vec<_> = cx
+    .variants()
+    .filter_map(|(_, v)| {
+      let (_, target) = cx.legacy_target(v)?;
+      let (v_name, cfg) = (&v.name, v.cfg());
+      let converted = cx.legacy_value(v, target);
+      let target = &target.name;
+      Some(quote! { #cfg Self::#v_name(v) => Self::#target(#converted) })
+    })
+    .collect();
+  if arms.is_empty() {
+    return TokenStream2::new();
+  }
+
+  quote! {
+      impl #impl_generics #wire_ty #where_clause {
+          /// Converts a legacy variant into the variant superseding it; other messages are
+          /// returned unchanged. Grouping a message performs the same conversion.
+          #vis fn normalize(self) -> Self {
+              match self {
+                  #(#arms,)*
+                  other => other,
+              }
+          }
+      }
+  }
+}
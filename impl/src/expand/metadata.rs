@@ -0,0 +1,135 @@
+//! Static per-variant metadata (`METADATA`) and the accessors built on top of it.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use super::Context;
+
+/// Generates `WireMsg::METADATA`, `metadata(&self)` and, when any variant or group sets
+/// the corresponding options, the `timeout` / `max_retries`, `requires_auth` /
+/// `required_role` and `meta` accessors.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let wire_ty = &cx.wire_ty;
+  let (impl_generics, where_clause) = cx.impl_generics();
+
+  let entries = cx.variants().map(|(g, v)| {
+    let name = v.name.to_string();
+    let group = g.name.to_string();
+    let tag = cx.tag(v);
+    let timeout_ms = match v.options.timeout_ms {
+      Some(ms) => quote! { ::core::option::Option::Some(#ms) },
+      None => quote! { ::core::option::Option::None },
+    };
+    let max_retries = v.options.retries.unwrap_or(0);
+    let requires_auth = g.options.requires_auth;
+    let required_role = match &g.options.role {
+      Some(role) => quote! { ::core::option::Option::Some(#role) },
+      None => quote! { ::core::option::Option::None },
+    };
+    // Variant entries win over group entries with the same key
+    let group_meta = g
+      .options
+      .meta
+      .iter()
+      .filter(|gm| !v.options.meta.iter().any(|vm| vm.key.value() == gm.key.value()));
+    let meta = v.options.meta.iter().chain(group_meta).map(|m| {
+      let (key, value) = (&m.key, &m.value);
+      quote! { (#key, #value) }
+    });
+    let cfg = v.cfg();
+    quote! {
+        #cfg
+        ::enum_group_macros::VariantMetadata {
+            name: #name,
+            group: #group,
+            tag: #tag,
+            timeout_ms: #timeout_ms,
+            max_retries: #max_retries,
+            requires_auth: #requires_auth,
+            required_role: #required_role,
+            meta: &[#(#meta),*],
+        }
+    }
+  });
+
+  // Indexed by kind, which skips the variants configured out just like the table does
+  let kind = &cx.kind;
+  let index_arms = cx.variants().map(|(_, v)| {
+    let (v_name, cfg) = (&v.name, v.cfg());
+    quote! { #cfg Self::#v_name { .. } => &Self::METADATA[#kind::#v_name as usize] }
+  });
+
+  let uses_retry_options =
+    cx.variants().any(|(_, v)| v.options.timeout_ms.is_some() || v.options.retries.is_some());
+  let retry_accessors = uses_retry_options.then(|| {
+    quote! {
+        /// The `#[variant(timeout_ms = ...)]` of this message's variant, if any.
+        #vis fn timeout(&self) -> ::core::option::Option<::core::time::Duration> {
+            match self.metadata().timeout_ms {
+                ::core::option::Option::Some(ms) => {
+                    ::core::option::Option::Some(::core::time::Duration::from_millis(ms))
+                }
+                ::core::option::Option::None => ::core::option::Option::None,
+            }
+        }
+
+        /// The `#[variant(retries = ...)]` of this message's variant, or `0`.
+        #vis fn max_retries(&self) -> u32 {
+            self.metadata().max_retries
+        }
+    }
+  });
+
+  let uses_auth_options = cx.input.groups.iter().any(|g| g.options.requires_auth);
+  let auth_accessors = uses_auth_options.then(|| {
+    quote! {
+        /// Whether this message's group has `#[group(requires_auth)]` or a required role.
+        #vis fn requires_auth(&self) -> bool {
+            self.metadata().requires_auth
+        }
+
+        /// The `#[group(role = ...)]` of this message's group, if any.
+        #vis fn required_role(&self) -> ::core::option::Option<&'static str> {
+            self.metadata().required_role
+        }
+    }
+  });
+
+  let uses_meta = cx
+    .input
+    .groups
+    .iter()
+    .any(|g| !g.options.meta.is_empty() || g.variants.iter().any(|v| !v.options.meta.is_empty()));
+  let meta_accessor = uses_meta.then(|| {
+    quote! {
+        /// Looks up a `#[meta(key = ..., value = ...)]` value of this message's variant,
+        /// falling back to its group.
+        #vis fn meta(&self, key: &str) -> ::core::option::Option<&'static str> {
+            self.metadata().meta(key)
+        }
+    }
+  });
+
+  quote! {
+      impl #impl_generics #wire_ty #where_clause {
+          /// Static metadata of every variant, in declaration order.
+          #vis const METADATA: &'static [::enum_group_macros::VariantMetadata] = &[
+              #(#entries),*
+          ];
+
+          /// Static metadata of this message's variant.
+          #vis fn metadata(&self) -> &'static ::enum_group_macros::VariantMetadata {
+              match *self {
+                  #(#index_arms),*
+              }
+          }
+
+          #retry_accessors
+
+          #auth_accessors
+
+          #meta_accessor
+      }
+  }
+}
@@ -0,0 +1,953 @@
+//! Code generator for `define_enum_group!`.
+//!
+//! The core of the expansion (group enums, wire enum, dispatch enum and the `into_group`
+//! conversion) lives here; optional or auxiliary items are generated by the submodules.
+
+mod accessors;
+mod avro;
+mod borrow;
+mod by_tag;
+mod closures;
+mod convert;
+mod discriminant;
+mod examples;
+mod filter;
+mod group_tags;
+mod handler;
+mod index;
+mod kind;
+mod kind_map;
+mod legacy;
+mod metadata;
+mod modules;
+mod numeric_tags;
+mod pair;
+mod payload_any;
+mod peek;
+mod python;
+mod queues;
+mod router;
+mod sink;
+mod tags;
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{Attribute, Generics, Ident, ImplGenerics, Item, Type, Visibility, WhereClause};
+
+use crate::options::{EnumGroupOptions, ExtraAttrs, Targets};
+use crate::parse::{
+  DefineEnumGroups, EnumGroupInput, NestedGroup, ParsedGroup, ParsedVariant, SharedGroup,
+};
+use crate::serde_attrs::SerdeContainer;
+use crate::util::{
+  derive_path, doc_cfg, doc_comments, has_derive, nested_visibility, used_generics, without_derive,
+};
+
+pub(crate) use pair::generate_enum_group_pair;
+
+/// Names and input shared by all generators of one `define_enum_group!` invocation.
+pub(crate) struct Context<'a> {
+  pub(crate) input: &'a EnumGroupInput,
+  /// The flat wire enum, e.g. `WireMsg`.
+  pub(crate) wire: &'a Ident,
+  /// The wire enum as a type, with the arguments of its generics, e.g. `WireMsg<T>`.
+  pub(crate) wire_ty: TokenStream2,
+  /// The dispatch enum, e.g. `WireMsgGroup`.
+  pub(crate) dispatch: Ident,
+  /// The dispatch enum as a type, e.g. `WireMsgGroup<T>`.
+  pub(crate) dispatch_ty: TokenStream2,
+  /// The fieldless variant kind enum, e.g. `WireMsgKind`.
+  pub(crate) kind: Ident,
+  /// The fieldless group kind enum, e.g. `WireMsgGroupKind`.
+  pub(crate) group_kind: Ident,
+  /// Container-level serde settings of the wire enum.
+  pub(crate) serde: SerdeContainer,
+}
+
+impl<'a> Context<'a> {
+  fn new(input: &'a EnumGroupInput) -> Self {
+    let wire = &input.name;
+    let dispatch = input.dispatch_name();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    Context {
+      input,
+      wire,
+      wire_ty: quote! { #wire #ty_generics },
+      dispatch_ty: quote! { #dispatch #ty_generics },
+      dispatch,
+      kind: format_ident!("{}Kind", wire),
+      group_kind: format_ident!("{}GroupKind", wire),
+      serde: SerdeContainer::from_attrs(&input.attrs),
+    }
+  }
+
+  /// `impl<...>` and the where clause of impls on the wire or the dispatch enum.
+  pub(crate) fn impl_generics(&self) -> (ImplGenerics<'a>, Option<&'a WhereClause>) {
+    let (impl_generics, _, where_clause) = self.input.generics.split_for_impl();
+    (impl_generics, where_clause)
+  }
+
+  /// The generics of the wire enum that the variants of `group` use. Shared groups are
+  /// never generic.
+  pub(crate) fn group_generics(&self, group: &ParsedGroup) -> Generics {
+    if group.shared {
+      return Generics::default();
+    }
+    self.variants_generics(&group.variants)
+  }
+
+  /// The generics of the wire enum that `variants` use.
+  fn variants_generics<'v>(
+    &self,
+    variants: impl IntoIterator<Item = &'v ParsedVariant>,
+  ) -> Generics {
+    used_generics(&self.input.generics, variants.into_iter().flat_map(ParsedVariant::field_types))
+  }
+
+  /// The module the enum of `group` is generated in, if any: the one of `groups_module`,
+  /// which shared groups are not generated in.
+  pub(crate) fn groups_module(&self, group: &ParsedGroup) -> Option<&'a Ident> {
+    self.input.options.groups_module.as_ref().filter(|_| !group.shared)
+  }
+
+  /// The enum of a nested group of `group` as a type, e.g. `Handshake<T>`.
+  pub(crate) fn nested_ty(&self, group: &ParsedGroup, nested: &NestedGroup) -> TokenStream2 {
+    let module = self.groups_module(group).map(|m| quote! { #m:: });
+    let name = nested.name;
+    let generics = self.nested_generics(nested);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    quote! { #module #name #ty_generics }
+  }
+
+  /// The generics of the wire enum that the variants of a nested group use.
+  pub(crate) fn nested_generics(&self, nested: &NestedGroup) -> Generics {
+    self.variants_generics(nested.variants.iter().copied())
+  }
+
+  /// A rustdoc link to the group enum of `group`, "[`Protocol`]", through its module if
+  /// any.
+  pub(crate) fn group_link(&self, group: &ParsedGroup) -> String {
+    match self.groups_module(group) {
+      Some(module) => format!("[`{}`]({}::{})", group.name, module, group.name),
+      None => format!("[`{}`]", group.name),
+    }
+  }
+
+  /// The group enum of `group` as a type, e.g. `Protocol<T>` or `groups::Protocol<T>`.
+  pub(crate) fn group_ty(&self, group: &ParsedGroup) -> TokenStream2 {
+    let module = self.groups_module(group).map(|m| quote! { #m:: });
+    let name = &group.name;
+    let generics = self.group_generics(group);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    quote! { #module #name #ty_generics }
+  }
+
+  /// Every variant of the wire enum in declaration order, paired with its group.
+  pub(crate) fn variants(&self) -> impl Iterator<Item = (&'a ParsedGroup, &'a ParsedVariant)> {
+    self.input.groups.iter().flat_map(|g| g.variants.iter().map(move |v| (g, v)))
+  }
+
+  /// The groups that appear in the dispatch enum, i.e. all but the `skip_dispatch` ones.
+  pub(crate) fn dispatched_groups(&self) -> impl Iterator<Item = &'a ParsedGroup> {
+    self.input.groups.iter().filter(|g| !g.options.skip_dispatch)
+  }
+
+  /// The dispatch enum variant wrapping messages of `skip_dispatch` groups, if there are
+  /// any.
+  pub(crate) fn undispatched(&self) -> Option<Ident> {
+    let skipped = self.input.groups.iter().any(|g| g.options.skip_dispatch);
+    skipped.then(|| format_ident!("Undispatched"))
+  }
+
+  /// The match arm of the dispatch enum's `Undispatched` variant, binding the message to
+  /// `msg`, if there is one.
+  pub(crate) fn undispatched_arm(&self, msg: &Ident, body: TokenStream2) -> TokenStream2 {
+    let dispatch = &self.dispatch;
+    match self.undispatched() {
+      Some(undispatched) => quote! { #dispatch::#undispatched(#msg) => #body, },
+      None => TokenStream2::new(),
+    }
+  }
+
+  /// The catch-all variant of the `#[unknown]` group, if there is one.
+  pub(crate) fn unknown(&self) -> Option<&'a ParsedVariant> {
+    self.input.groups.iter().find(|g| g.unknown).map(|g| &g.variants[0])
+  }
+
+  /// The variants serialized under a tag of their own, i.e. all but the `#[unknown]`
+  /// catch-all, paired with their group.
+  pub(crate) fn tagged_variants(
+    &self,
+  ) -> impl Iterator<Item = (&'a ParsedGroup, &'a ParsedVariant)> {
+    self.variants().filter(|(g, _)| !g.unknown)
+  }
+
+  /// The variant named `name`, paired with its group.
+  pub(crate) fn variant_named(&self, name: &Ident) -> Option<(&'a ParsedGroup, &'a ParsedVariant)> {
+    self.variants().find(|(_, v)| v.name == *name)
+  }
+
+  /// The replacement of `variant` if it is marked `legacy_of`, paired with its group.
+  pub(crate) fn legacy_target(
+    &self,
+    variant: &ParsedVariant,
+  ) -> Option<(&'a ParsedGroup, &'a ParsedVariant)> {
+    variant.options.legacy_of.as_ref().and_then(|target| self.variant_named(target))
+  }
+
+  /// The payload of the `target` of the legacy `variant`, converted with `From` from the
+  /// payload bound to `v`.
+  pub(crate) fn legacy_value(
+    &self,
+    variant: &ParsedVariant,
+    target: &ParsedVariant,
+  ) -> TokenStream2 {
+    let payload = variant.unbox_payload(quote! { v });
+    target.box_payload(quote! { ::core::convert::From::from(#payload) })
+  }
+
+  /// Whether the payload type of `variant` backs no other variant, so that conversions
+  /// keyed on the payload type are unambiguous.
+  pub(crate) fn has_unique_payload(&self, variant: &ParsedVariant) -> bool {
+    let payload = |v: &ParsedVariant| v.payload().map(|ty| ty.to_token_stream().to_string());
+    let Some(ty) = payload(variant) else {
+      return false;
+    };
+    self.variants().filter(|(_, v)| payload(v).as_ref() == Some(&ty)).count() == 1
+  }
+
+  /// A call converting the wire message `msg` into the dispatch enum, through the inherent
+  /// method or, if that is skipped, the `EnumGroup` trait.
+  pub(crate) fn dispatch_expr(&self, msg: TokenStream2) -> TokenStream2 {
+    let wire = self.wire;
+    match (&self.input.options.method, self.input.options.skip_inherent) {
+      (_, true) => quote! { <#wire as ::enum_group_macros::EnumGroup>::into_group(#msg) },
+      (Some(method), false) => quote! { #wire::#method(#msg) },
+      (None, false) => quote! { #wire::into_group(#msg) },
+    }
+  }
+
+  /// `#[doc(alias = ...)]` attributes letting rustdoc search find `variant` by its serde
+  /// tag, on the wire enum by the name of its group and on a group enum renaming it by its
+  /// wire name. Aliases equal to the variant name or containing characters rustdoc rejects
+  /// are left out.
+  fn doc_aliases(&self, group: Option<&ParsedGroup>, variant: &ParsedVariant) -> TokenStream2 {
+    let (name, other) = match group {
+      Some(group) => (&variant.name, &group.name),
+      None => (variant.group_ident(), &variant.name),
+    };
+    let name = name.to_string();
+    let tag = self.tag(variant);
+    let mut aliases: Vec<String> = Vec::new();
+    for alias in [tag, other.to_string()] {
+      let valid =
+        !alias.is_empty() && !alias.chars().any(|c| c.is_whitespace() || c == '"' || c == '\'');
+      if valid && alias != name && !aliases.contains(&alias) {
+        aliases.push(alias);
+      }
+    }
+    quote! { #(#[doc(alias = #aliases)])* }
+  }
+
+  /// Rejects serde layouts that compile but fail at runtime: payloads internally tagged
+  /// enums cannot hold, and untagged variants that could never be deserialized because an
+  /// earlier variant has the same payload type.
+  fn check_serde_layout(&self, wire_attrs: &[Attribute]) -> syn::Result<()> {
+    if self.serde.internally_tagged() {
+      // Named fields serialize as a map
+      let mut payloads = self.variants().filter_map(|(_, v)| v.payload());
+      if let Some(ty) = payloads.find(|ty| !holds_map(ty)) {
+        return Err(syn::Error::new_spanned(
+          ty,
+          "internally tagged enums (`#[serde(tag = \"...\")]` without `content`) can only \
+           hold struct, map or unit payloads; add `content = \"...\"` to tag it adjacently",
+        ));
+      }
+    }
+    if self.serde.untagged && has_derive(wire_attrs, "Deserialize") {
+      let variants: Vec<_> = self.variants().filter_map(|(_, v)| Some((v, v.payload()?))).collect();
+      for (i, (v, ty)) in variants.iter().enumerate() {
+        let key = ty.to_token_stream().to_string();
+        if let Some((earlier, _)) =
+          variants[..i].iter().find(|(_, e)| e.to_token_stream().to_string() == key)
+        {
+          return Err(syn::Error::new_spanned(
+            ty,
+            format!(
+              "untagged enums deserialize into the first variant that fits, so `{}` is \
+               unreachable: `{}` has the same payload type",
+              v.name, earlier.name,
+            ),
+          ));
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// The serde tag `variant` is serialized under.
+  pub(crate) fn tag(&self, variant: &ParsedVariant) -> String {
+    self.serde.variant_tag(&variant.name.to_string(), &variant.attrs)
+  }
+}
+
+/// Generates every wire enum of a `define_enum_group!` invocation, and the group enums they
+/// share once.
+pub(crate) fn generate_enum_groups(input: &DefineEnumGroups) -> TokenStream2 {
+  let shared = input.shared.iter().map(generate_shared_group);
+  let enums = input.enums.iter().map(generate_enum_group);
+  quote! {
+      #(#shared)*
+
+      #(#enums)*
+  }
+}
+
+/// The enum of a group block shared by several wire enums, with the attributes written on
+/// the block itself.
+fn generate_shared_group(shared: &SharedGroup) -> TokenStream2 {
+  let SharedGroup { attrs, vis, group } = shared;
+  let group_name = &group.name;
+  let serde = SerdeContainer::from_attrs(attrs);
+  let variants = group.variants.iter().map(|v| {
+    let v_attrs = &v.attrs;
+    let v_doc_cfg = doc_cfg(&v.attrs);
+    let v_rename = keep_tag(&serde, attrs, v);
+    let v_name = v.group_ident();
+    let v_fields = v.fields_decl();
+    quote! {
+        #(#v_attrs)*
+        #v_rename
+        #v_doc_cfg
+        #v_name #v_fields
+    }
+  });
+  let copy = group.options.copy.filter(|_| !has_derive(attrs, "Copy")).map(|span| {
+    quote_spanned! {span=> #[derive(::core::marker::Copy)] }
+  });
+  let markers = marker_payloads(vis, attrs, &group.variants);
+  quote! {
+      #(#attrs)*
+      #copy
+      #vis enum #group_name {
+          #(#variants),*
+      }
+
+      #markers
+  }
+}
+
+pub(crate) fn generate_enum_group(input: &EnumGroupInput) -> TokenStream2 {
+  let cx = Context::new(input);
+  let EnumGroupInput { attrs, options, vis, groups, .. } = input;
+  let wire_name = cx.wire;
+  let group_enum_name = &cx.dispatch;
+
+  // Outer attributes as propagated to the wire enum and to the group enums
+  let wire_attrs = propagated_attrs(attrs, options, |t| t.wire);
+  let group_attrs = propagated_attrs(attrs, options, |t| t.groups);
+  if let Err(error) = cx.check_serde_layout(&wire_attrs) {
+    return error.to_compile_error();
+  }
+  let ExtraAttrs { wire: wire_extra, groups: group_extra, dispatch: dispatch_extra, .. } =
+    &options.extra_attrs;
+  let wire_repr = match index::wire_repr(&cx, &wire_attrs, wire_extra) {
+    Ok(repr) => repr,
+    Err(error) => return error.to_compile_error(),
+  };
+
+  // Whether every group enum derives `Copy`, so that the dispatch enum can as well
+  let copies = |g: &ParsedGroup| {
+    g.options.copy.is_some() || has_derive(&group_enum_attrs(&group_attrs, g), "Copy")
+  };
+  let all_copy =
+    (cx.undispatched().is_none() || has_derive(&wire_attrs, "Copy")) && groups.iter().all(copies);
+
+  // Collect all variants for the flat wire enum
+  let mut all_variants = Vec::new();
+  let mut group_enum_variants = Vec::new();
+  let mut into_group_arms = Vec::new();
+
+  // Group enums generated in `groups_module` must stay visible to the wire enum's scope
+  let module = options.groups_module.as_ref();
+  let group_vis = |group: &ParsedGroup| {
+    let vis = group.vis.as_ref().unwrap_or(vis);
+    match module {
+      Some(_) => nested_visibility(vis),
+      None => vis.to_token_stream(),
+    }
+  };
+
+  // Generate group enums and collect info
+  let group_enums: Vec<TokenStream2> = groups
+    .iter()
+    .map(|group| {
+      let group_name = &group.name;
+      let group_attrs = group_enum_attrs(&group_attrs, group);
+      let group_vis = group_vis(group);
+
+      // Variants for this group enum and the enums of its nested groups
+      let declare = |v: &ParsedVariant| {
+        let v_attrs = &v.attrs;
+        let v_doc_cfg = doc_cfg(&v.attrs);
+        let v_aliases = cx.doc_aliases(None, v);
+        let v_rename = keep_tag(&cx.serde, &group_attrs, v);
+        let v_name = v.group_ident();
+        let v_fields = v.fields_decl();
+        quote! {
+            #(#v_attrs)*
+            #v_rename
+            #v_doc_cfg
+            #v_aliases
+            #v_name #v_fields
+        }
+      };
+      let variants = group_members(&cx, group, group.variants.iter(), 0, &declare);
+      let nested = group.nested_groups();
+      let nested_members: Vec<_> = nested
+        .iter()
+        .map(|n| group_members(&cx, group, n.variants.iter().copied(), n.depth, &declare))
+        .collect();
+
+      // Add to all_variants for wire enum
+      for v in &group.variants {
+        let v_attrs = &v.attrs;
+        let v_doc_cfg = doc_cfg(&v.attrs);
+        let v_aliases = cx.doc_aliases(Some(group), v);
+        let v_name = &v.name;
+        let v_fields = v.fields_decl();
+        let v_binding = v.fields_binding(&format_ident!("v"));
+        // The lookup table of `group_index_table` is indexed by discriminant
+        let v_discriminant = wire_repr.as_ref().map(|_| {
+          let index = proc_macro2::Literal::usize_unsuffixed(all_variants.len());
+          quote! { = #index }
+        });
+        all_variants.push(quote! {
+            #(#v_attrs)*
+            #v_doc_cfg
+            #v_aliases
+            #v_name #v_fields #v_discriminant
+        });
+
+        // Generate into_group arm, converting legacy variants into their replacement
+        let arm = match (cx.legacy_target(v), cx.undispatched()) {
+          (Some((target_group, target)), undispatched) => {
+            let target_name = &target.name;
+            let converted = cx.legacy_value(v, target);
+            match undispatched.filter(|_| target_group.options.skip_dispatch) {
+              Some(undispatched) => quote! {
+                  Self::#v_name(v) => #group_enum_name::#undispatched(Self::#target_name(#converted))
+              },
+              None => {
+                let module = cx.groups_module(target_group);
+                let value = target_group.wrap_variant(target, 0, module, quote! { (#converted) });
+                let target_group = &target_group.name;
+                quote! {
+                    Self::#v_name(v) => #group_enum_name::#target_group(#value)
+                }
+              }
+            }
+          }
+          (None, Some(undispatched)) if group.options.skip_dispatch => quote! {
+              msg @ Self::#v_name { .. } => #group_enum_name::#undispatched(msg)
+          },
+          (None, _) => {
+            let value = group.wrap_variant(v, 0, cx.groups_module(group), v_binding.clone());
+            quote! {
+                Self::#v_name #v_binding => #group_enum_name::#group_name(#value)
+            }
+          }
+        };
+        let cfg = v.cfg();
+        into_group_arms.push(quote! { #cfg #arm });
+      }
+
+      // Add to group enum variants
+      if !group.options.skip_dispatch {
+        let discriminant = group.discriminant.as_ref().map(|d| quote! { = #d });
+        let group_ty = cx.group_ty(group);
+        let docs = doc_comments(&group.attrs);
+        group_enum_variants.push(quote! {
+            #(#docs)*
+            #group_name(#group_ty) #discriminant
+        });
+      }
+
+      // `#[group(copy)]`, unless the derives already include `Copy`
+      let copy = group.options.copy.filter(|_| !has_derive(&group_attrs, "Copy")).map(|span| {
+        quote_spanned! {span=> #[derive(::core::marker::Copy)] }
+      });
+
+      // Shared groups are generated once, by `generate_enum_groups`
+      if group.shared {
+        return TokenStream2::new();
+      }
+
+      // Generate the group enum, followed by those of its nested groups
+      let generics = cx.group_generics(group);
+      let where_clause = &generics.where_clause;
+      let nested_enums = nested.iter().zip(nested_members).map(|(n, members)| {
+        let (name, docs) = (n.name, n.docs);
+        let generics = cx.nested_generics(n);
+        let where_clause = &generics.where_clause;
+        quote! {
+            #(#group_attrs)*
+            #(#docs)*
+            #copy
+            #(#[#group_extra])*
+            #group_vis enum #name #generics #where_clause {
+                #(#members),*
+            }
+        }
+      });
+      let borrowed = borrow::group_enums(&cx, group, &group_vis);
+      quote! {
+          #(#group_attrs)*
+          #copy
+          #(#[#group_extra])*
+          #group_vis enum #group_name #generics #where_clause {
+              #(#variants),*
+          }
+
+          #(#nested_enums)*
+
+          #borrowed
+      }
+    })
+    .collect();
+
+  // Generate the flat wire enum, unless the user wrote it and derives the rest
+  let generics = &input.generics;
+  let where_clause = &generics.where_clause;
+  let (wire_ty, dispatch_ty) = (&cx.wire_ty, &cx.dispatch_ty);
+  let (impl_generics, impl_where) = cx.impl_generics();
+  let wire_enum = (!input.derived).then(|| {
+    quote! {
+        #(#wire_attrs)*
+        #(#[#wire_extra])*
+        #vis enum #wire_name #generics #where_clause {
+            #(#all_variants),*
+        }
+    }
+  });
+
+  // Generate the group dispatch enum, with a fixed representation when discriminants are
+  // given explicitly
+  let repr = groups.iter().any(|g| g.discriminant.is_some()).then(|| quote! { #[repr(u16)] });
+  if let Some(undispatched) = cx.undispatched() {
+    let discriminant = match discriminant::undispatched(&cx) {
+      Ok(discriminant) => discriminant,
+      Err(error) => return error.to_compile_error(),
+    };
+    group_enum_variants.push(quote! {
+        /// Messages of the groups left out of dispatch with `skip_dispatch`.
+        #undispatched(#wire_ty) #discriminant
+    });
+  }
+  let dispatch_copy = all_copy.then(|| quote! { #[derive(::core::marker::Copy)] });
+  // Groups added later must not break matches downstream, on the dispatch enum either
+  let non_exhaustive = attrs.iter().find(|attr| attr.path().is_ident("non_exhaustive"));
+  let dispatch_doc = format!("A [`{}`] message wrapped in the enum of its group.", wire_name);
+  let group_dispatch_enum = quote! {
+      #[doc = #dispatch_doc]
+      #[derive(Debug, Clone)]
+      #dispatch_copy
+      #repr
+      #non_exhaustive
+      #(#[#dispatch_extra])*
+      #vis enum #group_enum_name #generics #where_clause {
+          #(#group_enum_variants),*
+      }
+  };
+
+  let into_group_body = quote! {
+      match self {
+          #(#into_group_arms),*
+      }
+  };
+
+  // Generate an inherent into_group method (doesn't require trait import)
+  let method = options.method.clone().unwrap_or_else(|| format_ident!("into_group"));
+  let inherent_impl = (!options.skip_inherent).then(|| {
+    quote! {
+        impl #impl_generics #wire_ty #impl_where {
+            /// Convert this enum into its grouped representation.
+            #vis fn #method(self) -> #dispatch_ty {
+                #into_group_body
+            }
+        }
+    }
+  });
+
+  // Convert the dispatch enum back, losslessly
+  let from_group_impl = {
+    let group_names = cx.dispatched_groups().map(|g| &g.name);
+    let msg = format_ident!("msg");
+    let undispatched_arm = cx.undispatched_arm(&msg, quote! { #msg });
+    quote! {
+        impl #impl_generics ::core::convert::From<#dispatch_ty> for #wire_ty #impl_where {
+            fn from(group: #dispatch_ty) -> Self {
+                match group {
+                    #(
+                        #group_enum_name::#group_names(group) => {
+                            ::enum_group_macros::GroupOf::into_wire(group)
+                        }
+                    )*
+                    #undispatched_arm
+                }
+            }
+        }
+    }
+  };
+
+  // Generate the EnumGroup trait impl (for users who want trait-based access)
+  let trait_impl = (!options.skip_trait).then(|| {
+    // Delegate to the inherent method when there is one
+    let body = if options.skip_inherent {
+      into_group_body.clone()
+    } else {
+      quote! { #wire_name::#method(self) }
+    };
+    quote! {
+        impl #impl_generics ::enum_group_macros::EnumGroup for #wire_ty #impl_where {
+            type Group = #dispatch_ty;
+
+            fn into_group(self) -> Self::Group {
+                #body
+            }
+
+            fn from_group(group: Self::Group) -> Self {
+                ::core::convert::From::from(group)
+            }
+        }
+    }
+  });
+
+  // Optional borrowed views of a message
+  let borrowed = borrow::generate(&cx);
+
+  // Optional per-variant payload accessors
+  let accessors = accessors::generate(&cx);
+
+  // Conversions between wire, group and payload types
+  let conversions = convert::generate(&cx);
+
+  // Fieldless kind enums and the mappings between them
+  let kinds = kind::generate(&cx);
+
+  // Optional array-backed maps keyed by kind
+  let kind_map = kind_map::generate(&cx);
+
+  // Static tables of serialized tags
+  let tag_tables = tags::generate(&cx);
+
+  // Explicit group discriminants
+  let discriminants = discriminant::generate(&cx);
+
+  // Explicit numeric variant tags
+  let numeric_tags = numeric_tags::generate(&cx);
+
+  // Optional branch-free group lookup
+  let group_index = index::generate(&cx, wire_repr.as_ref());
+
+  // Static per-variant metadata
+  let metadata = metadata::generate(&cx);
+
+  // Normalization of legacy variants
+  let legacy = legacy::generate(&cx);
+
+  // Optional Default-built instances of every variant
+  let examples = examples::generate(&cx);
+
+  // Optional deserialization from separate tag and payload
+  let by_tag = by_tag::generate(&cx);
+
+  // Optional type-erased payload access
+  let payload_any = payload_any::generate(&cx);
+
+  // Optional tag peeking on encoded messages
+  let peek = peek::generate(&cx);
+
+  // Optional Avro schema and encoding
+  let avro = avro::generate(&cx);
+
+  // Optional Python bindings
+  let python = python::generate(&cx);
+
+  // Optional routers dispatching to per-group handlers
+  let routers = router::generate(&cx);
+
+  // Optional sink fanning messages out per group
+  let sink_router = sink::generate(&cx);
+
+  // Optional closure-based handlers
+  let closures = closures::generate(&cx);
+
+  // Optional handler traits and dispatchers
+  let handlers = handler::generate(&cx);
+
+  // Optional subscription filters
+  let filter = filter::generate(&cx);
+
+  // Optional per-group message queues
+  let queues = queues::generate(&cx);
+
+  // Optional type-level tags of the groups
+  let group_tags = group_tags::generate(&cx);
+
+  // Optional per-group modules re-exporting everything that belongs to a group
+  let group_modules: Vec<TokenStream2> = if options.group_modules {
+    groups
+      .iter()
+      .filter(|g| !g.shared)
+      .map(|group| {
+        let vis = group.vis.as_ref().unwrap_or(vis);
+        modules::generate_group_module(vis, &cx.input.generics, group, cx.groups_module(group))
+      })
+      .collect()
+  } else {
+    Vec::new()
+  };
+
+  // Combine all generated code
+  let markers =
+    marker_payloads(vis, attrs, groups.iter().filter(|g| !g.shared).flat_map(|g| &g.variants));
+  let group_enums = match module {
+    Some(module) => {
+      let doc = format!("The group enums of [`{}`].", wire_name);
+      quote! {
+          #[doc = #doc]
+          #vis mod #module {
+              #[allow(unused_imports)]
+              use super::*;
+
+              #(#group_enums)*
+          }
+      }
+    }
+    None => quote! { #(#group_enums)* },
+  };
+  let expanded = quote! {
+      #group_enums
+
+      #wire_enum
+
+      #markers
+
+      #group_dispatch_enum
+
+      #inherent_impl
+
+      #trait_impl
+
+      #from_group_impl
+
+      #borrowed
+
+      #accessors
+
+      #conversions
+
+      #kinds
+
+      #kind_map
+
+      #tag_tables
+
+      #discriminants
+
+      #numeric_tags
+
+      #group_index
+
+      #metadata
+
+      #legacy
+
+      #examples
+
+      #by_tag
+
+      #payload_any
+
+      #peek
+
+      #avro
+
+      #python
+
+      #group_tags
+
+      #routers
+
+      #sink_router
+
+      #handlers
+
+      #closures
+
+      #queues
+
+      #filter
+
+      #(#group_modules)*
+  };
+  // Groups less visible than the wire enum appear in the public items generated for them
+  let mut lints = Vec::new();
+  if cx.variants().any(|(_, v)| v.attrs.iter().any(|a| a.path().is_ident("deprecated"))) {
+    lints.push(quote! { deprecated });
+  }
+  if groups.iter().any(|g| g.vis.is_some()) {
+    lints.push(quote! { private_interfaces });
+  }
+  if lints.is_empty() {
+    expanded
+  } else {
+    allow_lints(expanded, &lints)
+  }
+}
+
+/// The unit structs of the `Name()` variants among `variants`, named after the variant.
+/// They derive the standard traits, and serde's as far as the enum `attrs` do.
+fn marker_payloads<'v>(
+  vis: &Visibility,
+  attrs: &[Attribute],
+  variants: impl IntoIterator<Item = &'v ParsedVariant>,
+) -> TokenStream2 {
+  let serde = ["Serialize", "Deserialize"].into_iter().filter_map(|name| derive_path(attrs, name));
+  let serde: Vec<_> = serde.collect();
+  let markers = variants.into_iter().filter(|v| v.marker).map(|v| {
+    let (name, cfg) = (&v.name, v.cfg());
+    let doc = format!("Payload of the `{}` variant, which carries no data.", name);
+    quote! {
+        #[doc = #doc]
+        #cfg
+        #[derive(
+            ::core::fmt::Debug, ::core::clone::Clone, ::core::marker::Copy,
+            ::core::default::Default, ::core::cmp::PartialEq, ::core::cmp::Eq,
+            ::core::cmp::PartialOrd, ::core::cmp::Ord, ::core::hash::Hash, #(#serde),*
+        )]
+        #vis struct #name;
+    }
+  });
+  quote! { #(#markers)* }
+}
+
+/// `#[serde(rename = "...")]` keeping a variant renamed with `as` under the tag of its wire
+/// name on a group enum deriving serde traits, so that both enums serialize it alike.
+fn keep_tag(
+  serde: &SerdeContainer,
+  enum_attrs: &[Attribute],
+  variant: &ParsedVariant,
+) -> Option<TokenStream2> {
+  let serialized = has_derive(enum_attrs, "Serialize") || has_derive(enum_attrs, "Deserialize");
+  let group_name = variant.group_name.as_ref().filter(|_| serialized)?;
+  let tag = serde.variant_tag(&variant.name.to_string(), &variant.attrs);
+  let group_tag = serde.variant_tag(&group_name.to_string(), &variant.attrs);
+  (group_tag != tag).then(|| quote! { #[serde(rename = #tag)] })
+}
+
+/// Adds `#[allow(lints)]` to the generated items. The generated code matches and builds
+/// `#[deprecated]` variants on behalf of the user, and the dispatch enum, traits and
+/// helpers name group enums less visible than them (`private_interfaces`); both lints are
+/// meant for the user's own code.
+fn allow_lints(expanded: TokenStream2, lints: &[TokenStream2]) -> TokenStream2 {
+  let Ok(mut file) = syn::parse2::<syn::File>(expanded.clone()) else {
+    return expanded;
+  };
+  for item in &mut file.items {
+    let attrs = match item {
+      Item::Impl(item) => &mut item.attrs,
+      Item::Trait(item) => &mut item.attrs,
+      Item::Fn(item) => &mut item.attrs,
+      Item::Const(item) => &mut item.attrs,
+      Item::Static(item) => &mut item.attrs,
+      Item::Mod(item) => &mut item.attrs,
+      Item::Enum(item) => &mut item.attrs,
+      Item::Struct(item) => &mut item.attrs,
+      _ => continue,
+    };
+    attrs.push(syn::parse_quote! { #[allow(#(#lints),*)] });
+  }
+  file.into_token_stream()
+}
+
+/// The variants of the group enum at `depth` holding `variants`: those declared directly in
+/// it, and one per nested group, wrapping the nested group's enum.
+fn group_members<'v>(
+  cx: &Context,
+  group: &ParsedGroup,
+  variants: impl IntoIterator<Item = &'v ParsedVariant>,
+  depth: usize,
+  declare: &dyn Fn(&ParsedVariant) -> TokenStream2,
+) -> Vec<TokenStream2> {
+  let nested = group.nested_groups();
+  let mut seen = Vec::new();
+  let mut members = Vec::new();
+  for v in variants {
+    match v.nesting.get(depth) {
+      None => members.push(declare(v)),
+      Some(name) if !seen.contains(&name) => {
+        seen.push(name);
+        let n = nested.iter().find(|n| n.name == name).expect("nested group of a variant");
+        // Declared next to the group enum, in the same module
+        let generics = cx.nested_generics(n);
+        let (_, ty_generics, _) = generics.split_for_impl();
+        let docs = n.docs;
+        members.push(quote! { #(#docs)* #name(#name #ty_generics) });
+      }
+      Some(_) => {}
+    }
+  }
+  members
+}
+
+/// The attributes of a group's enum: the propagated outer ones, with their derives left out
+/// when the group derives on its own, followed by the group's own.
+fn group_enum_attrs(outer: &[Attribute], group: &ParsedGroup) -> Vec<Attribute> {
+  let own_derives = group.attrs.iter().any(|attr| attr.path().is_ident("derive"));
+  outer
+    .iter()
+    .filter(|attr| !(own_derives && attr.path().is_ident("derive")))
+    .chain(&group.attrs)
+    .cloned()
+    .collect()
+}
+
+/// The outer attributes for one kind of generated enum, with the serde derives excluded by
+/// `serialize_only` / `deserialize_only` removed.
+fn propagated_attrs(
+  attrs: &[Attribute],
+  options: &EnumGroupOptions,
+  applies: impl Fn(&Targets) -> bool,
+) -> Vec<Attribute> {
+  if applies(&options.serialize_only) {
+    without_derive(attrs, "Deserialize")
+  } else if applies(&options.deserialize_only) {
+    without_derive(attrs, "Serialize")
+  } else {
+    attrs.to_vec()
+  }
+}
+
+/// Whether `ty` may serialize as a map, as internally tagged enums require of their
+/// payloads. Only types known to serialize otherwise (primitives, strings, sequences,
+/// options, non-empty tuples) are ruled out.
+fn holds_map(ty: &Type) -> bool {
+  const NOT_MAPS: &[&str] = &[
+    "bool", "char", "str", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64",
+    "u128", "usize", "f32", "f64", "String", "Vec", "VecDeque", "HashSet", "BTreeSet", "Option",
+  ];
+  match ty {
+    Type::Paren(inner) => holds_map(&inner.elem),
+    Type::Group(inner) => holds_map(&inner.elem),
+    Type::Reference(reference) => holds_map(&reference.elem),
+    Type::Tuple(tuple) => tuple.elems.is_empty(),
+    Type::Array(_) | Type::Slice(_) => false,
+    Type::Path(path) => !path.path.segments.last().is_some_and(|last| {
+      let name = last.ident.to_string();
+      NOT_MAPS.contains(&name.as_str())
+    }),
+    _ => true,
+  }
+}
@@ -0,0 +1,53 @@
+//! Per-group re-export modules (`#[enum_group(group_modules)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Generics, Ident, Visibility};
+
+use crate::parse::ParsedGroup;
+use crate::util::{nested_visibility, reexport_path, snake_ident, used_generics};
+
+/// Generates `mod <group> { pub use super::{Group, PayloadA, PayloadB}; }` for one group,
+/// also re-exporting the enums of its nested groups.
+///
+/// Payload types that cannot be named through `super::` (prelude types, types with
+/// generic arguments, the wire enum's own type parameters, ...) are skipped.
+///
+/// `module` is the module of `groups_module` holding the group enums, if any.
+pub(crate) fn generate_group_module(
+  vis: &Visibility,
+  generics: &Generics,
+  group: &ParsedGroup,
+  module: Option<&Ident>,
+) -> TokenStream2 {
+  let group_name = &group.name;
+  let mod_name = snake_ident(group_name);
+  let use_vis = nested_visibility(vis);
+
+  let mut payload_paths: Vec<TokenStream2> = Vec::new();
+  for ty in group.variants.iter().flat_map(|v| v.field_types()) {
+    if !used_generics(generics, [ty]).params.is_empty() {
+      continue;
+    }
+    if let Some(path) = reexport_path(ty) {
+      // The same payload type may back several variants
+      if !payload_paths.iter().any(|p| p.to_string() == path.to_string()) {
+        payload_paths.push(path);
+      }
+    }
+  }
+
+  let nested: Vec<_> = group.nested_groups().into_iter().map(|n| n.name).collect();
+  let module = module.map(|m| quote! { #m:: });
+
+  let doc = format!("The `{}` group enum together with its payload types.", group_name);
+
+  quote! {
+      #[doc = #doc]
+      #vis mod #mod_name {
+          #use_vis use super::#module #group_name;
+          #(#use_vis use super::#module #nested;)*
+          #(#use_vis use #payload_paths;)*
+      }
+  }
+}
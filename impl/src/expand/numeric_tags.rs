@@ -0,0 +1,47 @@
+//! Fixed numeric message ids from explicit variant tags (`#[variant(tag = N)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use super::Context;
+
+/// Generates `tag()` and `from_tag()` on the wire enum when the variants have explicit
+/// numeric tags.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let wire_ty = &cx.wire_ty;
+  let (impl_generics, where_clause) = cx.impl_generics();
+  let kind = &cx.kind;
+
+  if cx.variants().next().is_none() || cx.variants().any(|(_, v)| v.options.tag.is_none()) {
+    return TokenStream2::new();
+  }
+  let tag_arms = cx.variants().map(|(_, v)| {
+    let (v_name, cfg, tag) = (&v.name, v.cfg(), &v.options.tag);
+    quote! { #cfg Self::#v_name { .. } => #tag }
+  });
+  let kind_arms = cx.variants().map(|(_, v)| {
+    let (v_name, cfg, tag) = (&v.name, v.cfg(), &v.options.tag);
+    quote! { #cfg #tag => ::core::option::Option::Some(#kind::#v_name) }
+  });
+
+  quote! {
+      impl #impl_generics #wire_ty #where_clause {
+          /// The explicit numeric tag of this message's variant, a fixed message id for
+          /// binary protocols.
+          #vis const fn tag(&self) -> u16 {
+              match self {
+                  #(#tag_arms),*
+              }
+          }
+
+          /// The kind of the variant with the explicit numeric tag `tag`, if any.
+          #vis const fn from_tag(tag: u16) -> ::core::option::Option<#kind> {
+              match tag {
+                  #(#kind_arms,)*
+                  _ => ::core::option::Option::None,
+              }
+          }
+      }
+  }
+}
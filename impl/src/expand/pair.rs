@@ -0,0 +1,98 @@
+//! Two wire enums with mirrored groups (`define_enum_group_pair!`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+
+use super::generate_enum_group;
+use crate::parse::{pair_group_name, EnumGroupPairInput};
+use crate::util::snake_ident;
+
+/// Generates both wire enums, the group kind shared by them and the mappings between
+/// corresponding groups.
+pub(crate) fn generate_enum_group_pair(input: &EnumGroupPairInput) -> TokenStream2 {
+  let EnumGroupPairInput { first, second, groups } = input;
+  let vis = &first.vis;
+  let first_kind = format_ident!("{}GroupKind", first.name);
+  let second_kind = format_ident!("{}GroupKind", second.name);
+  let shared = format_ident!("{}{}GroupKind", first.name, second.name);
+  let first_fn = snake_ident(&first.name);
+  let second_fn = snake_ident(&second.name);
+
+  let first_groups: Vec<_> = groups.iter().map(|g| pair_group_name(g, &first.name)).collect();
+  let second_groups: Vec<_> = groups.iter().map(|g| pair_group_name(g, &second.name)).collect();
+
+  let shared_doc = format!(
+    "Fieldless identifier of a group shared by [`{}`] and [`{}`].",
+    first.name, second.name
+  );
+  let first_fn_doc = format!("The corresponding group of [`{}`].", first.name);
+  let second_fn_doc = format!("The corresponding group of [`{}`].", second.name);
+
+  let first_enum = generate_enum_group(first);
+  let second_enum = generate_enum_group(second);
+
+  quote! {
+      #first_enum
+
+      #second_enum
+
+      #[doc = #shared_doc]
+      #[derive(
+          ::core::fmt::Debug, ::core::clone::Clone, ::core::marker::Copy, ::core::cmp::PartialEq,
+          ::core::cmp::Eq, ::core::hash::Hash
+      )]
+      #vis enum #shared {
+          #(#groups),*
+      }
+
+      impl #shared {
+          #[doc = #first_fn_doc]
+          #vis const fn #first_fn(self) -> #first_kind {
+              match self {
+                  #(Self::#groups => #first_kind::#first_groups),*
+              }
+          }
+
+          #[doc = #second_fn_doc]
+          #vis const fn #second_fn(self) -> #second_kind {
+              match self {
+                  #(Self::#groups => #second_kind::#second_groups),*
+              }
+          }
+      }
+
+      impl ::core::convert::From<#first_kind> for #shared {
+          fn from(kind: #first_kind) -> Self {
+              match kind {
+                  #(#first_kind::#first_groups => Self::#groups),*
+              }
+          }
+      }
+
+      impl ::core::convert::From<#second_kind> for #shared {
+          fn from(kind: #second_kind) -> Self {
+              match kind {
+                  #(#second_kind::#second_groups => Self::#groups),*
+              }
+          }
+      }
+
+      impl #first_kind {
+          #[doc = #second_fn_doc]
+          #vis const fn counterpart(self) -> #second_kind {
+              match self {
+                  #(Self::#first_groups => #second_kind::#second_groups),*
+              }
+          }
+      }
+
+      impl #second_kind {
+          #[doc = #first_fn_doc]
+          #vis const fn counterpart(self) -> #first_kind {
+              match self {
+                  #(Self::#second_groups => #first_kind::#first_groups),*
+              }
+          }
+      }
+  }
+}
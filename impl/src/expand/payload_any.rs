@@ -0,0 +1,63 @@
+//! Type-erased access to payloads (`#[enum_group(payload_any)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use super::Context;
+
+/// Generates `into_payload_any()` and `payload_downcast()` when requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  if !cx.input.options.payload_any {
+    return TokenStream2::new();
+  }
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let kind = &cx.kind;
+  if let Err(error) =
+    cx.variants().try_for_each(|(_, v)| v.require_payload("payload_any").map(drop))
+  {
+    return error.to_compile_error();
+  }
+
+  let v_names: Vec<_> = cx.variants().map(|(_, v)| &v.name).collect();
+  let cfgs: Vec<_> = cx.variants().map(|(_, v)| v.cfg()).collect();
+  // A `boxed` payload is already on the heap, and goes back into its box when it is not a `T`
+  let erased = cx.variants().map(|(_, v)| {
+    if v.options.boxed {
+      quote! { v as ::std::boxed::Box<dyn ::core::any::Any + ::core::marker::Send> }
+    } else {
+      quote! { ::std::boxed::Box::new(v) }
+    }
+  });
+  let payloads = cx.variants().map(|(_, v)| v.unbox_payload(quote! { v }));
+  let restores = cx.variants().map(|(_, v)| {
+    let (v_name, stored) = (&v.name, v.box_payload(quote! { v }));
+    quote! { |v| Self::#v_name(#stored) }
+  });
+
+  quote! {
+      impl #wire {
+          /// Splits this message into its kind and its type-erased payload.
+          #vis fn into_payload_any(
+              self,
+          ) -> (#kind, ::std::boxed::Box<dyn ::core::any::Any + ::core::marker::Send>) {
+              match self {
+                  #(#cfgs Self::#v_names(v) => (#kind::#v_names, #erased),)*
+              }
+          }
+
+          /// Takes the payload out of this message if it is a `T`, or returns the message
+          /// unchanged.
+          #vis fn payload_downcast<T: 'static>(self) -> ::core::result::Result<T, Self> {
+              match self {
+                  #(
+                      #cfgs
+                      Self::#v_names(v) => {
+                          ::enum_group_macros::__private::any::downcast(#payloads).map_err(#restores)
+                      }
+                  )*
+              }
+          }
+      }
+  }
+}
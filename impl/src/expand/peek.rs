@@ -0,0 +1,77 @@
+//! Reading the kind of an encoded message from its tag alone (`#[enum_group(peek_kind)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use super::Context;
+
+/// Generates `WireMsg::peek_kind()` when requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let kind = &cx.kind;
+
+  let Some(predicate) = &cx.input.options.peek_kind else {
+    return TokenStream2::new();
+  };
+  if cx.serde.untagged {
+    return syn::Error::new(wire.span(), "`peek_kind` is not supported for untagged enums")
+      .to_compile_error();
+  }
+  let cfg = predicate.as_ref().map(|p| quote! { #[cfg(#p)] });
+  let tag_field = match &cx.serde.tag {
+    Some(tag) => quote! { ::core::option::Option::Some(#tag) },
+    None => quote! { ::core::option::Option::None },
+  };
+
+  // Tags and indices no variant has are messages of the `#[unknown]` catch-all, which is
+  // declared last and thus has the indices past the `TAG_TABLE`
+  let unknown = cx.unknown().map(|v| {
+    let (v_name, cfg) = (&v.name, v.cfg());
+    quote! { #cfg { return ::core::result::Result::Ok(#kind::#v_name); } }
+  });
+  let allow_unreachable = unknown.as_ref().map(|_| quote! { #[allow(unreachable_code)] });
+
+  // Internally tagged payloads start with the tag field, not a variant index
+  let by_index = if cx.serde.internally_tagged() {
+    quote! {
+        ::core::result::Result::Err(::enum_group_macros::PeekError::UnsupportedFormat(format))
+    }
+  } else {
+    quote! {
+        let index = ::enum_group_macros::__private::peek::variant_index(bytes, format)?;
+        if let ::core::option::Option::Some((kind, _)) = Self::TAG_TABLE.get(index) {
+            return ::core::result::Result::Ok(*kind);
+        }
+        #unknown
+        ::core::result::Result::Err(::enum_group_macros::PeekError::UnknownIndex(index))
+    }
+  };
+
+  quote! {
+      #cfg
+      impl #wire {
+          /// The kind of the message encoded in `bytes`, read from its tag or variant index
+          /// alone, without decoding the payload.
+          #allow_unreachable
+          #vis fn peek_kind(
+              bytes: &[u8],
+              format: ::enum_group_macros::WireFormat,
+          ) -> ::core::result::Result<#kind, ::enum_group_macros::PeekError> {
+              if let ::enum_group_macros::WireFormat::Json = format {
+                  let tag = ::enum_group_macros::__private::peek::json_tag(bytes, #tag_field)?;
+                  if let ::core::option::Option::Some((kind, _)) =
+                      Self::TAG_TABLE.iter().find(|(_, t)| *t == tag)
+                  {
+                      return ::core::result::Result::Ok(*kind);
+                  }
+                  #unknown
+                  return ::core::result::Result::Err(
+                      ::enum_group_macros::PeekError::UnknownTag(tag),
+                  );
+              }
+              #by_index
+          }
+      }
+  }
+}
@@ -0,0 +1,67 @@
+//! Python bindings through PyO3 (`#[enum_group(pyo3)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+
+use super::Context;
+
+/// The `#[pyclass]` attribute for the kind enums, behind the requested `cfg` if any.
+pub(crate) fn pyclass_attr(cx: &Context) -> Option<TokenStream2> {
+  cx.input.options.pyo3.as_ref().map(|predicate| {
+    let pyclass = quote! {
+        ::enum_group_macros::__private::pyo3::pyclass(
+            crate = "::enum_group_macros::__private::pyo3",
+            eq,
+            eq_int,
+            frozen,
+            hash,
+            from_py_object
+        )
+    };
+    match predicate {
+      Some(p) => quote! { #[cfg_attr(#p, #pyclass)] },
+      None => quote! { #[#pyclass] },
+    }
+  })
+}
+
+/// Generates `WireMsg::to_py()` and `WireMsg::from_py()` when requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+
+  let Some(predicate) = &cx.input.options.pyo3 else {
+    return TokenStream2::new();
+  };
+  let cfg = predicate.as_ref().map(|p| quote! { #[cfg(#p)] });
+
+  quote! {
+      #cfg
+      impl #wire {
+          /// Converts this message into the Python object its serde serialization maps to
+          /// (a `dict` for struct-like layouts).
+          #vis fn to_py<'py>(
+              &self,
+              py: ::enum_group_macros::__private::pyo3::Python<'py>,
+          ) -> ::enum_group_macros::__private::pyo3::PyResult<
+              ::enum_group_macros::__private::pyo3::Bound<
+                  'py,
+                  ::enum_group_macros::__private::pyo3::PyAny,
+              >,
+          > {
+              ::enum_group_macros::__private::py::to_py(py, self)
+          }
+
+          /// Builds a message from a Python object shaped like the serde serialization of
+          /// this enum; raises `ValueError` if it does not match any variant.
+          #vis fn from_py(
+              obj: &::enum_group_macros::__private::pyo3::Bound<
+                  '_,
+                  ::enum_group_macros::__private::pyo3::PyAny,
+              >,
+          ) -> ::enum_group_macros::__private::pyo3::PyResult<Self> {
+              ::enum_group_macros::__private::py::from_py(obj)
+          }
+      }
+  }
+}
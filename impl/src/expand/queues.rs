@@ -0,0 +1,124 @@
+//! Per-group message buffers (`#[enum_group(queues)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+
+use super::Context;
+use crate::util::snake_ident;
+
+/// Generates `{Wire}Queues` when requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  if !cx.input.options.queues {
+    return TokenStream2::new();
+  }
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let dispatch = &cx.dispatch;
+  let queues = format_ident!("{}Queues", wire);
+
+  let groups: Vec<_> = cx.dispatched_groups().map(|g| &g.name).collect();
+  let fields: Vec<_> = groups.iter().map(|g| snake_ident(g)).collect();
+  let pops: Vec<_> =
+    groups.iter().map(|g| format_ident!("pop_{}", snake_ident(g).unraw())).collect();
+  let group_tys: Vec<_> = cx.dispatched_groups().map(|g| cx.group_ty(g)).collect();
+  let field_docs: Vec<_> =
+    cx.dispatched_groups().map(|g| format!("Buffered {} messages.", cx.group_link(g))).collect();
+  let pop_docs: Vec<_> = cx
+    .dispatched_groups()
+    .map(|g| format!("Takes the oldest buffered {} message.", cx.group_link(g)))
+    .collect();
+  let dispatch_expr = cx.dispatch_expr(quote! { msg });
+
+  // Messages of groups left out of dispatch share one more queue
+  let undispatched = cx.undispatched().map(|undispatched| {
+    let field = snake_ident(&undispatched);
+    let pop = format_ident!("pop_{}", field);
+    (undispatched, field, pop)
+  });
+  let undispatched_field = undispatched.as_ref().map(|(_, field, _)| {
+    quote! {
+        /// Buffered messages of the groups left out of dispatch.
+        #vis #field: ::std::collections::VecDeque<#wire>,
+    }
+  });
+  let undispatched_push = undispatched.as_ref().map(|(undispatched, field, _)| {
+    quote! { #dispatch::#undispatched(msg) => self.#field.push_back(msg), }
+  });
+  let undispatched_pop = undispatched.as_ref().map(|(_, field, pop)| {
+    quote! {
+        /// Takes the oldest buffered message of the groups left out of dispatch.
+        #vis fn #pop(&mut self) -> ::core::option::Option<#wire> {
+            self.#field.pop_front()
+        }
+    }
+  });
+  let undispatched_pop_any = undispatched.as_ref().map(|(undispatched, field, _)| {
+    quote! {
+        if let ::core::option::Option::Some(msg) = self.#field.pop_front() {
+            return ::core::option::Option::Some(#dispatch::#undispatched(msg));
+        }
+    }
+  });
+  let undispatched_fields: Vec<_> = undispatched.iter().map(|(_, field, _)| field).collect();
+
+  let doc = format!("Buffers [`{}`] messages in one FIFO queue per group.", wire);
+
+  quote! {
+      #[doc = #doc]
+      #[derive(::core::fmt::Debug, ::core::clone::Clone, ::core::default::Default)]
+      #vis struct #queues {
+          #(
+              #[doc = #field_docs]
+              #vis #fields: ::std::collections::VecDeque<#group_tys>,
+          )*
+          #undispatched_field
+      }
+
+      impl #queues {
+          /// Creates empty queues.
+          #vis fn new() -> Self {
+              ::core::default::Default::default()
+          }
+
+          /// Appends `msg` to the queue of its group.
+          #vis fn push(&mut self, msg: #wire) {
+              match #dispatch_expr {
+                  #(#dispatch::#groups(group) => self.#fields.push_back(group),)*
+                  #undispatched_push
+              }
+          }
+
+          #(
+              #[doc = #pop_docs]
+              #vis fn #pops(&mut self) -> ::core::option::Option<#group_tys> {
+                  self.#fields.pop_front()
+              }
+          )*
+
+          #undispatched_pop
+
+          /// Takes the oldest message of the first non-empty queue, in group declaration
+          /// order, then from the messages of groups left out of dispatch.
+          #vis fn pop(&mut self) -> ::core::option::Option<#dispatch> {
+              #(
+                  if let ::core::option::Option::Some(group) = self.#fields.pop_front() {
+                      return ::core::option::Option::Some(#dispatch::#groups(group));
+                  }
+              )*
+              #undispatched_pop_any
+              ::core::option::Option::None
+          }
+
+          /// The number of buffered messages across all groups.
+          #vis fn len(&self) -> usize {
+              0 #(+ self.#fields.len())* #(+ self.#undispatched_fields.len())*
+          }
+
+          /// Whether no message is buffered.
+          #vis fn is_empty(&self) -> bool {
+              true #(&& self.#fields.is_empty())* #(&& self.#undispatched_fields.is_empty())*
+          }
+      }
+  }
+}
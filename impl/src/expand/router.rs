@@ -0,0 +1,212 @@
+//! Routers dispatching messages to per-group handlers (`#[enum_group(router)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+
+use super::Context;
+use crate::util::snake_ident;
+
+/// Generates `{Wire}Router` and `{Wire}AsyncRouter` when requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  if !cx.input.options.router {
+    return TokenStream2::new();
+  }
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let dispatch = &cx.dispatch;
+  let router = format_ident!("{}Router", wire);
+  let async_router = format_ident!("{}AsyncRouter", wire);
+
+  let groups: Vec<_> = cx.dispatched_groups().map(|g| &g.name).collect();
+  let fields: Vec<_> = groups.iter().map(|g| snake_ident(g)).collect();
+  let setters: Vec<_> =
+    groups.iter().map(|g| format_ident!("on_{}", snake_ident(g).unraw())).collect();
+  let group_tys: Vec<_> = cx.dispatched_groups().map(|g| cx.group_ty(g)).collect();
+  let setter_docs: Vec<_> = cx
+    .dispatched_groups()
+    .map(|g| {
+      let link = cx.group_link(g);
+      format!("Registers the handler of {} messages, replacing any previous one.", link)
+    })
+    .collect();
+  let into_group = cx.dispatch_expr(quote! { msg });
+  let msg = format_ident!("msg");
+  // Reports `msg` to the `on_unexpected` hook before handing it back
+  let unhandled = |reason: TokenStream2| {
+    quote! {{
+        if let ::core::option::Option::Some(hook) = &mut self.__on_unexpected {
+            hook(&#msg, ::enum_group_macros::UnexpectedReason::#reason);
+        }
+        ::core::result::Result::Err(::enum_group_macros::RouteError::Unhandled(#msg))
+    }}
+  };
+  let no_handler = unhandled(quote! { NoHandler });
+  let undispatched_arm = cx.undispatched_arm(&msg, unhandled(quote! { SkippedGroup }));
+
+  let router_doc = format!(
+    "Routes [`{}`] messages to one handler per group, each getting mutable access to a \
+     shared state `S`.",
+    wire
+  );
+  let async_router_doc = format!("Async counterpart of [`{}`].", router);
+
+  quote! {
+      #[doc = #router_doc]
+      #vis struct #router<S, T = (), E = ::enum_group_macros::BoxError> {
+          #(
+              #fields: ::core::option::Option<
+                  ::std::boxed::Box<dyn FnMut(&mut S, #group_tys) -> ::core::result::Result<T, E>>,
+              >,
+          )*
+          __on_unexpected: ::core::option::Option<
+              ::std::boxed::Box<dyn FnMut(&#wire, ::enum_group_macros::UnexpectedReason)>,
+          >,
+      }
+
+      impl<S, T, E> ::core::default::Default for #router<S, T, E> {
+          fn default() -> Self {
+              Self {
+                  #(#fields: ::core::option::Option::None,)*
+                  __on_unexpected: ::core::option::Option::None,
+              }
+          }
+      }
+
+      impl<S, T, E> #router<S, T, E> {
+          /// Creates a router without any handler.
+          #vis fn new() -> Self {
+              ::core::default::Default::default()
+          }
+
+          #(
+              #[doc = #setter_docs]
+              #vis fn #setters(
+                  mut self,
+                  handler: impl FnMut(&mut S, #group_tys) -> ::core::result::Result<T, E> + 'static,
+              ) -> Self {
+                  self.#fields = ::core::option::Option::Some(::std::boxed::Box::new(handler));
+                  self
+              }
+          )*
+
+          /// Registers a hook seeing every message handed back as unhandled, together with
+          /// the reason, e.g. for logging or metrics.
+          #vis fn on_unexpected(
+              mut self,
+              hook: impl FnMut(&#wire, ::enum_group_macros::UnexpectedReason) + 'static,
+          ) -> Self {
+              self.__on_unexpected = ::core::option::Option::Some(::std::boxed::Box::new(hook));
+              self
+          }
+
+          /// Hands `msg` to the handler of its group, or back to the caller if that group has
+          /// no handler or is left out of dispatch.
+          #vis fn route(
+              &mut self,
+              state: &mut S,
+              msg: #wire,
+          ) -> ::core::result::Result<T, ::enum_group_macros::RouteError<#wire, E>> {
+              match #into_group {
+                  #(
+                      #dispatch::#groups(group) => match &mut self.#fields {
+                          ::core::option::Option::Some(handler) => handler(state, group)
+                              .map_err(::enum_group_macros::RouteError::Handler),
+                          ::core::option::Option::None => {
+                              let #msg = ::enum_group_macros::GroupOf::into_wire(group);
+                              #no_handler
+                          }
+                      },
+                  )*
+                  #undispatched_arm
+              }
+          }
+      }
+
+      #[doc = #async_router_doc]
+      ///
+      /// Handlers return boxed futures borrowing the state, typically written as
+      /// `|state, group| Box::pin(async move { ... })`.
+      #vis struct #async_router<S, T = (), E = ::enum_group_macros::BoxError> {
+          #(
+              #fields: ::core::option::Option<
+                  ::std::boxed::Box<
+                      dyn for<'s> FnMut(
+                              &'s mut S,
+                              #group_tys,
+                          ) -> ::enum_group_macros::BoxFuture<'s, ::core::result::Result<T, E>>
+                          + Send,
+                  >,
+              >,
+          )*
+          __on_unexpected: ::core::option::Option<
+              ::std::boxed::Box<dyn FnMut(&#wire, ::enum_group_macros::UnexpectedReason) + Send>,
+          >,
+      }
+
+      impl<S, T, E> ::core::default::Default for #async_router<S, T, E> {
+          fn default() -> Self {
+              Self {
+                  #(#fields: ::core::option::Option::None,)*
+                  __on_unexpected: ::core::option::Option::None,
+              }
+          }
+      }
+
+      impl<S, T, E> #async_router<S, T, E> {
+          /// Creates a router without any handler.
+          #vis fn new() -> Self {
+              ::core::default::Default::default()
+          }
+
+          #(
+              #[doc = #setter_docs]
+              #vis fn #setters(
+                  mut self,
+                  handler: impl for<'s> FnMut(
+                          &'s mut S,
+                          #group_tys,
+                      ) -> ::enum_group_macros::BoxFuture<'s, ::core::result::Result<T, E>>
+                      + Send
+                      + 'static,
+              ) -> Self {
+                  self.#fields = ::core::option::Option::Some(::std::boxed::Box::new(handler));
+                  self
+              }
+          )*
+
+          /// Registers a hook seeing every message handed back as unhandled, together with
+          /// the reason, e.g. for logging or metrics.
+          #vis fn on_unexpected(
+              mut self,
+              hook: impl FnMut(&#wire, ::enum_group_macros::UnexpectedReason) + Send + 'static,
+          ) -> Self {
+              self.__on_unexpected = ::core::option::Option::Some(::std::boxed::Box::new(hook));
+              self
+          }
+
+          /// Hands `msg` to the handler of its group, or back to the caller if that group has
+          /// no handler or is left out of dispatch.
+          #vis async fn route(
+              &mut self,
+              state: &mut S,
+              msg: #wire,
+          ) -> ::core::result::Result<T, ::enum_group_macros::RouteError<#wire, E>> {
+              match #into_group {
+                  #(
+                      #dispatch::#groups(group) => match &mut self.#fields {
+                          ::core::option::Option::Some(handler) => handler(state, group)
+                              .await
+                              .map_err(::enum_group_macros::RouteError::Handler),
+                          ::core::option::Option::None => {
+                              let #msg = ::enum_group_macros::GroupOf::into_wire(group);
+                              #no_handler
+                          }
+                      },
+                  )*
+                  #undispatched_arm
+              }
+          }
+      }
+  }
+}
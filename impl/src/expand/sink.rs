@@ -0,0 +1,160 @@
+//! A `Sink` fanning messages out to one sink per group (`#[enum_group(sink_router)]`).
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+
+use super::Context;
+use crate::util::snake_ident;
+
+/// Generates `{Wire}SinkRouter` when requested.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let Some(predicate) = &cx.input.options.sink_router else {
+    return TokenStream2::new();
+  };
+  let cfg = predicate.as_ref().map(|p| quote! { #[cfg(#p)] });
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let dispatch = &cx.dispatch;
+  let router = format_ident!("{}SinkRouter", wire);
+  let into_group = cx.dispatch_expr(quote! { msg });
+  let msg = format_ident!("msg");
+  let undispatched_arm = cx.undispatched_arm(
+    &msg,
+    quote! {{
+        if let ::core::option::Option::Some(hook) = this.__on_unexpected {
+            hook(&#msg, ::enum_group_macros::UnexpectedReason::SkippedGroup);
+        }
+        ::core::result::Result::Ok(())
+    }},
+  );
+  // Only groups left out of dispatch have no sink, so only they need the hook
+  let hook = cx.undispatched().map(|_| {
+    let field = quote! {
+        __on_unexpected: ::core::option::Option<fn(&#wire, ::enum_group_macros::UnexpectedReason)>
+    };
+    let setter = quote! {
+        /// Sets a hook seeing every message of a group left out of dispatch before it is
+        /// dropped, e.g. for logging or metrics.
+        #vis fn on_unexpected(
+            mut self,
+            hook: fn(&#wire, ::enum_group_macros::UnexpectedReason),
+        ) -> Self {
+            self.__on_unexpected = ::core::option::Option::Some(hook);
+            self
+        }
+    };
+    (field, setter)
+  });
+  let hook_field = hook.as_ref().map(|(field, _)| quote! { #field, });
+  let hook_init = hook.as_ref().map(|_| quote! { __on_unexpected: ::core::option::Option::None, });
+  let hook_setter = hook.as_ref().map(|(_, setter)| setter);
+
+  let groups: Vec<_> = cx.dispatched_groups().map(|g| &g.name).collect();
+  let fields: Vec<_> = groups.iter().map(|g| snake_ident(g)).collect();
+  let params: Vec<_> = groups.iter().map(|g| format_ident!("{}Sink", g)).collect();
+  let group_tys: Vec<_> = cx.dispatched_groups().map(|g| cx.group_ty(g)).collect();
+  let field_docs: Vec<_> =
+    cx.dispatched_groups().map(|g| format!("The sink of {} messages.", cx.group_link(g))).collect();
+
+  let doc = format!(
+    "A `Sink` of [`{}`] messages forwarding each one to the sink of its group.\n\n\
+     It is ready, flushed or closed once every inner sink is, so the slowest group applies \
+     backpressure to all of them. All sinks must share one error type and be `Unpin`; pin \
+     others with `Box::pin`. Messages of groups left out of dispatch are dropped, after \
+     being passed to the `on_unexpected` hook if one is set.",
+    wire
+  );
+  let sink = quote! { ::enum_group_macros::__private::futures_sink::Sink };
+
+  // Polls `method` on every inner sink, pending until all of them are ready
+  let poll_all = |method: TokenStream2| {
+    quote! {
+        let this = self.get_mut();
+        let mut pending = false;
+        #(
+            match #sink::#method(::core::pin::Pin::new(&mut this.#fields), cx) {
+                ::core::task::Poll::Ready(::core::result::Result::Ok(())) => {}
+                ::core::task::Poll::Ready(::core::result::Result::Err(e)) => {
+                    return ::core::task::Poll::Ready(::core::result::Result::Err(e));
+                }
+                ::core::task::Poll::Pending => pending = true,
+            }
+        )*
+        if pending {
+            ::core::task::Poll::Pending
+        } else {
+            ::core::task::Poll::Ready(::core::result::Result::Ok(()))
+        }
+    }
+  };
+  let poll_ready = poll_all(quote! { poll_ready });
+  let poll_flush = poll_all(quote! { poll_flush });
+  let poll_close = poll_all(quote! { poll_close });
+
+  quote! {
+      #cfg
+      #[doc = #doc]
+      #[derive(::core::fmt::Debug, ::core::clone::Clone)]
+      #vis struct #router<#(#params),*> {
+          #(
+              #[doc = #field_docs]
+              #vis #fields: #params,
+          )*
+          #hook_field
+      }
+
+      #cfg
+      impl<#(#params),*> #router<#(#params),*> {
+          /// Wraps one sink per group, in declaration order.
+          #vis fn new(#(#fields: #params),*) -> Self {
+              Self { #(#fields,)* #hook_init }
+          }
+
+          #hook_setter
+      }
+
+      #cfg
+      impl<#(#params,)* E> #sink<#wire> for #router<#(#params),*>
+      where
+          #(#params: #sink<#group_tys, Error = E> + ::core::marker::Unpin,)*
+      {
+          type Error = E;
+
+          fn poll_ready(
+              self: ::core::pin::Pin<&mut Self>,
+              cx: &mut ::core::task::Context<'_>,
+          ) -> ::core::task::Poll<::core::result::Result<(), E>> {
+              #poll_ready
+          }
+
+          fn start_send(
+              self: ::core::pin::Pin<&mut Self>,
+              msg: #wire,
+          ) -> ::core::result::Result<(), E> {
+              let this = self.get_mut();
+              match #into_group {
+                  #(
+                      #dispatch::#groups(group) => {
+                          #sink::start_send(::core::pin::Pin::new(&mut this.#fields), group)
+                      }
+                  )*
+                  #undispatched_arm
+              }
+          }
+
+          fn poll_flush(
+              self: ::core::pin::Pin<&mut Self>,
+              cx: &mut ::core::task::Context<'_>,
+          ) -> ::core::task::Poll<::core::result::Result<(), E>> {
+              #poll_flush
+          }
+
+          fn poll_close(
+              self: ::core::pin::Pin<&mut Self>,
+              cx: &mut ::core::task::Context<'_>,
+          ) -> ::core::task::Poll<::core::result::Result<(), E>> {
+              #poll_close
+          }
+      }
+  }
+}
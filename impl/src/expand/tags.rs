@@ -0,0 +1,100 @@
+//! Static tables of the tags variants are serialized under.
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::LitByteStr;
+
+use super::Context;
+use crate::parse::ParsedVariant;
+
+/// Generates `TAG_TABLE` on the wire enum and on every group enum, and the wire enum's
+/// `WIRE_FINGERPRINT` and `assert_tags()`.
+pub(crate) fn generate(cx: &Context) -> TokenStream2 {
+  let vis = &cx.input.vis;
+  let wire = cx.wire;
+  let wire_ty = &cx.wire_ty;
+  let (impl_generics, where_clause) = cx.impl_generics();
+  let kind = &cx.kind;
+
+  let wire_table = tag_table(cx, cx.tagged_variants().map(|(_, v)| v));
+  let fingerprint = fingerprint(cx);
+  let wire_name = wire.to_string();
+  let group_tags = cx.tagged_variants().map(|(g, v)| {
+    let group = g.name.unraw().to_string();
+    let (tag, cfg) = (cx.tag(v), v.cfg());
+    quote! { #cfg (#group, #tag) }
+  });
+  // A shared group's table would be defined once per wire enum, with its kinds, and the
+  // `#[unknown]` group has no tag
+  let group_tables = cx.input.groups.iter().filter(|g| !g.shared && !g.unknown).map(|g| {
+    let g_ty = cx.group_ty(g);
+    let generics = cx.group_generics(g);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let table = tag_table(cx, g.variants.iter());
+    quote! {
+        impl #impl_generics #g_ty #where_clause {
+            /// Every variant kind of this group paired with the tag it is serialized under.
+            #vis const TAG_TABLE: &'static [(#kind, &'static str)] = #table;
+        }
+    }
+  });
+
+  quote! {
+      impl #impl_generics #wire_ty #where_clause {
+          /// Every variant kind paired with the tag it is serialized under, in declaration
+          /// order. The `#[unknown]` catch-all, having no tag, is left out.
+          #vis const TAG_TABLE: &'static [(#kind, &'static str)] = #wire_table;
+
+          /// A hash of the group names, variant names and tags, in declaration order.
+          ///
+          /// Peers built from the same definition agree on it, so comparing fingerprints at
+          /// handshake detects protocol mismatches. Variants configured out by `#[cfg]` are
+          /// left out. Payload types are not covered.
+          #vis const WIRE_FINGERPRINT: u64 = #fingerprint;
+
+          /// Panics with a diff unless the `(group, tag)` pairs of all variants, in
+          /// declaration order, equal `expected`, e.g. the tags frozen at the last release.
+          #[track_caller]
+          #vis fn assert_tags(expected: &[(&str, &str)]) {
+              ::enum_group_macros::__private::tags::assert_tags(
+                  #wire_name,
+                  &[#(#group_tags),*],
+                  expected,
+              );
+          }
+      }
+
+      #(#group_tables)*
+  }
+}
+
+/// `&[(Kind::A, "A"), ...]` for the given variants.
+fn tag_table<'a>(cx: &Context, variants: impl Iterator<Item = &'a ParsedVariant>) -> TokenStream2 {
+  let kind = &cx.kind;
+  let entries = variants.map(|v| {
+    let (v_name, cfg) = (&v.name, v.cfg());
+    let tag = cx.tag(v);
+    quote! { #cfg (#kind::#v_name, #tag) }
+  });
+  quote! { &[#(#entries),*] }
+}
+
+/// The hash of the layout of the wire enum, computed at compile time from one part per
+/// group delimiter and per variant, gated like the variant so that configured-out variants
+/// do not count. Every name is terminated by a separator byte that cannot occur in it, so
+/// that moving a character between names changes the hash.
+fn fingerprint(cx: &Context) -> TokenStream2 {
+  let span = Span::call_site();
+  let parts = cx.input.groups.iter().flat_map(|g| {
+    let open = LitByteStr::new(format!("{}{{", g.name).as_bytes(), span);
+    let variants = g.variants.iter().map(move |v| {
+      let part = LitByteStr::new(format!("{}={};", v.name, cx.tag(v)).as_bytes(), span);
+      let cfg = v.cfg();
+      quote! { #cfg #part }
+    });
+    let close = LitByteStr::new(b"}", span);
+    std::iter::once(quote! { #open }).chain(variants).chain(std::iter::once(quote! { #close }))
+  });
+  quote! { ::enum_group_macros::__private::tags::fingerprint(&[#(#parts),*]) }
+}
@@ -7,218 +7,20 @@
 
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
-use quote::{format_ident, quote};
-use syn::parse::{Parse, ParseStream};
-use syn::{braced, parse_macro_input, Attribute, Ident, Token, Type, Visibility};
+use syn::parse_macro_input;
 
-// =============================================================================
-// Custom Syntax Parser
-// =============================================================================
-
-/// Parsed representation of a single variant within a group
-#[derive(Debug)]
-struct ParsedVariant {
-  attrs: Vec<Attribute>,
-  name: Ident,
-  ty: Type,
-}
-
-/// Parsed representation of a group (e.g., `SupportMessage { ... }`)
-#[derive(Debug)]
-struct ParsedGroup {
-  name: Ident,
-  variants: Vec<ParsedVariant>,
-}
-
-/// Parsed input for `define_enum_group!`
-#[derive(Debug)]
-struct EnumGroupInput {
-  attrs: Vec<Attribute>,
-  vis: Visibility,
-  name: Ident,
-  groups: Vec<ParsedGroup>,
-}
-
-impl Parse for ParsedVariant {
-  fn parse(input: ParseStream) -> syn::Result<Self> {
-    let attrs = input.call(Attribute::parse_outer)?;
-    let name: Ident = input.parse()?;
-
-    // Parse (Type)
-    let content;
-    syn::parenthesized!(content in input);
-    let ty: Type = content.parse()?;
-
-    Ok(ParsedVariant { attrs, name, ty })
-  }
-}
-
-impl Parse for ParsedGroup {
-  fn parse(input: ParseStream) -> syn::Result<Self> {
-    let name: Ident = input.parse()?;
-
-    let content;
-    braced!(content in input);
-
-    let mut variants = Vec::new();
-    while !content.is_empty() {
-      variants.push(content.parse::<ParsedVariant>()?);
-      // Optional trailing comma
-      if content.peek(Token![,]) {
-        content.parse::<Token![,]>()?;
-      }
-    }
-
-    Ok(ParsedGroup { name, variants })
-  }
-}
-
-impl Parse for EnumGroupInput {
-  fn parse(input: ParseStream) -> syn::Result<Self> {
-    // Parse outer attributes (like #[derive(...)])
-    let attrs = input.call(Attribute::parse_outer)?;
-
-    // Parse visibility and enum keyword
-    let vis: Visibility = input.parse()?;
-    input.parse::<Token![enum]>()?;
-    let name: Ident = input.parse()?;
-
-    // Parse the groups inside braces
-    let content;
-    braced!(content in input);
+mod expand;
+mod match_group;
+mod migrate;
+mod options;
+mod parse;
+mod serde_attrs;
+mod util;
 
-    let mut groups = Vec::new();
-    while !content.is_empty() {
-      groups.push(content.parse::<ParsedGroup>()?);
-      // Handle optional comma between groups
-      if content.peek(Token![,]) {
-        content.parse::<Token![,]>()?;
-      }
-    }
-
-    Ok(EnumGroupInput { attrs, vis, name, groups })
-  }
-}
-
-// =============================================================================
-// Code Generator
-// =============================================================================
-
-fn generate_enum_group(input: EnumGroupInput) -> TokenStream2 {
-  let EnumGroupInput { attrs, vis, name: wire_name, groups } = input;
-
-  let group_enum_name = format_ident!("{}Group", wire_name);
-
-  // Collect all variants for the flat wire enum
-  let mut all_variants = Vec::new();
-  let mut group_enum_variants = Vec::new();
-  let mut into_group_arms = Vec::new();
-
-  // Generate group enums and collect info
-  let group_enums: Vec<TokenStream2> = groups
-    .iter()
-    .map(|group| {
-      let group_name = &group.name;
-
-      // Variants for this group enum
-      let variants: Vec<TokenStream2> = group
-        .variants
-        .iter()
-        .map(|v| {
-          let v_attrs = &v.attrs;
-          let v_name = &v.name;
-          let v_ty = &v.ty;
-          quote! {
-              #(#v_attrs)*
-              #v_name(#v_ty)
-          }
-        })
-        .collect();
-
-      // Add to all_variants for wire enum
-      for v in &group.variants {
-        let v_attrs = &v.attrs;
-        let v_name = &v.name;
-        let v_ty = &v.ty;
-        all_variants.push(quote! {
-            #(#v_attrs)*
-            #v_name(#v_ty)
-        });
-
-        // Generate into_group arm
-        into_group_arms.push(quote! {
-            Self::#v_name(v) => #group_enum_name::#group_name(#group_name::#v_name(v))
-        });
-      }
-
-      // Add to group enum variants
-      group_enum_variants.push(quote! {
-          #group_name(#group_name)
-      });
-
-      // Generate the group enum
-      quote! {
-          #(#attrs)*
-          #vis enum #group_name {
-              #(#variants),*
-          }
-      }
-    })
-    .collect();
-
-  // Generate the flat wire enum
-  let wire_enum = quote! {
-      #(#attrs)*
-      #vis enum #wire_name {
-          #(#all_variants),*
-      }
-  };
-
-  // Generate the group dispatch enum
-  let group_dispatch_enum = quote! {
-      #[derive(Debug, Clone)]
-      #vis enum #group_enum_name {
-          #(#group_enum_variants),*
-      }
-  };
-
-  // Generate an inherent into_group method (doesn't require trait import)
-  let inherent_impl = quote! {
-      impl #wire_name {
-          /// Convert this enum into its grouped representation.
-          #vis fn into_group(self) -> #group_enum_name {
-              match self {
-                  #(#into_group_arms),*
-              }
-          }
-      }
-  };
-
-  // Generate the EnumGroup trait impl (for users who want trait-based access)
-  let trait_impl = quote! {
-      impl ::enum_group_macros::EnumGroup for #wire_name {
-          type Group = #group_enum_name;
-
-          fn into_group(self) -> Self::Group {
-              // Delegate to inherent method
-              #wire_name::into_group(self)
-          }
-      }
-  };
-
-  // Combine all generated code
-  quote! {
-      #(#group_enums)*
-
-      #wire_enum
-
-      #group_dispatch_enum
-
-      #inherent_impl
-
-      #trait_impl
-  }
-}
+use expand::{generate_enum_group, generate_enum_group_pair, generate_enum_groups};
+use match_group::{parse_match_enum_group, parse_match_enum_group_pair};
+use migrate::parse_migrate_enum_group;
+use parse::{DefineEnumGroups, EnumGroupInput, EnumGroupPairInput};
 
 // =============================================================================
 // Procedural Macro Entry Point
@@ -231,6 +33,7 @@ fn generate_enum_group(input: EnumGroupInput) -> TokenStream2 {
 /// 2. A single flat "wire" enum containing all variants from all groups.
 /// 3. A `Group` enum for dispatch between groups.
 /// 4. An `EnumGroup` trait implementation for converting wire → group.
+/// 5. Fieldless kind enums naming each variant and each group.
 ///
 /// # Example
 ///
@@ -257,12 +60,107 @@ fn generate_enum_group(input: EnumGroupInput) -> TokenStream2 {
 /// - `enum Protocol { A(MsgA), B(MsgB) }` - categorical enum
 /// - `enum Business { C(MsgC) }` - categorical enum
 /// - `enum WireMsg { A(MsgA), B(MsgB), C(MsgC) }` - flat wire enum
-/// - `enum WireMsgGroup { Protocol(Protocol), Business(Business) }` - dispatch enum
-/// - `impl EnumGroup for WireMsg` - conversion trait
+/// - `enum WireMsgGroup { Protocol(Protocol), Business(Business) }` - dispatch enum (renamed
+///   with `dispatch_name`)
+/// - `impl EnumGroup for WireMsg` - conversion trait, `into_group` and back with `from_group`
+/// - `impl From<WireMsgGroup> for WireMsg` - the lossless way back without the trait, also
+///   generated with `skip_trait`
+/// - `enum WireMsgKind { A, B, C }` and `enum WireMsgGroupKind { Protocol, Business }` -
+///   fieldless kinds, mapped onto each other by `WireMsg::group_of_kind`. Whatever the wire
+///   enum derives, both derive `Debug`, `Clone`, `Copy`, `PartialEq`, `Eq`, `PartialOrd`,
+///   `Ord` (in declaration order) and `Hash`, to be used as map keys
+///
+/// See the `enum-group-macros` crate documentation for the other generated items, the
+/// declaration syntax and every option.
 #[proc_macro]
 pub fn define_enum_group(input: TokenStream) -> TokenStream {
-  let input = parse_macro_input!(input as EnumGroupInput);
-  generate_enum_group(input).into()
+  let input = parse_macro_input!(input as DefineEnumGroups);
+  generate_enum_groups(&input).into()
+}
+
+/// Defines two wire enums with mirrored groups, such as the commands and events of a
+/// CQRS setup, so that their group structure cannot drift apart.
+///
+/// Each enum is written and expanded like an input of [`define_enum_group!`], and both
+/// must declare the same group names. To keep the two sets of group enums apart, their
+/// names are suffixed with the name of their wire enum (`Account` becomes
+/// `AccountCommand` and `AccountEvent`). Additionally generated:
+///
+/// - `enum CommandEventGroupKind { Account, ... }` - the group kind shared by both enums,
+///   convertible `From` either `CommandGroupKind` or `EventGroupKind`, and back through
+///   `.command()` / `.event()`
+/// - `CommandGroupKind::counterpart()` / `EventGroupKind::counterpart()` - the
+///   corresponding group of the other enum
+///
+/// # Example
+///
+/// ```ignore
+/// use enum_group_macros::define_enum_group_pair;
+///
+/// define_enum_group_pair! {
+///     #[derive(Debug, Clone)]
+///     pub enum Command {
+///         Account { Open(OpenAccount), Close(CloseAccount) },
+///         Billing { Charge(Charge) },
+///     }
+///
+///     #[derive(Debug, Clone)]
+///     pub enum Event {
+///         Account { Opened(AccountOpened), Closed(AccountClosed) },
+///         Billing { Charged(Charged) },
+///     }
+/// }
+///
+/// assert_eq!(CommandGroupKind::AccountCommand.counterpart(), EventGroupKind::AccountEvent);
+/// ```
+#[proc_macro]
+pub fn define_enum_group_pair(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as EnumGroupPairInput);
+  generate_enum_group_pair(&input).into()
+}
+
+/// Derives the items of [`define_enum_group!`] for an ordinary enum, whose variants name
+/// their group with `#[group(Name)]`.
+///
+/// The enum stays as written, so rustfmt and rust-analyzer see plain Rust, and the group
+/// enums, the dispatch enum, `into_group` and the kinds are generated next to it. The
+/// variants of a group must be declared together, and groups appear in the order of their
+/// first variant. `#[enum_group(...)]` takes the options of [`define_enum_group!`], except
+/// `wire_attr(...)` and `group_index_table`; `#[variant(...)]` and `#[meta(...)]` work on
+/// the variants as well. Nested groups and group options are not available.
+///
+/// A derive cannot see the other derives of its enum. The group enums derive `Debug` and
+/// `Clone`, get the other outer attributes such as `#[serde(...)]`, and take further
+/// derives from `group_attr(...)`, e.g. `#[enum_group(group_attr(derive(Serialize)))]`.
+///
+/// # Example
+///
+/// ```ignore
+/// use enum_group_macros::EnumGroup;
+///
+/// #[derive(Debug, Clone, EnumGroup)]
+/// pub enum WireMsg {
+///     #[group(Protocol)]
+///     A(MsgA),
+///     #[group(Protocol)]
+///     B(MsgB),
+///     #[group(Business)]
+///     C(MsgC),
+/// }
+///
+/// match WireMsg::C(MsgC).into_group() {
+///     WireMsgGroup::Protocol(Protocol::A(a)) => handle_a(a),
+///     WireMsgGroup::Protocol(Protocol::B(b)) => handle_b(b),
+///     WireMsgGroup::Business(Business::C(c)) => handle_c(c),
+/// }
+/// ```
+#[proc_macro_derive(EnumGroup, attributes(enum_group, group, variant, meta))]
+pub fn derive_enum_group(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as syn::DeriveInput);
+  match EnumGroupInput::from_derive(input) {
+    Ok(input) => generate_enum_group(&input).into(),
+    Err(error) => error.to_compile_error().into(),
+  }
 }
 
 // =============================================================================
@@ -290,6 +188,51 @@ pub fn define_enum_group(input: TokenStream) -> TokenStream {
 ///     BusinessMessage(b) => handle_business(b),
 /// })
 /// ```
+///
+/// The value may also be a `Result` of the wire enum, matched with an additional
+/// `Err(e) => ...` arm, which saves unwrapping a deserialization result first. To propagate
+/// the error instead, pass `result?` as the value. Likewise, an `Option` of the wire enum
+/// is matched with a `None => ...` arm, e.g. for a closed channel, and a
+/// `Result<Option<_>, _>` with both. Groups may therefore not be named `Err` or `None`.
+///
+/// A final `_ => ...` arm catches every group without an arm of its own, as well as the
+/// messages of groups left out of dispatch with `#[group(skip_dispatch)]`.
+///
+/// Prefixing the value with `box`, after `async` if any, takes the message out of a `Box` of the wire enum, as in
+/// `match_enum_group!(box boxed, WireMsg, { ... })`; see `Scrutinee` for matching an
+/// `Arc` of it. Without `box`, the value is the wire enum itself and its type may be left
+/// to inference, e.g. `serde_json::from_str(json)?`.
+///
+/// Prefixing the value with `async` switches to async mode: every arm yields a future,
+/// and the macro evaluates to a single `async move` block awaiting the one of the matched
+/// arm, so arms may return futures of different types without boxing:
+///
+/// ```ignore
+/// let handled = match_enum_group!(async msg, WireMsg, {
+///     Protocol(p) => handle_protocol(p),
+///     Business(b) => async move { store(b).await },
+/// });
+/// handled.await;
+/// ```
+///
+/// Without a value, `match_enum_group!(WireMsg, { ... })` builds an arm set instead: a
+/// closure taking the group enum of `WireMsg`. Generic code, which cannot name the groups
+/// of a type parameter `T: EnumGroup`, takes an arm set from its caller and applies it with
+/// `match_enum_group!(msg, T, arms)`:
+///
+/// ```ignore
+/// fn traced<T: EnumGroup + Debug, R>(msg: T, arms: impl FnOnce(T::Group) -> R) -> R {
+///     tracing::debug!(?msg, "dispatching");
+///     match_enum_group!(msg, T, arms)
+/// }
+///
+/// traced(msg, match_enum_group!(WireMsg, {
+///     Protocol(p) => handle_protocol(p),
+///     Business(b) => handle_business(b),
+/// }));
+/// ```
+///
+/// The `Err` and `None` arms are not available for arm sets.
 #[proc_macro]
 pub fn match_enum_group(input: TokenStream) -> TokenStream {
   let input2: TokenStream2 = input.into();
@@ -302,81 +245,61 @@ pub fn match_enum_group(input: TokenStream) -> TokenStream {
   }
 }
 
-/// Parsed match arm for match_enum_group!
-struct MatchArm {
-  group_name: Ident,
-  binding: proc_macro2::TokenStream,
-  body: TokenStream2,
+/// Matches on the groups of two wire values at once.
+///
+/// Each arm names a group (or `_`) for both values, optionally followed by an `if` guard;
+/// a bare `_` arm catches every remaining combination. Both values must be of the same
+/// wire enum.
+///
+/// # Example
+///
+/// ```ignore
+/// use enum_group_macros::match_enum_group_pair;
+///
+/// match_enum_group_pair!((req, resp), WireMsg, {
+///     (Protocol(a), Protocol(b)) => correlate_protocol(a, b),
+///     (Business(a), Business(b)) => correlate_business(a, b),
+///     (_, Errors(e)) => report(e),
+///     _ => mismatch(),
+/// })
+/// ```
+#[proc_macro]
+pub fn match_enum_group_pair(input: TokenStream) -> TokenStream {
+  match parse_match_enum_group_pair(input.into()) {
+    Ok(tokens) => tokens.into(),
+    Err(e) => e.to_compile_error().into(),
+  }
 }
 
-fn parse_match_enum_group(input: TokenStream2) -> syn::Result<TokenStream2> {
-  use syn::parse::Parser;
-
-  let parser = |input: ParseStream| -> syn::Result<(syn::Expr, Ident, Vec<MatchArm>)> {
-    // Parse value expression
-    let val: syn::Expr = input.parse()?;
-    input.parse::<Token![,]>()?;
-
-    // Parse wire enum type (just the identifier)
-    let wire: Ident = input.parse()?;
-    input.parse::<Token![,]>()?;
-
-    // Parse arms block
-    let content;
-    braced!(content in input);
-
-    let mut arms = Vec::new();
-    while !content.is_empty() {
-      // Parse: GroupName(binding) => body
-      let group_name: Ident = content.parse()?;
-
-      let paren_content;
-      syn::parenthesized!(paren_content in content);
-      // Parse the binding pattern (can be complex like `s` or `_`)
-      let binding: proc_macro2::TokenStream = paren_content.parse()?;
-
-      content.parse::<Token![=>]>()?;
-
-      // Parse the body (could be a block or expression)
-      let body: syn::Expr = content.parse()?;
-
-      arms.push(MatchArm { group_name, binding, body: quote! { #body } });
-
-      // Optional trailing comma
-      if content.peek(Token![,]) {
-        content.parse::<Token![,]>()?;
-      }
-    }
-
-    Ok((val, wire, arms))
-  };
-
-  let (val, wire, arms) = parser.parse2(input)?;
-
-  // Generate match arms using the local type alias
-  let match_arms: Vec<TokenStream2> = arms
-    .iter()
-    .map(|arm| {
-      let group_name = &arm.group_name;
-      let binding = &arm.binding;
-      let body = &arm.body;
-
-      quote! {
-          __EnumGroup__::#group_name(#binding) => #body
-      }
-    })
-    .collect();
-
-  // Generate expansion with local type alias
-  // This avoids requiring users to import the Group type
-  Ok(quote! {
-      {
-          #[allow(non_camel_case_types)]
-          type __EnumGroup__ = <#wire as ::enum_group_macros::EnumGroup>::Group;
+// =============================================================================
+// migrate_enum_group! Macro
+// =============================================================================
 
-          match <#wire as ::enum_group_macros::EnumGroup>::into_group(#val) {
-              #(#match_arms),*
-          }
-      }
-  })
+/// Converts messages of one version of a wire enum into the next.
+///
+/// Every variant of the old enum must be mapped, either to a variant of the new enum, whose
+/// payload is built with `From` from the old payload, or to `drop`. The generated match has
+/// no fallback, so a variant missing from the list is a compile error. Without `drop`
+/// entries this implements `From<Old> for New`; with them `TryFrom<Old> for New`, handing
+/// dropped messages back as the error.
+///
+/// # Example
+///
+/// ```ignore
+/// use enum_group_macros::migrate_enum_group;
+///
+/// migrate_enum_group!(WireMsgV1 => WireMsgV2 {
+///     A => A,
+///     B => BRenamed,
+///     C => drop,
+/// });
+///
+/// let v2 = WireMsgV2::try_from(v1)?;
+/// ```
+#[proc_macro]
+pub fn migrate_enum_group(input: TokenStream) -> TokenStream {
+  match parse_migrate_enum_group(input.into()) {
+    Ok(tokens) => tokens.into(),
+    Err(e) => e.to_compile_error().into(),
+  }
 }
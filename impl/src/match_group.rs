@@ -0,0 +1,368 @@
+//! Parser and expansion for `match_enum_group!`.
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::{braced, Ident, Token};
+
+/// Parsed match arm for match_enum_group!
+struct MatchArm {
+  group_name: Ident,
+  binding: proc_macro2::TokenStream,
+  body: TokenStream2,
+}
+
+/// What follows the wire type in `match_enum_group!`.
+enum MatchBody {
+  /// `{ Group(binding) => body, ... }`
+  Arms(Vec<MatchArm>),
+  /// An arm set built elsewhere, e.g. passed into a generic function
+  ArmSet(syn::Expr),
+}
+
+/// `match_enum_group!` input: the `async` and `box` markers, the value, the wire type and
+/// what follows it.
+type MatchInput = (bool, bool, Option<syn::Expr>, Ident, MatchBody);
+
+/// Parses `{ Group(binding) => body, ... }`.
+fn parse_arms(input: ParseStream) -> syn::Result<Vec<MatchArm>> {
+  let content;
+  braced!(content in input);
+
+  let mut arms = Vec::new();
+  while !content.is_empty() {
+    // `_ => body` catches the remaining groups, including the `Undispatched` messages
+    if let Some(underscore) = content.parse::<Option<Token![_]>>()? {
+      content.parse::<Token![=>]>()?;
+      let body: syn::Expr = content.parse()?;
+      let group_name = Ident::new("_", underscore.span);
+      arms.push(MatchArm { group_name, binding: TokenStream2::new(), body: quote! { #body } });
+      if content.peek(Token![,]) {
+        content.parse::<Token![,]>()?;
+      }
+      continue;
+    }
+
+    // Parse: GroupName(binding) => body
+    let group_name: Ident = content.parse()?;
+
+    // `None => body` has no binding
+    if group_name == "None" && !content.peek(syn::token::Paren) {
+      content.parse::<Token![=>]>()?;
+      let body: syn::Expr = content.parse()?;
+      arms.push(MatchArm { group_name, binding: TokenStream2::new(), body: quote! { #body } });
+      if content.peek(Token![,]) {
+        content.parse::<Token![,]>()?;
+      }
+      continue;
+    }
+
+    let paren_content;
+    syn::parenthesized!(paren_content in content);
+    // Parse the binding pattern (can be complex like `s` or `_`)
+    let binding: proc_macro2::TokenStream = paren_content.parse()?;
+
+    content.parse::<Token![=>]>()?;
+
+    // Parse the body (could be a block or expression)
+    let body: syn::Expr = content.parse()?;
+
+    arms.push(MatchArm { group_name, binding, body: quote! { #body } });
+
+    // Optional trailing comma
+    if content.peek(Token![,]) {
+      content.parse::<Token![,]>()?;
+    }
+  }
+  Ok(arms)
+}
+
+pub(crate) fn parse_match_enum_group(input: TokenStream2) -> syn::Result<TokenStream2> {
+  use syn::parse::Parser;
+
+  let parser = |input: ParseStream| -> syn::Result<MatchInput> {
+    // Parse the optional `async` mode and `box` unwrapping markers and the value expression
+    let is_async = input.peek(Token![async]);
+    if is_async {
+      input.parse::<Token![async]>()?;
+    }
+    let boxed = input.parse::<Option<Token![box]>>()?;
+
+    // `match_enum_group!(WireMsg, { ... })` builds an arm set without a value
+    if input.peek(Ident) && input.peek2(Token![,]) && input.peek3(syn::token::Brace) {
+      let wire: Ident = input.parse()?;
+      input.parse::<Token![,]>()?;
+      if is_async {
+        return Err(syn::Error::new_spanned(&wire, "an arm set cannot be `async`"));
+      }
+      if let Some(boxed) = boxed {
+        return Err(syn::Error::new_spanned(boxed, "an arm set cannot be `box`"));
+      }
+      let arms = parse_arms(input)?;
+      input.parse::<Option<Token![,]>>()?;
+      return Ok((false, false, None, wire, MatchBody::Arms(arms)));
+    }
+
+    let val: syn::Expr = input.parse()?;
+    input.parse::<Token![,]>()?;
+
+    // Parse wire enum type (just the identifier)
+    let wire: Ident = input.parse()?;
+    input.parse::<Token![,]>()?;
+
+    // Parse arms block, or an arm set expression
+    let body = if input.peek(syn::token::Brace) {
+      MatchBody::Arms(parse_arms(input)?)
+    } else {
+      let arm_set: syn::Expr = input.parse()?;
+      input.parse::<Option<Token![,]>>()?;
+      MatchBody::ArmSet(arm_set)
+    };
+
+    Ok((is_async, boxed.is_some(), Some(val), wire, body))
+  };
+
+  let (is_async, boxed, val, wire, body) = parser.parse2(input)?;
+  // Plain values keep a type inferred from the wire enum; `box` ones go through `Scrutinee`
+  let into_wire = |val: TokenStream2| {
+    if boxed {
+      quote! { ::enum_group_macros::Scrutinee::<#wire>::into_wire(#val) }
+    } else {
+      val
+    }
+  };
+  let map_async = |expansion: TokenStream2| {
+    if is_async {
+      quote! { async move { #expansion } }
+    } else {
+      expansion
+    }
+  };
+
+  let mut arms = match body {
+    // The wire type may be a type parameter here, so expand through the trait only
+    MatchBody::ArmSet(arm_set) => {
+      let val = into_wire(quote! { #val });
+      let call = quote! {
+          (#arm_set)(<#wire as ::enum_group_macros::EnumGroup>::into_group(#val))
+      };
+      return Ok(map_async(if is_async {
+        quote! { (#call).await }
+      } else {
+        call
+      }));
+    }
+    MatchBody::Arms(arms) => arms,
+  };
+
+  // In async mode every arm yields a future, awaited inside one async block so that arms
+  // may produce futures of different types
+  if is_async {
+    for arm in &mut arms {
+      let body = &arm.body;
+      arm.body = quote! { (#body).await };
+    }
+  }
+
+  // An `Err(e)` arm makes the scrutinee a `Result` of the wire enum, a `None` arm an
+  // `Option` of it, and both a `Result` of an `Option`
+  let (err_arms, arms): (Vec<_>, Vec<_>) =
+    arms.into_iter().partition(|arm| arm.group_name == "Err");
+  let (none_arms, arms): (Vec<_>, Vec<_>) =
+    arms.into_iter().partition(|arm| arm.group_name == "None" && arm.binding.is_empty());
+  if let Some(duplicate) = err_arms.get(1).or(none_arms.get(1)) {
+    let name = &duplicate.group_name;
+    return Err(syn::Error::new_spanned(name, format!("duplicate `{name}` arm")));
+  }
+
+  // Generate match arms using the local type alias
+  let match_arms: Vec<TokenStream2> = arms
+    .iter()
+    .map(|arm| {
+      let group_name = &arm.group_name;
+      let binding = &arm.binding;
+      let body = &arm.body;
+
+      if group_name == "_" {
+        return quote! { _ => #body };
+      }
+      quote! {
+          __EnumGroup__::#group_name(#binding) => #body
+      }
+    })
+    .collect();
+
+  // Mixed-site hygiene keeps the unwrapped values out of reach of the arm bodies
+  let msg = Ident::new("msg", Span::mixed_site());
+
+  // An arm set is a closure over the group enum, for passing into generic code
+  let Some(val) = val else {
+    if let Some(arm) = err_arms.first().or(none_arms.first()) {
+      let name = &arm.group_name;
+      return Err(syn::Error::new_spanned(name, format!("an arm set cannot have a `{name}` arm")));
+    }
+    return Ok(quote! {
+        {
+            #[allow(non_camel_case_types)]
+            type __EnumGroup__ = <#wire as ::enum_group_macros::EnumGroup>::Group;
+
+            |#msg: __EnumGroup__| match #msg {
+                #(#match_arms),*
+            }
+        }
+    });
+  };
+
+  let match_groups = |val: TokenStream2| {
+    let val = into_wire(val);
+    quote! {
+        match <#wire as ::enum_group_macros::EnumGroup>::into_group(#val) {
+            #(#match_arms),*
+        }
+    }
+  };
+  let option = Ident::new("option", Span::mixed_site());
+  let match_option = |val: TokenStream2| match none_arms.first() {
+    Some(MatchArm { body, .. }) => {
+      let match_groups = match_groups(quote! { #msg });
+      quote! {
+          match #val {
+              ::core::option::Option::Some(#msg) => #match_groups,
+              ::core::option::Option::None => #body,
+          }
+      }
+    }
+    None => match_groups(val),
+  };
+  let expansion = match err_arms.first() {
+    Some(MatchArm { binding, body, .. }) => {
+      let match_option = match_option(quote! { #option });
+      quote! {
+          match #val {
+              ::core::result::Result::Ok(#option) => #match_option,
+              ::core::result::Result::Err(#binding) => #body,
+          }
+      }
+    }
+    None => match_option(quote! { #val }),
+  };
+
+  // Generate expansion with local type alias
+  // This avoids requiring users to import the Group type
+  let expansion = quote! {
+      {
+          #[allow(non_camel_case_types)]
+          type __EnumGroup__ = <#wire as ::enum_group_macros::EnumGroup>::Group;
+
+          #expansion
+      }
+  };
+  Ok(map_async(expansion))
+}
+
+/// One side of a `match_enum_group_pair!` arm: `Group(binding)` or `_`.
+enum PairSide {
+  Group { group_name: Ident, binding: TokenStream2 },
+  Wildcard,
+}
+
+impl PairSide {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    if input.peek(Token![_]) {
+      input.parse::<Token![_]>()?;
+      return Ok(PairSide::Wildcard);
+    }
+    let group_name: Ident = input.parse()?;
+    let content;
+    syn::parenthesized!(content in input);
+    Ok(PairSide::Group { group_name, binding: content.parse()? })
+  }
+
+  fn pattern(&self) -> TokenStream2 {
+    match self {
+      PairSide::Group { group_name, binding } => quote! { __EnumGroup__::#group_name(#binding) },
+      PairSide::Wildcard => quote! { _ },
+    }
+  }
+}
+
+pub(crate) fn parse_match_enum_group_pair(input: TokenStream2) -> syn::Result<TokenStream2> {
+  use syn::parse::Parser;
+
+  let parser =
+    |input: ParseStream| -> syn::Result<(syn::Expr, syn::Expr, Ident, Vec<TokenStream2>)> {
+      // Parse the `(left, right)` value pair
+      let values;
+      syn::parenthesized!(values in input);
+      let left: syn::Expr = values.parse()?;
+      values.parse::<Token![,]>()?;
+      let right: syn::Expr = values.parse()?;
+      if values.peek(Token![,]) {
+        values.parse::<Token![,]>()?;
+      }
+      input.parse::<Token![,]>()?;
+
+      // Parse wire enum type (just the identifier)
+      let wire: Ident = input.parse()?;
+      input.parse::<Token![,]>()?;
+
+      // Parse arms block
+      let content;
+      braced!(content in input);
+
+      let mut arms = Vec::new();
+      while !content.is_empty() {
+        // Parse: (Left(a), Right(b)) => body, or _ => body
+        let pattern = if content.peek(Token![_]) {
+          content.parse::<Token![_]>()?;
+          quote! { _ }
+        } else {
+          let sides;
+          syn::parenthesized!(sides in content);
+          let left = PairSide::parse(&sides)?.pattern();
+          sides.parse::<Token![,]>()?;
+          let right = PairSide::parse(&sides)?.pattern();
+          if sides.peek(Token![,]) {
+            sides.parse::<Token![,]>()?;
+          }
+          quote! { (#left, #right) }
+        };
+
+        // Optional match guard
+        let guard = if content.peek(Token![if]) {
+          content.parse::<Token![if]>()?;
+          let condition: syn::Expr = content.parse()?;
+          Some(quote! { if #condition })
+        } else {
+          None
+        };
+
+        content.parse::<Token![=>]>()?;
+        let body: syn::Expr = content.parse()?;
+        arms.push(quote! { #pattern #guard => #body });
+
+        // Optional trailing comma
+        if content.peek(Token![,]) {
+          content.parse::<Token![,]>()?;
+        }
+      }
+
+      Ok((left, right, wire, arms))
+    };
+
+  let (left, right, wire, arms) = parser.parse2(input)?;
+
+  Ok(quote! {
+      {
+          #[allow(non_camel_case_types)]
+          type __EnumGroup__ = <#wire as ::enum_group_macros::EnumGroup>::Group;
+
+          match (
+              <#wire as ::enum_group_macros::EnumGroup>::into_group(#left),
+              <#wire as ::enum_group_macros::EnumGroup>::into_group(#right),
+          ) {
+              #(#arms),*
+          }
+      }
+  })
+}
@@ -0,0 +1,90 @@
+//! Parser and expansion for `migrate_enum_group!`.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse::{ParseStream, Parser};
+use syn::{braced, Ident, Path, Token};
+
+/// Parsed `Old => New` or `Old => drop` entry of migrate_enum_group!
+struct Mapping {
+  from: Ident,
+  /// `None` for `drop`.
+  to: Option<Ident>,
+}
+
+pub(crate) fn parse_migrate_enum_group(input: TokenStream2) -> syn::Result<TokenStream2> {
+  let parser = |input: ParseStream| -> syn::Result<(Path, Path, Vec<Mapping>)> {
+    // Parse `OldWire => NewWire`
+    let old: Path = input.parse()?;
+    input.parse::<Token![=>]>()?;
+    let new: Path = input.parse()?;
+
+    // Parse the variant mappings
+    let content;
+    braced!(content in input);
+
+    let mut mappings: Vec<Mapping> = Vec::new();
+    while !content.is_empty() {
+      let from: Ident = content.parse()?;
+      content.parse::<Token![=>]>()?;
+      let to: Ident = content.parse()?;
+      if mappings.iter().any(|m| m.from == from) {
+        return Err(syn::Error::new_spanned(from, "variant mapped more than once"));
+      }
+      let to = (to != "drop").then_some(to);
+      mappings.push(Mapping { from, to });
+
+      // Optional trailing comma
+      if content.peek(Token![,]) {
+        content.parse::<Token![,]>()?;
+      }
+    }
+
+    Ok((old, new, mappings))
+  };
+
+  let (old, new, mappings) = parser.parse2(input)?;
+
+  // The match on the old enum has no fallback, so rustc reports every unmapped variant
+  let converted = |to: &Ident| quote! { #new::#to(::core::convert::From::from(payload)) };
+
+  if mappings.iter().all(|m| m.to.is_some()) {
+    let arms = mappings.iter().filter_map(|m| {
+      let from = &m.from;
+      let converted = converted(m.to.as_ref()?);
+      Some(quote! { #old::#from(payload) => #converted })
+    });
+    return Ok(quote! {
+        impl ::core::convert::From<#old> for #new {
+            fn from(msg: #old) -> Self {
+                match msg {
+                    #(#arms),*
+                }
+            }
+        }
+    });
+  }
+
+  let arms = mappings.iter().map(|m| {
+    let from = &m.from;
+    match &m.to {
+      Some(to) => {
+        let converted = converted(to);
+        quote! { #old::#from(payload) => ::core::result::Result::Ok(#converted) }
+      }
+      None => quote! { dropped @ #old::#from(_) => ::core::result::Result::Err(dropped) },
+    }
+  });
+  Ok(quote! {
+      impl ::core::convert::TryFrom<#old> for #new {
+          /// The message of a dropped variant, handed back unchanged.
+          type Error = #old;
+
+          fn try_from(msg: #old) -> ::core::result::Result<Self, #old> {
+              match msg {
+                  #(#arms),*
+              }
+          }
+      }
+  })
+}
@@ -0,0 +1,560 @@
+//! Parsing of `#[enum_group(...)]`, `#[group(...)]` and `#[variant(...)]` options.
+//!
+//! Options are written as outer attributes on the wire enum, on a group or on a variant
+//! and are consumed by the macro, so they never reach the generated enums.
+
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use syn::meta::ParseNestedMeta;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Attribute, Ident, LitInt, LitStr, Meta, Path, Token, Type};
+
+/// Options controlling what `define_enum_group!` generates.
+#[derive(Debug, Default)]
+pub(crate) struct EnumGroupOptions {
+  /// Generate one module per group re-exporting the group enum and its payload types.
+  pub(crate) group_modules: bool,
+  /// Generate one zero-sized `GroupTag` marker type per group.
+  pub(crate) group_tags: bool,
+  /// Generate the borrowed `{Group}Ref<'msg>` / `{Group}Mut<'msg>` enums, `as_group()` and
+  /// `as_group_mut()`.
+  pub(crate) as_group: bool,
+  /// Generate `as_<variant>()` and `into_<variant>()` for single-payload variants.
+  pub(crate) accessors: bool,
+  /// Generate `impl Display for {Wire}` printing `Group::Variant`.
+  pub(crate) display: bool,
+  /// Generate `{Wire}Router` and `{Wire}AsyncRouter` dispatching messages to per-group
+  /// handlers.
+  pub(crate) router: bool,
+  /// Generate `{Wire}Filter` and `parse_filter()`.
+  pub(crate) filter: bool,
+  /// Generate `{Wire}Queues`, buffering messages in one queue per group.
+  pub(crate) queues: bool,
+  /// Generate `{Wire}Handlers`, optional closures per group with a fallback.
+  pub(crate) handlers: bool,
+  /// Generate `{Wire}KindMap<V>`, a map stored in an array indexed by variant.
+  pub(crate) kind_map: bool,
+  /// Generate `GROUP_INDEX_TABLE` and `group_index_fast()`, assigning the wire variants
+  /// their indices as discriminants.
+  pub(crate) group_index_table: Option<Span>,
+  /// Generate `into_payload_any()` and `payload_downcast()`.
+  pub(crate) payload_any: bool,
+  /// Generate the `{Wire}Handler` / `{Wire}AsyncHandler` traits with `dispatch` and
+  /// `dispatch_async`.
+  pub(crate) handler: bool,
+  /// Generate `{Wire}PartialHandler`, whose group methods default to a required
+  /// `handle_unhandled`, and `dispatch_partial`.
+  pub(crate) partial_handler: bool,
+  /// Generate `{Wire}VariantHandler` with one method per variant and `dispatch_variant`.
+  pub(crate) variant_handler: bool,
+  /// `handler(context)`: the handler traits are generic over a context type passed to
+  /// every method.
+  pub(crate) handler_context: bool,
+  /// Name of the generated inherent conversion method (defaults to `into_group`).
+  pub(crate) method: Option<Ident>,
+  /// `bare_payloads`: a bare `MsgA` declares `MsgA(MsgA)` rather than a unit variant.
+  pub(crate) bare_payloads: bool,
+  /// `groups_module(name)`: generate the group enums inside `mod name` rather than next to
+  /// the wire enum.
+  pub(crate) groups_module: Option<Ident>,
+  /// Name of the dispatch enum (defaults to `{Wire}Group`).
+  pub(crate) dispatch_name: Option<Ident>,
+  /// Do not generate the inherent conversion method.
+  pub(crate) skip_inherent: bool,
+  /// Do not generate the `EnumGroup` trait impl.
+  pub(crate) skip_trait: bool,
+  /// Generated enums that derive only `Serialize` out of the propagated serde derives.
+  pub(crate) serialize_only: Targets,
+  /// Generated enums that derive only `Deserialize` out of the propagated serde derives.
+  pub(crate) deserialize_only: Targets,
+  /// Generate `examples()`; `Some(None)` unconditionally, `Some(Some(predicate))` behind
+  /// `#[cfg(predicate)]`.
+  pub(crate) examples: Option<Option<TokenStream2>>,
+  /// Generate `sample_json(kind)`, with an optional `cfg` predicate like `examples`.
+  pub(crate) sample_json: Option<Option<TokenStream2>>,
+  /// Generate `deserialize_by_tag(tag, payload)`, with an optional `cfg` predicate like
+  /// `examples`.
+  pub(crate) deserialize_by_tag: Option<Option<TokenStream2>>,
+  /// Generate `TryFrom<(&str, &[u8])>`, decoding payloads like `decode_by_tag(with = ...,
+  /// error = ...)` says.
+  pub(crate) decode_by_tag: Option<DecodeByTag>,
+  /// Generate `peek_kind(bytes, format)`, with an optional `cfg` predicate like `examples`.
+  pub(crate) peek_kind: Option<Option<TokenStream2>>,
+  /// Generate `avro_schema()`, `to_avro()` and `from_avro()`, with an optional `cfg`
+  /// predicate like `examples`.
+  pub(crate) avro: Option<Option<TokenStream2>>,
+  /// Expose the kind enums to Python and generate `to_py` / `from_py`, with an optional
+  /// `cfg` predicate like `examples`.
+  pub(crate) pyo3: Option<Option<TokenStream2>>,
+  /// Generate `{Wire}SinkRouter`, with an optional `cfg` predicate like `examples`.
+  pub(crate) sink_router: Option<Option<TokenStream2>>,
+  /// Extra attributes for individual generated enums (`wire_attr(...)`, `group_attr(...)`,
+  /// `dispatch_attr(...)`, `kind_attr(...)`, `group_kind_attr(...)`).
+  pub(crate) extra_attrs: ExtraAttrs,
+}
+
+/// Attributes passed through to one kind of generated enum only.
+#[derive(Debug, Default)]
+pub(crate) struct ExtraAttrs {
+  pub(crate) wire: Vec<Meta>,
+  pub(crate) groups: Vec<Meta>,
+  pub(crate) dispatch: Vec<Meta>,
+  pub(crate) kind: Vec<Meta>,
+  pub(crate) group_kind: Vec<Meta>,
+}
+
+impl ExtraAttrs {
+  /// Parses `target_attr(meta, ...)` if `meta` is one of the per-target attribute options.
+  fn parse(&mut self, meta: &ParseNestedMeta) -> Option<syn::Result<()>> {
+    let target = if meta.path.is_ident("wire_attr") {
+      &mut self.wire
+    } else if meta.path.is_ident("group_attr") {
+      &mut self.groups
+    } else if meta.path.is_ident("dispatch_attr") {
+      &mut self.dispatch
+    } else if meta.path.is_ident("kind_attr") {
+      &mut self.kind
+    } else if meta.path.is_ident("group_kind_attr") {
+      &mut self.group_kind
+    } else {
+      return None;
+    };
+    Some(parse_metas(meta).map(|metas| target.extend(metas)))
+  }
+}
+
+/// A selection of the generated enums that carry the propagated attributes.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Targets {
+  pub(crate) wire: bool,
+  pub(crate) groups: bool,
+}
+
+impl Targets {
+  /// Parses an optional `(wire, groups)` list; a bare option selects every enum.
+  fn parse(meta: &ParseNestedMeta) -> syn::Result<Self> {
+    if meta.input.is_empty() || meta.input.peek(syn::Token![,]) {
+      return Ok(Targets { wire: true, groups: true });
+    }
+    let mut targets = Targets::default();
+    meta.parse_nested_meta(|target| {
+      if target.path.is_ident("wire") {
+        targets.wire = true;
+        Ok(())
+      } else if target.path.is_ident("groups") {
+        targets.groups = true;
+        Ok(())
+      } else {
+        Err(target.error("expected `wire` or `groups`"))
+      }
+    })?;
+    Ok(targets)
+  }
+}
+
+impl EnumGroupOptions {
+  /// The first enabled option whose generated items do not carry the generics of a
+  /// generic wire enum.
+  pub(crate) fn non_generic_option(&self) -> Option<&'static str> {
+    [
+      (self.group_tags, "group_tags"),
+      (self.router, "router"),
+      (self.filter, "filter"),
+      (self.queues, "queues"),
+      (self.handlers, "handlers"),
+      (self.group_index_table.is_some(), "group_index_table"),
+      (self.payload_any, "payload_any"),
+      (self.examples.is_some(), "examples"),
+      (self.sample_json.is_some(), "sample_json"),
+      (self.deserialize_by_tag.is_some(), "deserialize_by_tag"),
+      (self.decode_by_tag.is_some(), "decode_by_tag"),
+      (self.peek_kind.is_some(), "peek_kind"),
+      (self.avro.is_some(), "avro"),
+      (self.pyo3.is_some(), "pyo3"),
+      (self.sink_router.is_some(), "sink_router"),
+    ]
+    .into_iter()
+    .find_map(|(enabled, option)| enabled.then_some(option))
+  }
+
+  /// Removes every `#[enum_group(...)]` attribute from `attrs` and parses its contents.
+  pub(crate) fn extract(attrs: &mut Vec<Attribute>) -> syn::Result<Self> {
+    let mut options = EnumGroupOptions::default();
+    for attr in take_attrs(attrs, "enum_group") {
+      options.parse_attr(&attr)?;
+    }
+    Ok(options)
+  }
+
+  fn parse_attr(&mut self, attr: &Attribute) -> syn::Result<()> {
+    self.parse_nested(attr)?;
+
+    if self.skip_inherent && self.skip_trait {
+      return Err(syn::Error::new_spanned(
+        attr,
+        "`skip_inherent` and `skip_trait` cannot be combined; nothing would convert the wire enum",
+      ));
+    }
+    if self.skip_inherent && self.method.is_some() {
+      return Err(syn::Error::new_spanned(attr, "`method` has no effect with `skip_inherent`"));
+    }
+    let (ser, de) = (self.serialize_only, self.deserialize_only);
+    if (ser.wire && de.wire) || (ser.groups && de.groups) {
+      return Err(syn::Error::new_spanned(
+        attr,
+        "`serialize_only` and `deserialize_only` cannot both apply to the same enum",
+      ));
+    }
+    Ok(())
+  }
+
+  fn parse_nested(&mut self, attr: &Attribute) -> syn::Result<()> {
+    attr.parse_nested_meta(|meta| {
+      if let Some(result) = self.extra_attrs.parse(&meta) {
+        result
+      } else if meta.path.is_ident("group_modules") {
+        self.group_modules = true;
+        Ok(())
+      } else if meta.path.is_ident("group_tags") {
+        self.group_tags = true;
+        Ok(())
+      } else if meta.path.is_ident("as_group") {
+        self.as_group = true;
+        Ok(())
+      } else if meta.path.is_ident("accessors") {
+        self.accessors = true;
+        Ok(())
+      } else if meta.path.is_ident("display") {
+        self.display = true;
+        Ok(())
+      } else if meta.path.is_ident("router") {
+        self.router = true;
+        Ok(())
+      } else if meta.path.is_ident("filter") {
+        self.filter = true;
+        Ok(())
+      } else if meta.path.is_ident("queues") {
+        self.queues = true;
+        Ok(())
+      } else if meta.path.is_ident("handlers") {
+        self.handlers = true;
+        Ok(())
+      } else if meta.path.is_ident("kind_map") {
+        self.kind_map = true;
+        Ok(())
+      } else if meta.path.is_ident("group_index_table") {
+        self.group_index_table = Some(meta.path.span());
+        Ok(())
+      } else if meta.path.is_ident("payload_any") {
+        self.payload_any = true;
+        Ok(())
+      } else if meta.path.is_ident("partial_handler") {
+        self.partial_handler = true;
+        Ok(())
+      } else if meta.path.is_ident("variant_handler") {
+        self.variant_handler = true;
+        Ok(())
+      } else if meta.path.is_ident("handler") {
+        self.handler = true;
+        if meta.input.peek(syn::token::Paren) {
+          meta.parse_nested_meta(|inner| {
+            if inner.path.is_ident("context") {
+              self.handler_context = true;
+              Ok(())
+            } else {
+              Err(inner.error("expected `context`"))
+            }
+          })?;
+        }
+        Ok(())
+      } else if meta.path.is_ident("method") {
+        let name: LitStr = meta.value()?.parse()?;
+        self.method = Some(name.parse()?);
+        Ok(())
+      } else if meta.path.is_ident("bare_payloads") {
+        self.bare_payloads = true;
+        Ok(())
+      } else if meta.path.is_ident("groups_module") {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        self.groups_module = Some(content.parse()?);
+        Ok(())
+      } else if meta.path.is_ident("dispatch_name") {
+        let name: LitStr = meta.value()?.parse()?;
+        self.dispatch_name = Some(name.parse()?);
+        Ok(())
+      } else if meta.path.is_ident("skip_inherent") {
+        self.skip_inherent = true;
+        Ok(())
+      } else if meta.path.is_ident("skip_trait") {
+        self.skip_trait = true;
+        Ok(())
+      } else if meta.path.is_ident("serialize_only") {
+        self.serialize_only = Targets::parse(&meta)?;
+        Ok(())
+      } else if meta.path.is_ident("deserialize_only") {
+        self.deserialize_only = Targets::parse(&meta)?;
+        Ok(())
+      } else if meta.path.is_ident("examples") {
+        self.examples = Some(parse_cfg(&meta)?);
+        Ok(())
+      } else if meta.path.is_ident("sample_json") {
+        self.sample_json = Some(parse_cfg(&meta)?);
+        Ok(())
+      } else if meta.path.is_ident("deserialize_by_tag") {
+        self.deserialize_by_tag = Some(parse_cfg(&meta)?);
+        Ok(())
+      } else if meta.path.is_ident("decode_by_tag") {
+        self.decode_by_tag = Some(DecodeByTag::parse(&meta)?);
+        Ok(())
+      } else if meta.path.is_ident("peek_kind") {
+        self.peek_kind = Some(parse_cfg(&meta)?);
+        Ok(())
+      } else if meta.path.is_ident("avro") {
+        self.avro = Some(parse_cfg(&meta)?);
+        Ok(())
+      } else if meta.path.is_ident("pyo3") {
+        self.pyo3 = Some(parse_cfg(&meta)?);
+        Ok(())
+      } else if meta.path.is_ident("sink_router") {
+        self.sink_router = Some(parse_cfg(&meta)?);
+        Ok(())
+      } else {
+        Err(meta.error("unsupported enum_group option"))
+      }
+    })
+  }
+}
+
+/// Settings of `decode_by_tag(with = path, error = Type, cfg(predicate))`.
+#[derive(Debug, Default)]
+pub(crate) struct DecodeByTag {
+  /// The decoding function and its error type, e.g. `bincode::deserialize` and
+  /// `bincode::Error`; `serde_json::from_slice` when not given.
+  pub(crate) with: Option<(Path, Type)>,
+  pub(crate) cfg: Option<TokenStream2>,
+}
+
+impl DecodeByTag {
+  fn parse(meta: &ParseNestedMeta) -> syn::Result<Self> {
+    let mut settings = DecodeByTag::default();
+    if meta.input.is_empty() || meta.input.peek(syn::Token![,]) {
+      return Ok(settings);
+    }
+    let (mut with, mut error) = (None, None);
+    meta.parse_nested_meta(|inner| {
+      if inner.path.is_ident("with") {
+        with = Some(inner.value()?.parse::<Path>()?);
+        Ok(())
+      } else if inner.path.is_ident("error") {
+        error = Some(inner.value()?.parse::<Type>()?);
+        Ok(())
+      } else if inner.path.is_ident("cfg") {
+        let content;
+        syn::parenthesized!(content in inner.input);
+        settings.cfg = Some(content.parse()?);
+        Ok(())
+      } else {
+        Err(inner.error("expected `with = ...`, `error = ...` or `cfg(...)`"))
+      }
+    })?;
+    settings.with = match (with, error) {
+      (Some(with), Some(error)) => Some((with, error)),
+      (None, None) => None,
+      _ => return Err(meta.error("`with` and `error` must be given together")),
+    };
+    Ok(settings)
+  }
+}
+
+/// Parses an optional `(cfg(predicate))` list after an option, returning the predicate.
+fn parse_cfg(meta: &ParseNestedMeta) -> syn::Result<Option<TokenStream2>> {
+  if meta.input.is_empty() || meta.input.peek(syn::Token![,]) {
+    return Ok(None);
+  }
+  let mut predicate = None;
+  meta.parse_nested_meta(|inner| {
+    if inner.path.is_ident("cfg") {
+      let content;
+      syn::parenthesized!(content in inner.input);
+      predicate = Some(content.parse()?);
+      Ok(())
+    } else {
+      Err(inner.error("expected `cfg(...)`"))
+    }
+  })?;
+  Ok(predicate)
+}
+
+/// Parses the `(meta, ...)` list following an option.
+fn parse_metas(meta: &ParseNestedMeta) -> syn::Result<Punctuated<Meta, Token![,]>> {
+  let content;
+  syn::parenthesized!(content in meta.input);
+  Punctuated::parse_terminated(&content)
+}
+
+/// Options attached to a group with `#[group(...)]`.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct GroupOptions {
+  /// `requires_auth`: messages of this group may only be sent by authenticated peers.
+  pub(crate) requires_auth: bool,
+  /// `role = "..."`: the role required to send messages of this group (implies
+  /// `requires_auth`).
+  pub(crate) role: Option<LitStr>,
+  /// `copy`: derive `Copy` on this group enum; holds the span of the option for errors
+  /// about non-`Copy` payloads.
+  pub(crate) copy: Option<Span>,
+  /// `skip_dispatch`: leave this group out of the dispatch enum; its messages are wrapped
+  /// whole in its `Undispatched` variant instead.
+  pub(crate) skip_dispatch: bool,
+  /// `strip_prefix = "..."`: the prefix dropped from the names of the variants on the group
+  /// enum.
+  pub(crate) strip_prefix: Option<LitStr>,
+  /// `#[meta(key = "...", value = "...")]` entries.
+  pub(crate) meta: Vec<MetaEntry>,
+}
+
+impl GroupOptions {
+  /// Whether any option is set.
+  pub(crate) fn has_any(&self) -> bool {
+    self.requires_auth
+      || self.copy.is_some()
+      || self.skip_dispatch
+      || self.strip_prefix.is_some()
+      || !self.meta.is_empty()
+  }
+
+  /// Removes every `#[group(...)]` attribute from `attrs` and parses its contents.
+  pub(crate) fn extract(attrs: &mut Vec<Attribute>) -> syn::Result<Self> {
+    let mut options = GroupOptions::default();
+    for attr in take_attrs(attrs, "group") {
+      attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("requires_auth") {
+          options.requires_auth = true;
+          Ok(())
+        } else if meta.path.is_ident("copy") {
+          options.copy = Some(meta.path.span());
+          Ok(())
+        } else if meta.path.is_ident("skip_dispatch") {
+          options.skip_dispatch = true;
+          Ok(())
+        } else if meta.path.is_ident("role") {
+          options.role = Some(meta.value()?.parse()?);
+          options.requires_auth = true;
+          Ok(())
+        } else if meta.path.is_ident("strip_prefix") {
+          options.strip_prefix = Some(meta.value()?.parse()?);
+          Ok(())
+        } else {
+          Err(meta.error("unsupported group option"))
+        }
+      })?;
+    }
+    options.meta = MetaEntry::extract(attrs)?;
+    Ok(options)
+  }
+}
+
+/// Options attached to a single variant with `#[variant(...)]`.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct VariantOptions {
+  /// `timeout_ms = N`: how long a request of this kind may take.
+  pub(crate) timeout_ms: Option<u64>,
+  /// `retries = N`: how many times a request of this kind may be retried.
+  pub(crate) retries: Option<u32>,
+  /// `legacy_of = NewVariant`: the variant superseding this one, whose payload implements
+  /// `From` this variant's payload.
+  pub(crate) legacy_of: Option<Ident>,
+  /// `tag = N`: the explicit numeric tag of the variant, a fixed message id.
+  pub(crate) tag: Option<LitInt>,
+  /// `boxed`: the wire and group enums store the payload as `Box<Payload>`, while the
+  /// conversions and handlers still take and return the payload itself.
+  pub(crate) boxed: bool,
+  /// `#[meta(key = "...", value = "...")]` entries, overriding the group's.
+  pub(crate) meta: Vec<MetaEntry>,
+}
+
+impl VariantOptions {
+  /// Removes every `#[variant(...)]` attribute from `attrs` and parses its contents.
+  pub(crate) fn extract(attrs: &mut Vec<Attribute>) -> syn::Result<Self> {
+    let mut options = VariantOptions::default();
+    for attr in take_attrs(attrs, "variant") {
+      attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("timeout_ms") {
+          options.timeout_ms = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+          Ok(())
+        } else if meta.path.is_ident("retries") {
+          options.retries = Some(meta.value()?.parse::<LitInt>()?.base10_parse()?);
+          Ok(())
+        } else if meta.path.is_ident("legacy_of") {
+          options.legacy_of = Some(meta.value()?.parse()?);
+          Ok(())
+        } else if meta.path.is_ident("tag") {
+          let tag: LitInt = meta.value()?.parse()?;
+          tag.base10_parse::<u16>()?;
+          if let Some(earlier) = &options.tag {
+            return Err(syn::Error::new_spanned(
+              &tag,
+              format!("conflicting tags {} and {} for the same variant", earlier, tag),
+            ));
+          }
+          options.tag = Some(tag);
+          Ok(())
+        } else if meta.path.is_ident("boxed") {
+          options.boxed = true;
+          Ok(())
+        } else {
+          Err(meta.error("unsupported variant option"))
+        }
+      })?;
+    }
+    options.meta = MetaEntry::extract(attrs)?;
+    Ok(options)
+  }
+}
+
+/// A free-form `#[meta(key = "...", value = "...")]` entry on a group or variant.
+#[derive(Debug, Clone)]
+pub(crate) struct MetaEntry {
+  pub(crate) key: LitStr,
+  pub(crate) value: LitStr,
+}
+
+impl MetaEntry {
+  /// Removes every `#[meta(...)]` attribute from `attrs` and parses its contents.
+  fn extract(attrs: &mut Vec<Attribute>) -> syn::Result<Vec<Self>> {
+    let mut entries: Vec<MetaEntry> = Vec::new();
+    for attr in take_attrs(attrs, "meta") {
+      let (mut key, mut value) = (None::<LitStr>, None::<LitStr>);
+      attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("key") {
+          key = Some(meta.value()?.parse()?);
+          Ok(())
+        } else if meta.path.is_ident("value") {
+          value = Some(meta.value()?.parse()?);
+          Ok(())
+        } else {
+          Err(meta.error("expected `key` or `value`"))
+        }
+      })?;
+      let (Some(key), Some(value)) = (key, value) else {
+        return Err(syn::Error::new_spanned(
+          attr,
+          "expected `#[meta(key = \"...\", value = \"...\")]`",
+        ));
+      };
+      if entries.iter().any(|e| e.key.value() == key.value()) {
+        return Err(syn::Error::new_spanned(key, "duplicate meta key"));
+      }
+      entries.push(MetaEntry { key, value });
+    }
+    Ok(entries)
+  }
+}
+
+/// Removes the attributes named `name` from `attrs` and returns them.
+fn take_attrs(attrs: &mut Vec<Attribute>, name: &str) -> Vec<Attribute> {
+  let (taken, kept) = std::mem::take(attrs).into_iter().partition(|a| a.path().is_ident(name));
+  *attrs = kept;
+  taken
+}
@@ -0,0 +1,1235 @@
+//! Custom syntax parser for `define_enum_group!`.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::{
+  braced, parse_quote, Attribute, Data, DeriveInput, Fields, FieldsNamed, Generics, Ident, LitInt,
+  Token, Type, Visibility,
+};
+
+use crate::options::{EnumGroupOptions, GroupOptions, VariantOptions};
+use crate::serde_attrs::SerdeContainer;
+use crate::util::{cfg_attrs, has_derive, is_doc_comment};
+
+/// Parsed representation of a single variant within a group
+#[derive(Debug, Clone)]
+pub(crate) struct ParsedVariant {
+  pub(crate) attrs: Vec<Attribute>,
+  pub(crate) options: VariantOptions,
+  pub(crate) name: Ident,
+  pub(crate) fields: VariantFields,
+  /// `Name(Type) as GroupName`: the name of the variant on its group enums, when it
+  /// differs from its name on the wire enum.
+  pub(crate) group_name: Option<Ident>,
+  /// `Name()`: the payload is a marker struct generated under the name of the variant.
+  pub(crate) marker: bool,
+  /// The nested groups enclosing the variant within its group, outermost first: `[Handshake]`
+  /// for `Hello` in `Protocol { Handshake { Hello(Hello) } }`.
+  pub(crate) nesting: Vec<Ident>,
+}
+
+/// The fields of a variant.
+#[derive(Debug, Clone)]
+pub(crate) enum VariantFields {
+  /// `Name(#[attr] Type)`: a single payload, with the attributes on its field, e.g.
+  /// `#[serde(with = "serde_bytes")]`.
+  Payload { attrs: Vec<Attribute>, ty: Type },
+  /// `Name { a: A, b: B }`: named fields.
+  Named(FieldsNamed),
+  /// `Name`: no payload at all.
+  Unit,
+}
+
+impl ParsedVariant {
+  /// The name of the variant on its group enums.
+  pub(crate) fn group_ident(&self) -> &Ident {
+    self.group_name.as_ref().unwrap_or(&self.name)
+  }
+
+  /// The `#[cfg(...)]` attributes of the variant, repeated on every item, match arm and
+  /// table entry generated for it.
+  pub(crate) fn cfg(&self) -> TokenStream2 {
+    let cfgs = cfg_attrs(&self.attrs);
+    quote! { #(#cfgs)* }
+  }
+
+  /// The payload type of a single-payload variant.
+  pub(crate) fn payload(&self) -> Option<&Type> {
+    match &self.fields {
+      VariantFields::Payload { ty, .. } => Some(ty),
+      VariantFields::Named(_) | VariantFields::Unit => None,
+    }
+  }
+
+  /// The types of all fields, in declaration order.
+  pub(crate) fn field_types(&self) -> Vec<&Type> {
+    match &self.fields {
+      VariantFields::Payload { ty, .. } => vec![ty],
+      VariantFields::Named(named) => named.named.iter().map(|f| &f.ty).collect(),
+      VariantFields::Unit => Vec::new(),
+    }
+  }
+
+  /// The fields as declared on the generated enums: `(#[attr] Type)`, `{ a: A, ... }` or
+  /// nothing. A `boxed` payload is declared as `Box<Type>`.
+  pub(crate) fn fields_decl(&self) -> TokenStream2 {
+    match &self.fields {
+      VariantFields::Payload { attrs, ty } if self.options.boxed => {
+        quote! { (#(#attrs)* ::std::boxed::Box<#ty>) }
+      }
+      VariantFields::Payload { attrs, ty } => quote! { (#(#attrs)* #ty) },
+      VariantFields::Named(named) => named.to_token_stream(),
+      VariantFields::Unit => TokenStream2::new(),
+    }
+  }
+
+  /// The fields bound to local variables, `(payload)`, `{ a, ... }` or nothing. The same
+  /// tokens rebuild the variant from these variables, on any enum declaring it.
+  pub(crate) fn fields_binding(&self, payload: &Ident) -> TokenStream2 {
+    match &self.fields {
+      VariantFields::Payload { .. } => quote! { (#payload) },
+      VariantFields::Named(named) => {
+        let names = named.named.iter().map(|f| &f.ident);
+        quote! { { #(#names),* } }
+      }
+      VariantFields::Unit => TokenStream2::new(),
+    }
+  }
+
+  /// The payload `value` as stored in the variant, boxed for `boxed` variants.
+  pub(crate) fn box_payload(&self, value: TokenStream2) -> TokenStream2 {
+    if self.options.boxed {
+      quote! { ::std::boxed::Box::new(#value) }
+    } else {
+      value
+    }
+  }
+
+  /// The payload stored in the variant as `value`, moved out of its box for `boxed`
+  /// variants.
+  pub(crate) fn unbox_payload(&self, value: TokenStream2) -> TokenStream2 {
+    if self.options.boxed {
+      quote! { *#value }
+    } else {
+      value
+    }
+  }
+
+  /// An error unless this is a single-payload variant, for options that need the
+  /// payload type.
+  pub(crate) fn require_payload(&self, option: &str) -> syn::Result<&Type> {
+    self.payload().ok_or_else(|| {
+      syn::Error::new_spanned(
+        &self.name,
+        format!("`{option}` requires single-payload variants like `{}(Type)`", self.name),
+      )
+    })
+  }
+}
+
+/// Parsed representation of a group (e.g., `SupportMessage { ... }`)
+#[derive(Debug, Clone)]
+pub(crate) struct ParsedGroup {
+  /// Attributes of the group enum, e.g. `#[derive(PartialEq)]`, without the `#[group(...)]`
+  /// options. Its derives replace the outer ones for this group.
+  pub(crate) attrs: Vec<Attribute>,
+  pub(crate) options: GroupOptions,
+  /// `pub(crate) Name { ... }`: the visibility of the group enum, when it differs from the
+  /// wire enum's.
+  pub(crate) vis: Option<Visibility>,
+  pub(crate) name: Ident,
+  /// `Name = N { ... }`: the explicit discriminant of the group's dispatch enum variant.
+  pub(crate) discriminant: Option<LitInt>,
+  pub(crate) variants: Vec<ParsedVariant>,
+  /// The doc comments of the nested groups, by name.
+  pub(crate) nested_docs: Vec<(Ident, Vec<Attribute>)>,
+  /// `use Name`: a shared group block of the invocation, whose enum is generated once for
+  /// every wire enum including it. Its variants are filled in by [`DefineEnumGroups`].
+  pub(crate) shared: bool,
+  /// `#[unknown] Name`: the catch-all group, whose single variant has no tag of its own
+  /// and receives the messages of tags no other variant has.
+  pub(crate) unknown: bool,
+}
+
+/// A group nested in a group of the wire enum, with its variants at any depth.
+pub(crate) struct NestedGroup<'a> {
+  pub(crate) name: &'a Ident,
+  pub(crate) docs: &'a [Attribute],
+  /// How many group enums enclose it: 1 for a group nested directly in the group.
+  pub(crate) depth: usize,
+  pub(crate) variants: Vec<&'a ParsedVariant>,
+}
+
+impl ParsedGroup {
+  /// Every nested group, in declaration order, outer ones before those they hold.
+  pub(crate) fn nested_groups(&self) -> Vec<NestedGroup<'_>> {
+    let mut nested: Vec<NestedGroup> = Vec::new();
+    for (depth, name) in self.variants.iter().flat_map(|v| v.nesting.iter().enumerate()) {
+      if nested.iter().all(|n| n.name != name) {
+        let variants =
+          self.variants.iter().filter(|v| v.nesting.get(depth) == Some(name)).collect();
+        let docs = self.nested_docs.iter().find(|(n, _)| n == name).map_or(&[][..], |(_, d)| d);
+        nested.push(NestedGroup { name, docs, depth: depth + 1, variants });
+      }
+    }
+    nested
+  }
+
+  /// Applies `#[group(strip_prefix = "...")]`: the variants whose name starts with the
+  /// prefix, and that are not renamed with `as`, drop it on the group enums.
+  fn strip_prefix(&mut self) -> syn::Result<()> {
+    let Some(prefix) = &self.options.strip_prefix else {
+      return Ok(());
+    };
+    for v in self.variants.iter_mut().filter(|v| v.group_name.is_none()) {
+      let name = v.name.unraw().to_string();
+      let Some(rest) = name.strip_prefix(&prefix.value()) else {
+        continue;
+      };
+      let group_name = syn::parse_str::<Ident>(rest).map_err(|_| {
+        syn::Error::new_spanned(
+          &v.name,
+          format!(
+            "stripping `{}` leaves no valid variant name; rename it with `as`",
+            prefix.value()
+          ),
+        )
+      })?;
+      v.group_name = Some(Ident::new(&group_name.to_string(), v.name.span()));
+    }
+    Ok(())
+  }
+
+  /// The value of the group enum at `depth` holding `variant` with the fields `inner`, as
+  /// an expression or a pattern: `Protocol::Handshake(Handshake::Hello(inner))` at depth 0
+  /// and `Handshake::Hello(inner)` at depth 1. The enums are named through `module` when
+  /// they are generated in one.
+  pub(crate) fn wrap_variant(
+    &self,
+    variant: &ParsedVariant,
+    depth: usize,
+    module: Option<&Ident>,
+    inner: TokenStream2,
+  ) -> TokenStream2 {
+    let enums: Vec<&Ident> = std::iter::once(&self.name).chain(&variant.nesting).collect();
+    let module = module.map(|m| quote! { #m:: });
+    let (innermost, v_name) = (enums[enums.len() - 1], variant.group_ident());
+    let mut value = quote! { #module #innermost::#v_name #inner };
+    for i in (depth..variant.nesting.len()).rev() {
+      let (outer, nested) = (enums[i], enums[i + 1]);
+      value = quote! { #module #outer::#nested(#value) };
+    }
+    value
+  }
+}
+
+/// Parsed input for `define_enum_group!`
+#[derive(Debug)]
+pub(crate) struct EnumGroupInput {
+  pub(crate) attrs: Vec<Attribute>,
+  pub(crate) options: EnumGroupOptions,
+  pub(crate) vis: Visibility,
+  pub(crate) name: Ident,
+  /// `enum WireMsg<T: Bound> where ... { ... }`: the generics of the wire enum, carried
+  /// onto the dispatch enum and, as far as their variants use them, the group enums.
+  pub(crate) generics: Generics,
+  pub(crate) groups: Vec<ParsedGroup>,
+  /// `#[derive(EnumGroup)]`: the wire enum is written by the user rather than generated.
+  pub(crate) derived: bool,
+}
+
+impl Parse for ParsedVariant {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let mut attrs = input.call(Attribute::parse_outer)?;
+    let options = VariantOptions::extract(&mut attrs)?;
+    let name: Ident = input.parse()?;
+
+    // Parse (#[field_attr] Type), { field: Type, ... }, () or nothing
+    let mut marker = false;
+    let fields = if input.peek(syn::token::Brace) {
+      VariantFields::Named(input.parse()?)
+    } else if !input.peek(syn::token::Paren) {
+      VariantFields::Unit
+    } else {
+      let content;
+      syn::parenthesized!(content in input);
+      marker = content.is_empty();
+      let attrs = content.call(Attribute::parse_outer)?;
+      let ty: Type = if marker {
+        parse_quote! { #name }
+      } else {
+        content.parse()?
+      };
+      VariantFields::Payload { attrs, ty }
+    };
+
+    // `as GroupName` renames the variant on its group enums
+    let group_name = match input.parse::<Option<Token![as]>>()? {
+      Some(_) => Some(input.parse()?),
+      None => None,
+    };
+
+    Ok(ParsedVariant { attrs, options, name, fields, group_name, marker, nesting: Vec::new() })
+  }
+}
+
+/// Parses an optional `= N` group discriminant.
+fn parse_discriminant(input: ParseStream) -> syn::Result<Option<LitInt>> {
+  if !input.peek(Token![=]) {
+    return Ok(None);
+  }
+  input.parse::<Token![=]>()?;
+  let lit: LitInt = input.parse()?;
+  lit.base10_parse::<u16>()?;
+  Ok(Some(lit))
+}
+
+impl Parse for ParsedGroup {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let mut attrs = input.call(Attribute::parse_outer)?;
+    let options = GroupOptions::extract(&mut attrs)?;
+    let vis = match input.parse::<Visibility>()? {
+      Visibility::Inherited => None,
+      vis => Some(vis),
+    };
+
+    // `use Name` includes a shared group block, whose options live on its definition
+    if input.parse::<Option<Token![use]>>()?.is_some() {
+      if let Some(vis) = &vis {
+        return Err(syn::Error::new_spanned(
+          vis,
+          "a shared group has the visibility of its definition",
+        ));
+      }
+      let name: Ident = input.parse()?;
+      if let Some(attr) = attrs.first() {
+        return Err(syn::Error::new_spanned(
+          attr,
+          "put the attributes of a shared group on its definition",
+        ));
+      }
+      if options.has_any() {
+        return Err(syn::Error::new_spanned(
+          &name,
+          "put the `#[group(...)]` options of a shared group on its definition",
+        ));
+      }
+      let discriminant = parse_discriminant(input)?;
+      let (variants, nested_docs) = (Vec::new(), Vec::new());
+      return Ok(ParsedGroup {
+        attrs,
+        options,
+        vis,
+        name,
+        discriminant,
+        variants,
+        nested_docs,
+        shared: true,
+        unknown: false,
+      });
+    }
+
+    let name: Ident = input.parse()?;
+    let discriminant = parse_discriminant(input)?;
+
+    let content;
+    braced!(content in input);
+
+    let (mut variants, mut nested_docs) = (Vec::new(), Vec::new());
+    parse_members(&content, &[], &mut variants, &mut nested_docs)?;
+
+    let mut group = ParsedGroup {
+      attrs,
+      options,
+      vis,
+      name,
+      discriminant,
+      variants,
+      nested_docs,
+      shared: false,
+      unknown: false,
+    };
+    group.strip_prefix()?;
+    Ok(group)
+  }
+}
+
+/// Parses the variants and nested groups of a group body into `variants`, flattened in
+/// declaration order, and the doc comments of the nested groups into `nested_docs`, with
+/// `nesting` the nested groups enclosing the body.
+fn parse_members(
+  content: ParseStream,
+  nesting: &[Ident],
+  variants: &mut Vec<ParsedVariant>,
+  nested_docs: &mut Vec<(Ident, Vec<Attribute>)>,
+) -> syn::Result<()> {
+  while !content.is_empty() {
+    if is_nested_group(content) {
+      let attrs = content.call(Attribute::parse_outer)?;
+      let name: Ident = content.parse()?;
+      if let Some(attr) = attrs.iter().find(|a| !is_doc_comment(a)) {
+        return Err(syn::Error::new_spanned(
+          attr,
+          "only doc comments are supported on nested groups",
+        ));
+      }
+      if nesting.contains(&name) || variants.iter().any(|v| v.nesting.contains(&name)) {
+        return Err(syn::Error::new_spanned(
+          &name,
+          format!("nested group `{}` is declared more than once", name),
+        ));
+      }
+      let body;
+      braced!(body in content);
+      nested_docs.push((name.clone(), attrs));
+      let nesting = [nesting, &[name]].concat();
+      let start = variants.len();
+      parse_members(&body, &nesting, variants, nested_docs)?;
+      if variants.len() == start {
+        return Err(syn::Error::new_spanned(
+          &nesting[nesting.len() - 1],
+          "a nested group needs variants",
+        ));
+      }
+    } else {
+      let mut variant = content.parse::<ParsedVariant>()?;
+      variant.nesting = nesting.to_vec();
+      variants.push(variant);
+    }
+    // Optional trailing comma
+    if content.peek(Token![,]) {
+      content.parse::<Token![,]>()?;
+    }
+  }
+  Ok(())
+}
+
+/// Whether the input starts with the `#[unknown] Name` catch-all group.
+fn is_unknown_group(input: ParseStream) -> bool {
+  let fork = input.fork();
+  fork
+    .call(Attribute::parse_outer)
+    .is_ok_and(|attrs| attrs.iter().any(|a| a.path().is_ident("unknown")))
+}
+
+/// Parses `#[unknown] Name` or `#[unknown] Name(Type)` into a group `Name` holding a single
+/// variant `Name`, which deserializing the wire enum falls back to for the tags of variants
+/// it does not know. A unit variant is marked `#[serde(other)]` and needs the tag in the
+/// payload, while a payload captures the whole message through `#[serde(untagged)]`.
+fn parse_unknown_group(input: ParseStream, wire_attrs: &[Attribute]) -> syn::Result<ParsedGroup> {
+  let mut attrs = input.call(Attribute::parse_outer)?;
+  let marker = attrs.iter().position(|a| a.path().is_ident("unknown")).expect("an unknown group");
+  attrs.remove(marker).meta.require_path_only()?;
+  let group_options = GroupOptions::extract(&mut attrs)?;
+  let mut variant = input.parse::<ParsedVariant>()?;
+  variant.options = VariantOptions::extract(&mut attrs)?;
+  if let VariantFields::Named(named) = &variant.fields {
+    return Err(syn::Error::new_spanned(
+      named,
+      "the `#[unknown]` variant takes no fields or a single payload capturing the message",
+    ));
+  }
+  if variant.marker {
+    return Err(syn::Error::new_spanned(
+      &variant.name,
+      "the `#[unknown]` variant takes no fields or a single payload capturing the message",
+    ));
+  }
+  if let Some(group_name) = &variant.group_name {
+    return Err(syn::Error::new_spanned(
+      group_name,
+      "the `#[unknown]` variant has the same name on the wire and group enums",
+    ));
+  }
+  if has_derive(wire_attrs, "Serialize") || has_derive(wire_attrs, "Deserialize") {
+    let serde = SerdeContainer::from_attrs(wire_attrs);
+    let deserialize = has_derive(wire_attrs, "Deserialize");
+    variant.attrs.push(match variant.fields {
+      VariantFields::Unit if deserialize && (serde.tag.is_none() || serde.untagged) => {
+        return Err(syn::Error::new_spanned(
+          &variant.name,
+          "a unit `#[unknown]` variant only catches unknown tags of internally or adjacently \
+           tagged enums; give it a payload such as `serde_json::Value` to capture the message",
+        ));
+      }
+      VariantFields::Unit => parse_quote! { #[serde(other)] },
+      _ => parse_quote! { #[serde(untagged)] },
+    });
+  }
+  let docs = attrs.iter().filter(|a| is_doc_comment(a)).cloned().collect();
+  variant.attrs.splice(0..0, attrs);
+  Ok(ParsedGroup {
+    attrs: docs,
+    options: group_options,
+    vis: None,
+    name: variant.name.clone(),
+    discriminant: None,
+    variants: vec![variant],
+    nested_docs: Vec::new(),
+    shared: false,
+    unknown: true,
+  })
+}
+
+/// Whether the input starts with a nested group, `Name { Variant(..), .. }`, rather than a
+/// variant with named fields, `Name { field: Type, .. }`, or no fields at all, `Name {}`.
+fn is_nested_group(input: ParseStream) -> bool {
+  let peek = || -> syn::Result<bool> {
+    let fork = input.fork();
+    fork.call(Attribute::parse_outer)?;
+    fork.parse::<Ident>()?;
+    if !fork.peek(syn::token::Brace) {
+      return Ok(false);
+    }
+    let body;
+    braced!(body in fork);
+    body.call(Attribute::parse_outer)?;
+    let named_field = body.peek(Ident::peek_any) && body.peek2(Token![:]);
+    Ok(!(body.is_empty() || named_field))
+  };
+  peek().unwrap_or(false)
+}
+
+impl Parse for EnumGroupInput {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let input = EnumGroupInput::parse_unchecked(input)?;
+    if let Some(g) = input.groups.iter().find(|g| g.shared) {
+      return Err(syn::Error::new_spanned(
+        &g.name,
+        "shared groups are only supported by `define_enum_group!`",
+      ));
+    }
+    input.check()?;
+    Ok(input)
+  }
+}
+
+impl EnumGroupInput {
+  /// Parses one wire enum, leaving shared groups unresolved and the checks to the caller.
+  fn parse_unchecked(input: ParseStream) -> syn::Result<Self> {
+    // Parse outer attributes (like #[derive(...)]), pulling out our own options
+    let mut attrs = input.call(Attribute::parse_outer)?;
+    let options = EnumGroupOptions::extract(&mut attrs)?;
+
+    // Parse visibility and enum keyword
+    let vis: Visibility = input.parse()?;
+    input.parse::<Token![enum]>()?;
+    let name: Ident = input.parse()?;
+    let mut generics: Generics = input.parse()?;
+    generics.where_clause = input.parse()?;
+
+    // Parse the groups inside braces
+    let content;
+    braced!(content in input);
+
+    let mut groups: Vec<ParsedGroup> = Vec::new();
+    let mut unknown: Option<Ident> = None;
+    while !content.is_empty() {
+      if let Some(unknown) = &unknown {
+        return Err(syn::Error::new(
+          content.span(),
+          format!("the `#[unknown]` group `{}` must be declared last", unknown),
+        ));
+      }
+      if is_unknown_group(&content) {
+        let group = parse_unknown_group(&content, &attrs)?;
+        unknown = Some(group.name.clone());
+        groups.push(group);
+      } else {
+        let mut group = content.parse::<ParsedGroup>()?;
+        if options.bare_payloads {
+          for v in group.variants.iter_mut().filter(|v| matches!(v.fields, VariantFields::Unit)) {
+            let name = &v.name;
+            v.fields = VariantFields::Payload { attrs: Vec::new(), ty: parse_quote! { #name } };
+          }
+        }
+        groups.push(group);
+      }
+      // Handle optional comma between groups
+      if content.peek(Token![,]) {
+        content.parse::<Token![,]>()?;
+      }
+    }
+
+    Ok(EnumGroupInput { attrs, options, vis, name, generics, groups, derived: false })
+  }
+
+  /// Builds the input of `#[derive(EnumGroup)]` from an ordinary enum whose variants name
+  /// their group with `#[group(Name)]`. The variants of a group must be declared together,
+  /// so that the groups list them in the same order as the enum.
+  pub(crate) fn from_derive(input: DeriveInput) -> syn::Result<Self> {
+    let DeriveInput { mut attrs, vis, ident: name, generics, data } = input;
+    let Data::Enum(data) = data else {
+      return Err(syn::Error::new_spanned(name, "`EnumGroup` can only be derived for enums"));
+    };
+    let mut options = EnumGroupOptions::extract(&mut attrs)?;
+    if let Some(meta) = options.extra_attrs.wire.first() {
+      return Err(syn::Error::new_spanned(
+        meta,
+        "`wire_attr` has no effect with `#[derive(EnumGroup)]`; put the attribute on the enum",
+      ));
+    }
+    if options.bare_payloads {
+      return Err(syn::Error::new_spanned(
+        &name,
+        "`bare_payloads` has no effect with `#[derive(EnumGroup)]`; declare the payloads on \
+         the enum",
+      ));
+    }
+    if let Some(span) = options.group_index_table {
+      return Err(syn::Error::new(
+        span,
+        "`group_index_table` is not available with `#[derive(EnumGroup)]`, which cannot \
+         assign the discriminants of the enum",
+      ));
+    }
+    // The derives of the enum are not visible to a derive macro, while the dispatch enum
+    // needs these on the group enums. Those of `group_attr(...)` go first as well, so that
+    // they introduce the helper attributes among the outer ones.
+    let (derives, extra) = std::mem::take(&mut options.extra_attrs.groups)
+      .into_iter()
+      .partition(|meta| meta.path().is_ident("derive"));
+    options.extra_attrs.groups = extra;
+    let derives: Vec<Attribute> =
+      derives.into_iter().map(|meta| parse_quote! { #[#meta] }).collect();
+    attrs.splice(
+      0..0,
+      [parse_quote! { #[derive(::core::fmt::Debug, ::core::clone::Clone)] }]
+        .into_iter()
+        .chain(derives),
+    );
+
+    let mut groups: Vec<ParsedGroup> = Vec::new();
+    for variant in data.variants {
+      let syn::Variant { mut attrs, ident: v_name, fields, .. } = variant;
+      let group = derive_group_name(&v_name, &mut attrs)?;
+      let options = VariantOptions::extract(&mut attrs)?;
+      if options.boxed {
+        return Err(syn::Error::new_spanned(
+          &v_name,
+          "`boxed` is not available with `#[derive(EnumGroup)]`; declare the payload as \
+           `Box<Payload>` instead",
+        ));
+      }
+      let fields = match fields {
+        Fields::Named(named) => VariantFields::Named(named),
+        Fields::Unit => VariantFields::Unit,
+        Fields::Unnamed(unnamed) if unnamed.unnamed.len() == 1 => {
+          let field = unnamed.unnamed.into_iter().next().expect("one field");
+          VariantFields::Payload { attrs: field.attrs, ty: field.ty }
+        }
+        Fields::Unnamed(unnamed) => {
+          return Err(syn::Error::new_spanned(
+            unnamed,
+            "tuple variants take a single payload; use named fields for more",
+          ));
+        }
+      };
+      let variant = ParsedVariant {
+        attrs,
+        options,
+        name: v_name,
+        fields,
+        group_name: None,
+        marker: false,
+        nesting: Vec::new(),
+      };
+      match groups.iter().position(|g| g.name == group) {
+        Some(i) if i + 1 == groups.len() => groups[i].variants.push(variant),
+        Some(_) => {
+          return Err(syn::Error::new_spanned(
+            &variant.name,
+            format!("the variants of group `{}` must be declared together", group),
+          ));
+        }
+        None => groups.push(ParsedGroup {
+          attrs: Vec::new(),
+          options: GroupOptions::default(),
+          vis: None,
+          name: group,
+          discriminant: None,
+          variants: vec![variant],
+          nested_docs: Vec::new(),
+          shared: false,
+          unknown: false,
+        }),
+      }
+    }
+
+    let input = EnumGroupInput { attrs, options, vis, name, generics, groups, derived: true };
+    input.check()?;
+    Ok(input)
+  }
+
+  /// Runs every check on a fully resolved wire enum.
+  fn check(&self) -> syn::Result<()> {
+    self.check_duplicates()?;
+    self.check_dispatch_name()?;
+    self.check_arm_names()?;
+    self.check_markers()?;
+    self.check_recursion()?;
+    self.check_legacy()?;
+    self.check_boxed()?;
+    self.check_discriminants()?;
+    self.check_tags()?;
+    self.check_skip_dispatch()?;
+    self.check_group_attrs()?;
+    self.check_partial_handler()?;
+    self.check_on_unexpected()?;
+    self.check_as_group()?;
+    self.check_generics()
+  }
+}
+
+/// Removes the `#[group(Name)]` attribute of a variant of a derived enum and returns the
+/// group name.
+fn derive_group_name(variant: &Ident, attrs: &mut Vec<Attribute>) -> syn::Result<Ident> {
+  let (groups, kept): (Vec<_>, Vec<_>) =
+    std::mem::take(attrs).into_iter().partition(|a| a.path().is_ident("group"));
+  *attrs = kept;
+  match groups.as_slice() {
+    [attr] => attr.parse_args(),
+    [] => Err(syn::Error::new_spanned(
+      variant,
+      format!("`{}` needs a `#[group(Name)]` attribute naming its group", variant),
+    )),
+    [_, extra, ..] => Err(syn::Error::new_spanned(extra, "a variant belongs to a single group")),
+  }
+}
+
+/// A `group Name { ... }` block shared by several wire enums of one invocation.
+#[derive(Debug)]
+pub(crate) struct SharedGroup {
+  /// Attributes of the group enum, e.g. `#[derive(...)]`, without the `#[group(...)]`
+  /// options.
+  pub(crate) attrs: Vec<Attribute>,
+  pub(crate) vis: Visibility,
+  pub(crate) group: ParsedGroup,
+}
+
+impl Parse for SharedGroup {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let mut attrs = input.call(Attribute::parse_outer)?;
+    let mut options = GroupOptions::extract(&mut attrs)?;
+    let vis: Visibility = input.parse()?;
+    let keyword: Ident = input.call(Ident::parse_any)?;
+    if keyword != "group" {
+      return Err(syn::Error::new_spanned(keyword, "expected `enum` or `group`"));
+    }
+    // Deriving `Copy` directly counts as `#[group(copy)]` for the dispatch enums
+    if options.copy.is_none() && has_derive(&attrs, "Copy") {
+      options.copy = Some(keyword.span());
+    }
+    let mut group: ParsedGroup = input.parse()?;
+    if let Some(v) = group.variants.iter().find(|v| !v.nesting.is_empty()) {
+      return Err(syn::Error::new_spanned(&v.nesting[0], "shared groups cannot nest groups"));
+    }
+    if let Some(group_vis) = &group.vis {
+      return Err(syn::Error::new_spanned(group_vis, "put the visibility before `group`"));
+    }
+    if group.shared || group.discriminant.is_some() {
+      return Err(syn::Error::new_spanned(
+        &group.name,
+        "give discriminants of shared groups where they are included (`use Name = N`)",
+      ));
+    }
+    group.options = options;
+    group.strip_prefix()?;
+    Ok(SharedGroup { attrs, vis, group })
+  }
+}
+
+/// Parsed input for `define_enum_group!`: one or more wire enums and the group blocks they
+/// share.
+#[derive(Debug)]
+pub(crate) struct DefineEnumGroups {
+  pub(crate) shared: Vec<SharedGroup>,
+  pub(crate) enums: Vec<EnumGroupInput>,
+}
+
+impl Parse for DefineEnumGroups {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let (mut shared, mut enums) = (Vec::new(), Vec::new());
+    while !input.is_empty() {
+      let fork = input.fork();
+      fork.call(Attribute::parse_outer)?;
+      fork.parse::<Visibility>()?;
+      if fork.peek(Token![enum]) {
+        enums.push(EnumGroupInput::parse_unchecked(input)?);
+      } else {
+        shared.push(input.parse::<SharedGroup>()?);
+      }
+    }
+    if enums.is_empty() {
+      return Err(input.error("expected at least one wire enum"));
+    }
+    if let Some(s) = shared.iter().find(|s| s.group.variants.is_empty()) {
+      return Err(syn::Error::new_spanned(&s.group.name, "a shared group needs variants"));
+    }
+
+    for wire in &mut enums {
+      for group in wire.groups.iter_mut().filter(|g| g.shared) {
+        let Some(def) = shared.iter().find(|s| s.group.name == group.name) else {
+          return Err(syn::Error::new_spanned(
+            &group.name,
+            format!("no shared group named `{}` in this invocation", group.name),
+          ));
+        };
+        group.options = def.group.options.clone();
+        group.variants = def.group.variants.clone();
+      }
+      wire.check()?;
+    }
+    Ok(DefineEnumGroups { shared, enums })
+  }
+}
+
+/// Parsed input for `define_enum_group_pair!`: two wire enums with mirrored groups.
+#[derive(Debug)]
+pub(crate) struct EnumGroupPairInput {
+  pub(crate) first: EnumGroupInput,
+  pub(crate) second: EnumGroupInput,
+  /// The group names as written, shared by both enums, in the first enum's order.
+  pub(crate) groups: Vec<Ident>,
+}
+
+impl Parse for EnumGroupPairInput {
+  fn parse(input: ParseStream) -> syn::Result<Self> {
+    let mut first: EnumGroupInput = input.parse()?;
+    let mut second: EnumGroupInput = input.parse()?;
+    if !input.is_empty() {
+      return Err(input.error("expected exactly two enums"));
+    }
+    if let Some(generics) =
+      [&first, &second].iter().map(|w| &w.generics).find(|g| !g.params.is_empty())
+    {
+      return Err(syn::Error::new_spanned(
+        generics,
+        "generic wire enums are not supported by `define_enum_group_pair!`",
+      ));
+    }
+    for (this, other) in [(&first, &second), (&second, &first)] {
+      if let Some(g) = this.groups.iter().find(|g| !other.groups.iter().any(|o| o.name == g.name)) {
+        return Err(syn::Error::new_spanned(
+          &g.name,
+          format!("group `{}` of `{}` has no counterpart in `{}`", g.name, this.name, other.name),
+        ));
+      }
+    }
+
+    // `Account` becomes `AccountCommand` and `AccountEvent`, so both sets of group enums
+    // can live in the same module
+    let groups = first.groups.iter().map(|g| g.name.clone()).collect();
+    for wire in [&mut first, &mut second] {
+      for g in &mut wire.groups {
+        g.name = pair_group_name(&g.name, &wire.name);
+      }
+      wire.check_recursion()?;
+    }
+    Ok(EnumGroupPairInput { first, second, groups })
+  }
+}
+
+/// The name of the group enum of `group` in the paired wire enum `wire`.
+pub(crate) fn pair_group_name(group: &Ident, wire: &Ident) -> Ident {
+  Ident::new(&format!("{}{}", group.unraw(), wire.unraw()), group.span())
+}
+
+impl EnumGroupInput {
+  /// The name of the dispatch enum, `{Wire}Group` unless renamed with `dispatch_name`.
+  pub(crate) fn dispatch_name(&self) -> Ident {
+    match &self.options.dispatch_name {
+      Some(name) => name.clone(),
+      None => format_ident!("{}Group", self.name),
+    }
+  }
+
+  /// Checks that no two groups, top-level or nested, and no two variants share a name,
+  /// which would otherwise surface as errors about the generated enums.
+  fn check_duplicates(&self) -> syn::Result<()> {
+    let mut groups: Vec<&Ident> = Vec::new();
+    for g in &self.groups {
+      for name in std::iter::once(&g.name).chain(g.nested_docs.iter().map(|(nested, _)| nested)) {
+        if groups.contains(&name) {
+          return Err(syn::Error::new_spanned(
+            name,
+            format!("group `{}` is declared more than once", name),
+          ));
+        }
+        groups.push(name);
+      }
+    }
+    let mut variants: Vec<&Ident> = Vec::new();
+    for v in self.groups.iter().flat_map(|g| &g.variants) {
+      if variants.contains(&&v.name) {
+        return Err(syn::Error::new_spanned(
+          &v.name,
+          format!("variant `{}` is declared more than once", v.name),
+        ));
+      }
+      variants.push(&v.name);
+    }
+    Ok(())
+  }
+
+  /// Checks that a renamed dispatch enum is not named like another generated enum.
+  fn check_dispatch_name(&self) -> syn::Result<()> {
+    let Some(name) = &self.options.dispatch_name else {
+      return Ok(());
+    };
+    let kinds = [format_ident!("{}Kind", self.name), format_ident!("{}GroupKind", self.name)];
+    let mut groups = self
+      .groups
+      .iter()
+      .flat_map(|g| std::iter::once(&g.name).chain(g.nested_docs.iter().map(|(nested, _)| nested)));
+    if *name == self.name || kinds.contains(name) || groups.any(|g| g == name) {
+      return Err(syn::Error::new_spanned(
+        name,
+        format!("`dispatch_name = \"{}\"` clashes with another generated enum", name),
+      ));
+    }
+    Ok(())
+  }
+
+  /// Checks that no group is named `Err` or `None`, which `match_enum_group!` reads as the
+  /// arms of a `Result` or `Option` scrutinee.
+  fn check_arm_names(&self) -> syn::Result<()> {
+    if let Some(g) = self.groups.iter().find(|g| g.name == "Err" || g.name == "None") {
+      return Err(syn::Error::new_spanned(
+        &g.name,
+        format!(
+          "a group cannot be named `{}`, which `match_enum_group!` reserves for `Result` and \
+           `Option` scrutinees",
+          g.name,
+        ),
+      ));
+    }
+    Ok(())
+  }
+
+  /// Checks that the marker structs of `Name()` variants are not named like a generated
+  /// enum.
+  fn check_markers(&self) -> syn::Result<()> {
+    let mut enums = vec![
+      self.name.clone(),
+      self.dispatch_name(),
+      format_ident!("{}Kind", self.name),
+      format_ident!("{}GroupKind", self.name),
+    ];
+    for g in &self.groups {
+      enums.push(g.name.clone());
+      enums.extend(g.nested_docs.iter().map(|(nested, _)| nested.clone()));
+    }
+    let mut markers = self.groups.iter().flat_map(|g| &g.variants).filter(|v| v.marker);
+    if let Some(v) = markers.find(|v| enums.contains(&v.name)) {
+      return Err(syn::Error::new_spanned(
+        &v.name,
+        format!(
+          "`{}()` generates a payload struct `{}`, which clashes with a generated enum; \
+           declare the payload type instead",
+          v.name, v.name,
+        ),
+      ));
+    }
+    Ok(())
+  }
+
+  /// Checks that either every group has an explicit discriminant or none has, so that
+  /// adding a group never shifts an implicit one.
+  fn check_discriminants(&self) -> syn::Result<()> {
+    let explicit = self.groups.iter().filter(|g| g.discriminant.is_some()).count();
+    if explicit == 0 || explicit == self.groups.len() {
+      return Ok(());
+    }
+    let group =
+      self.groups.iter().find(|g| g.discriminant.is_none()).expect("some group lacks one");
+    Err(syn::Error::new_spanned(
+      &group.name,
+      "either every group or none must have an explicit discriminant (`Group = N { ... }`)",
+    ))
+  }
+
+  /// Checks that either every variant has an explicit numeric tag or none has, and that no
+  /// two variants share one.
+  fn check_tags(&self) -> syn::Result<()> {
+    let variants: Vec<_> = self.groups.iter().flat_map(|g| &g.variants).collect();
+    let tagged: Vec<_> = variants.iter().filter_map(|v| v.options.tag.as_ref()).collect();
+    if tagged.is_empty() {
+      return Ok(());
+    }
+    if let Some(v) = variants.iter().find(|v| v.options.tag.is_none()) {
+      return Err(syn::Error::new_spanned(
+        &v.name,
+        "either every variant or none must have an explicit tag (`#[variant(tag = N)]`)",
+      ));
+    }
+    for (i, tag) in tagged.iter().enumerate() {
+      let value = tag.base10_parse::<u16>()?;
+      if tagged[..i].iter().any(|earlier| earlier.base10_parse::<u16>().ok() == Some(value)) {
+        return Err(syn::Error::new_spanned(
+          tag,
+          format!("tag {} is used by more than one variant", value),
+        ));
+      }
+    }
+    Ok(())
+  }
+
+  /// Checks that no group is named like the `Undispatched` variant that the dispatch enum
+  /// gains once some group is left out of it.
+  fn check_skip_dispatch(&self) -> syn::Result<()> {
+    if !self.groups.iter().any(|g| g.options.skip_dispatch) {
+      return Ok(());
+    }
+    match self.groups.iter().find(|g| g.name == "Undispatched") {
+      Some(g) => Err(syn::Error::new_spanned(
+        &g.name,
+        "a group named `Undispatched` clashes with the dispatch enum variant holding the \
+         messages of `skip_dispatch` groups",
+      )),
+      None => Ok(()),
+    }
+  }
+
+  /// Checks that groups deriving on their own keep the derives of the dispatch enum, which
+  /// holds their enums.
+  fn check_group_attrs(&self) -> syn::Result<()> {
+    for group in &self.groups {
+      let Some(derive) = group.attrs.iter().find(|a| a.path().is_ident("derive")) else {
+        continue;
+      };
+      if let Some(missing) = ["Debug", "Clone"].into_iter().find(|d| !has_derive(&group.attrs, d)) {
+        return Err(syn::Error::new_spanned(
+          derive,
+          format!(
+            "the derives of a group replace the outer ones and must include `{}`, which the \
+             dispatch enum derives",
+            missing
+          ),
+        ));
+      }
+    }
+    Ok(())
+  }
+
+  /// Checks that no group is named like the required `handle_unhandled` method of the
+  /// partial handler trait.
+  fn check_partial_handler(&self) -> syn::Result<()> {
+    if !self.options.partial_handler {
+      return Ok(());
+    }
+    match self.groups.iter().find(|g| g.name == "Unhandled") {
+      Some(g) => Err(syn::Error::new_spanned(
+        &g.name,
+        "a group named `Unhandled` clashes with the `handle_unhandled` fallback of \
+         `partial_handler`",
+      )),
+      None => Ok(()),
+    }
+  }
+
+  /// Checks that no group is named like the `on_unexpected` hook, whose setter on the
+  /// routers and closure handlers would clash with the group's `on_*` setter.
+  fn check_on_unexpected(&self) -> syn::Result<()> {
+    if !self.options.router && !self.options.handlers {
+      return Ok(());
+    }
+    match self.groups.iter().find(|g| g.name == "Unexpected") {
+      Some(g) => Err(syn::Error::new_spanned(
+        &g.name,
+        "a group named `Unexpected` clashes with the `on_unexpected` hook of `router` and \
+         `handlers`",
+      )),
+      None => Ok(()),
+    }
+  }
+
+  /// Checks that `as_group` meets no shared group, whose borrowed enum would be defined
+  /// once per wire enum including it.
+  fn check_as_group(&self) -> syn::Result<()> {
+    if !self.options.as_group {
+      return Ok(());
+    }
+    match self.groups.iter().find(|g| g.shared) {
+      Some(g) => Err(syn::Error::new_spanned(
+        &g.name,
+        format!("`as_group` does not support shared groups like `{}`", g.name),
+      )),
+      None => Ok(()),
+    }
+  }
+
+  /// Checks that a generic wire enum enables no option whose generated items do not
+  /// carry generics.
+  fn check_generics(&self) -> syn::Result<()> {
+    if self.generics.params.is_empty() {
+      return Ok(());
+    }
+    match self.options.non_generic_option() {
+      Some(option) => Err(syn::Error::new_spanned(
+        &self.generics,
+        format!("`{option}` is not supported for generic wire enums"),
+      )),
+      None => Ok(()),
+    }
+  }
+
+  /// Checks that only single-payload variants are `boxed`.
+  fn check_boxed(&self) -> syn::Result<()> {
+    let variants = self.groups.iter().flat_map(|g| &g.variants);
+    variants.filter(|v| v.options.boxed).try_for_each(|v| v.require_payload("boxed").map(drop))
+  }
+
+  /// Checks that every `legacy_of` names another variant that is not itself legacy, so
+  /// that normalizing a message takes a single step.
+  fn check_legacy(&self) -> syn::Result<()> {
+    let variants = || self.groups.iter().flat_map(|g| &g.variants);
+    for v in variants() {
+      let Some(target) = &v.options.legacy_of else { continue };
+      // Normalizing converts between the two payloads with `From`
+      v.require_payload("legacy_of")?;
+      if target == &v.name {
+        return Err(syn::Error::new_spanned(target, "a variant cannot be a legacy of itself"));
+      }
+      match variants().find(|other| other.name == *target) {
+        None => {
+          return Err(syn::Error::new_spanned(target, format!("no variant named `{target}`")))
+        }
+        Some(other) if other.options.legacy_of.is_some() => {
+          return Err(syn::Error::new_spanned(
+            target,
+            format!("`{target}` is itself a legacy variant; point `legacy_of` at its replacement"),
+          ))
+        }
+        Some(other) => {
+          if other.payload().is_none() {
+            return Err(syn::Error::new_spanned(
+              target,
+              format!("`legacy_of` requires `{target}` to have a single payload"),
+            ));
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+
+  /// Rejects payloads that contain the wire enum, the dispatch enum or a group enum
+  /// without indirection, in a way that makes one of the generated enums infinitely
+  /// large. rustc reports those as a cycle inside the macro expansion; pointing at the
+  /// payload and suggesting `Box` is more helpful.
+  fn check_recursion(&self) -> syn::Result<()> {
+    let dispatch = self.dispatch_name();
+    // Every group enum, nested ones included, with its depth and the variants it stores at
+    // any depth
+    let mut enums: Vec<(&Ident, usize, Vec<&ParsedVariant>)> = Vec::new();
+    for g in &self.groups {
+      enums.push((&g.name, 0, g.variants.iter().collect()));
+      enums.extend(g.nested_groups().into_iter().map(|n| (n.name, n.depth, n.variants)));
+    }
+    // Nodes: 0 = wire enum, 1 = dispatch enum, 2.. = group enums
+    let node_of = |ident: &Ident| -> Option<usize> {
+      if *ident == self.name {
+        Some(0)
+      } else if *ident == dispatch {
+        Some(1)
+      } else {
+        enums.iter().position(|(name, ..)| *name == ident).map(|i| i + 2)
+      }
+    };
+
+    // The enums named by the field types of a variant stored inline, which `boxed` payloads
+    // are not, with the field type naming them
+    fn stored<'v>(
+      v: &'v ParsedVariant,
+      node_of: &impl Fn(&Ident) -> Option<usize>,
+    ) -> Vec<(&'v Type, &'v Ident, usize)> {
+      if v.options.boxed {
+        return Vec::new();
+      }
+      let mut stored = Vec::new();
+      for ty in v.field_types() {
+        let mut inline = Vec::new();
+        inline_idents(ty, &mut inline);
+        stored.extend(inline.into_iter().filter_map(|ident| Some((ty, ident, node_of(ident)?))));
+      }
+      stored
+    }
+
+    // Every enum a node stores inline: the wire enum its payloads, the dispatch enum the
+    // top-level groups, and a group enum its nested groups and the payloads below it
+    let mut edges: Vec<Vec<usize>> = vec![Vec::new(); enums.len() + 2];
+    for (i, (_, depth, variants)) in enums.iter().enumerate() {
+      if *depth == 0 {
+        edges[1].push(i + 2);
+      }
+      for v in variants {
+        edges[i + 2].extend(v.nesting[*depth..].iter().filter_map(&node_of));
+        edges[i + 2].extend(stored(v, &node_of).into_iter().map(|(.., node)| node));
+      }
+    }
+    for v in self.groups.iter().flat_map(|g| &g.variants) {
+      edges[0].extend(stored(v, &node_of).into_iter().map(|(.., node)| node));
+    }
+    let reaches = |from: usize, to: usize| {
+      let (mut seen, mut stack) = (vec![false; edges.len()], vec![from]);
+      while let Some(node) = stack.pop() {
+        if node == to {
+          return true;
+        }
+        if !std::mem::replace(&mut seen[node], true) {
+          stack.extend(&edges[node]);
+        }
+      }
+      false
+    };
+
+    // A variant is stored by its group enum and by every nested group enclosing it
+    for g in &self.groups {
+      for v in &g.variants {
+        let enclosing: Vec<_> =
+          std::iter::once(&g.name).chain(&v.nesting).filter_map(&node_of).collect();
+        for (ty, ident, node) in stored(v, &node_of) {
+          if reaches(node, 0) || enclosing.iter().any(|&e| reaches(node, e)) {
+            return Err(syn::Error::new_spanned(
+              ty,
+              format!(
+                "`{ident}` is stored here without indirection, so the generated enums would \
+                 have infinite size; use `Box<{ident}>` instead",
+              ),
+            ));
+          }
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Collects the single-segment type names stored inline in `ty`, looking through tuples,
+/// arrays, parentheses and `Option`, but not through pointers or collections.
+fn inline_idents<'a>(ty: &'a Type, out: &mut Vec<&'a Ident>) {
+  match ty {
+    Type::Paren(inner) => inline_idents(&inner.elem, out),
+    Type::Group(inner) => inline_idents(&inner.elem, out),
+    Type::Array(array) => inline_idents(&array.elem, out),
+    Type::Tuple(tuple) => tuple.elems.iter().for_each(|elem| inline_idents(elem, out)),
+    Type::Path(path) if path.qself.is_none() => {
+      let Some(last) = path.path.segments.last() else { return };
+      match &last.arguments {
+        syn::PathArguments::None if path.path.segments.len() == 1 => out.push(&last.ident),
+        syn::PathArguments::AngleBracketed(args) if last.ident == "Option" => {
+          for arg in &args.args {
+            if let syn::GenericArgument::Type(inner) = arg {
+              inline_idents(inner, out);
+            }
+          }
+        }
+        _ => {}
+      }
+    }
+    _ => {}
+  }
+}
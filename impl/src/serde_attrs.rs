@@ -0,0 +1,132 @@
+//! Read-only inspection of `#[serde(...)]` attributes.
+//!
+//! The macro never interprets serde attributes for code generation of the enums
+//! themselves (they are propagated verbatim), but some generated helpers need to know the
+//! tag a variant is serialized under. Malformed serde attributes are ignored here; serde's
+//! derive reports them.
+
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, LitStr, Token};
+
+/// Container-level serde settings relevant to the generated helpers.
+#[derive(Debug, Default)]
+pub(crate) struct SerdeContainer {
+  pub(crate) rename_all: Option<String>,
+  /// `tag = "..."`: the field holding the tag of internally or adjacently tagged enums.
+  pub(crate) tag: Option<String>,
+  /// `content = "..."`: the field holding the payload of adjacently tagged enums.
+  pub(crate) content: Option<String>,
+  pub(crate) untagged: bool,
+}
+
+impl SerdeContainer {
+  pub(crate) fn from_attrs(attrs: &[Attribute]) -> Self {
+    let mut container = SerdeContainer::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+      let _ = attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("rename_all") {
+          container.rename_all = parse_serialize_name(&meta)?;
+          Ok(())
+        } else if meta.path.is_ident("tag") {
+          container.tag = Some(meta.value()?.parse::<LitStr>()?.value());
+          Ok(())
+        } else if meta.path.is_ident("content") {
+          container.content = Some(meta.value()?.parse::<LitStr>()?.value());
+          Ok(())
+        } else if meta.path.is_ident("untagged") {
+          container.untagged = true;
+          Ok(())
+        } else {
+          skip(&meta)
+        }
+      });
+    }
+    container
+  }
+
+  /// Whether the tag is a field inside the payload (`tag` without `content`).
+  pub(crate) fn internally_tagged(&self) -> bool {
+    self.tag.is_some() && self.content.is_none() && !self.untagged
+  }
+
+  /// The tag a variant named `name` with attributes `attrs` is serialized under.
+  pub(crate) fn variant_tag(&self, name: &str, attrs: &[Attribute]) -> String {
+    let mut rename = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+      let _ = attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("rename") {
+          rename = parse_serialize_name(&meta)?;
+          Ok(())
+        } else {
+          skip(&meta)
+        }
+      });
+    }
+
+    match (rename, &self.rename_all) {
+      (Some(rename), _) => rename,
+      (None, Some(rule)) => apply_rename_rule(rule, name),
+      (None, None) => name.to_string(),
+    }
+  }
+}
+
+/// Parses `key = "name"` or `key(serialize = "name", deserialize = "...")`, returning the
+/// serialization name.
+fn parse_serialize_name(meta: &ParseNestedMeta) -> syn::Result<Option<String>> {
+  if meta.input.peek(Token![=]) {
+    let lit: LitStr = meta.value()?.parse()?;
+    return Ok(Some(lit.value()));
+  }
+  let mut name = None;
+  meta.parse_nested_meta(|inner| {
+    if inner.path.is_ident("serialize") {
+      let lit: LitStr = inner.value()?.parse()?;
+      name = Some(lit.value());
+      Ok(())
+    } else {
+      skip(&inner)
+    }
+  })?;
+  Ok(name)
+}
+
+/// Consumes the value of a nested meta item we are not interested in.
+fn skip(meta: &ParseNestedMeta) -> syn::Result<()> {
+  if meta.input.peek(Token![=]) {
+    meta.value()?.parse::<syn::Expr>()?;
+  } else if meta.input.peek(syn::token::Paren) {
+    meta.parse_nested_meta(|inner| skip(&inner))?;
+  }
+  Ok(())
+}
+
+/// Applies a serde `rename_all` rule to a `PascalCase` variant name, mirroring serde's
+/// own `RenameRule::apply_to_variant`.
+fn apply_rename_rule(rule: &str, variant: &str) -> String {
+  let snake = || {
+    let mut snake = String::new();
+    for (i, ch) in variant.char_indices() {
+      if i > 0 && ch.is_uppercase() {
+        snake.push('_');
+      }
+      snake.push(ch.to_ascii_lowercase());
+    }
+    snake
+  };
+
+  match rule {
+    "lowercase" => variant.to_ascii_lowercase(),
+    "UPPERCASE" => variant.to_ascii_uppercase(),
+    "PascalCase" => variant.to_string(),
+    "camelCase" => {
+      let mut chars = variant.chars();
+      chars.next().map(|c| c.to_ascii_lowercase().to_string() + chars.as_str()).unwrap_or_default()
+    }
+    "snake_case" => snake(),
+    "SCREAMING_SNAKE_CASE" => snake().to_ascii_uppercase(),
+    "kebab-case" => snake().replace('_', "-"),
+    "SCREAMING-KEBAB-CASE" => snake().to_ascii_uppercase().replace('_', "-"),
+    _ => variant.to_string(),
+  }
+}
@@ -0,0 +1,282 @@
+//! Small helpers shared by the code generators.
+
+use std::collections::HashSet;
+
+use proc_macro2::{Span, TokenStream as TokenStream2, TokenTree};
+use quote::{quote, ToTokens};
+use syn::punctuated::Punctuated;
+use syn::{
+  parse_quote, Attribute, GenericParam, Generics, Ident, Meta, Path, Token, Type, Visibility,
+  WhereClause,
+};
+
+/// Converts a `PascalCase` identifier into `snake_case`.
+pub(crate) fn to_snake_case(name: &str) -> String {
+  let mut out = String::with_capacity(name.len() + 4);
+  let chars: Vec<char> = name.chars().collect();
+  for (i, &c) in chars.iter().enumerate() {
+    if c.is_uppercase() {
+      // Start a new word on a lower→upper boundary, or before the last capital of an
+      // acronym (`HTTPServer` → `http_server`).
+      let prev_lower = i > 0 && (chars[i - 1].is_lowercase() || chars[i - 1].is_ascii_digit());
+      let acronym_end =
+        i > 0 && chars[i - 1].is_uppercase() && chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+      if prev_lower || acronym_end {
+        out.push('_');
+      }
+      out.extend(c.to_lowercase());
+    } else {
+      out.push(c);
+    }
+  }
+  out
+}
+
+/// Builds an identifier, falling back to a raw identifier for keywords (`type` → `r#type`).
+pub(crate) fn ident(name: &str, span: Span) -> Ident {
+  match syn::parse_str::<Ident>(name) {
+    Ok(mut ident) => {
+      ident.set_span(span);
+      ident
+    }
+    Err(_) => Ident::new_raw(name, span),
+  }
+}
+
+/// The `snake_case` form of `name`, keeping its span.
+pub(crate) fn snake_ident(name: &Ident) -> Ident {
+  ident(&to_snake_case(&name.to_string()), name.span())
+}
+
+/// Translates `vis` so that it means the same thing one module level deeper.
+///
+/// Used for items placed in a generated child module that should be exactly as visible
+/// as the item they were derived from.
+pub(crate) fn nested_visibility(vis: &Visibility) -> TokenStream2 {
+  match vis {
+    Visibility::Public(_) => quote! { pub },
+    Visibility::Inherited => quote! { pub(super) },
+    Visibility::Restricted(restricted) => {
+      let path = &restricted.path;
+      let first = path.segments.first().map(|s| s.ident.to_string());
+      match first.as_deref() {
+        Some("self") if path.segments.len() == 1 => quote! { pub(super) },
+        Some("self") => {
+          let rest = path.segments.iter().skip(1);
+          quote! { pub(in super #(::#rest)*) }
+        }
+        Some("super") => quote! { pub(in super::#path) },
+        _ => quote! { #vis },
+      }
+    }
+  }
+}
+
+/// Names that resolve through the prelude or the primitive types rather than the
+/// enclosing module, so they cannot be re-exported through `super::`.
+const NON_REEXPORTABLE: &[&str] = &[
+  "bool", "char", "str", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64",
+  "u128", "usize", "f32", "f64", "String", "Vec", "Box", "Option", "Result",
+];
+
+/// Returns the path to `use` for a payload type from inside a generated child module,
+/// or `None` when the type cannot be re-exported (generic arguments, prelude types,
+/// references, tuples, ...).
+pub(crate) fn reexport_path(ty: &Type) -> Option<TokenStream2> {
+  let Type::Path(type_path) = ty else { return None };
+  if type_path.qself.is_some() {
+    return None;
+  }
+  let path = &type_path.path;
+  if path.segments.iter().any(|s| !s.arguments.is_empty()) {
+    return None;
+  }
+
+  let first = path.segments.first()?.ident.to_string();
+  if path.leading_colon.is_some() || first == "crate" {
+    return Some(quote! { #path });
+  }
+  if path.segments.len() == 1 {
+    if NON_REEXPORTABLE.contains(&first.as_str()) {
+      return None;
+    }
+    return Some(quote! { super::#path });
+  }
+  match first.as_str() {
+    "self" => {
+      let rest = path.segments.iter().skip(1);
+      Some(quote! { super #(::#rest)* })
+    }
+    "super" => Some(quote! { super::#path }),
+    // A relative multi-segment path may name either a local module or an external
+    // crate; we cannot tell which from here.
+    _ => None,
+  }
+}
+
+/// The `#[cfg(...)]` attributes among `attrs`.
+pub(crate) fn cfg_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+  attrs.iter().filter(|a| a.path().is_ident("cfg"))
+}
+
+/// Whether `attr` is a doc comment, `/// ...` or `#[doc = "..."]`.
+pub(crate) fn is_doc_comment(attr: &Attribute) -> bool {
+  matches!(&attr.meta, Meta::NameValue(meta) if meta.path.is_ident("doc"))
+}
+
+/// The doc comments among `attrs`.
+pub(crate) fn doc_comments(attrs: &[Attribute]) -> Vec<&Attribute> {
+  attrs.iter().filter(|attr| is_doc_comment(attr)).collect()
+}
+
+/// `#[doc(cfg(...))]` mirroring the `#[cfg(...)]` attributes among `attrs`, so rustdoc shows
+/// which configuration an item requires. Empty unless the `doc-cfg` feature is enabled.
+pub(crate) fn doc_cfg(attrs: &[Attribute]) -> TokenStream2 {
+  if !cfg!(feature = "doc-cfg") {
+    return TokenStream2::new();
+  }
+  let predicates: Vec<TokenStream2> =
+    cfg_attrs(attrs).filter_map(|a| a.parse_args::<TokenStream2>().ok()).collect();
+  match predicates.len() {
+    0 => TokenStream2::new(),
+    1 => quote! { #[doc(cfg(#(#predicates)*))] },
+    _ => quote! { #[doc(cfg(all(#(#predicates),*)))] },
+  }
+}
+
+/// Whether some `#[derive(...)]` in `attrs` lists the derive named `name` (matched on the last
+/// path segment, like [`without_derive`]).
+pub(crate) fn has_derive(attrs: &[Attribute], name: &str) -> bool {
+  attrs.iter().filter(|attr| attr.path().is_ident("derive")).any(|attr| {
+    attr
+      .parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+      .is_ok_and(|paths| paths.iter().any(|p| p.segments.last().is_some_and(|s| s.ident == name)))
+  })
+}
+
+/// The path of the derive named `name` in `attrs`, as written: `Serialize` or
+/// `serde::Serialize`.
+pub(crate) fn derive_path(attrs: &[Attribute], name: &str) -> Option<Path> {
+  attrs.iter().filter(|attr| attr.path().is_ident("derive")).find_map(|attr| {
+    let paths = attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated).ok()?;
+    paths.into_iter().find(|p| p.segments.last().is_some_and(|s| s.ident == name))
+  })
+}
+
+/// Removes the derive named `name` (matched on the last path segment, so both `Deserialize`
+/// and `serde::Deserialize` are caught) from every `#[derive(...)]` in `attrs`.
+pub(crate) fn without_derive(attrs: &[Attribute], name: &str) -> Vec<Attribute> {
+  attrs
+    .iter()
+    .filter_map(|attr| {
+      if !attr.path().is_ident("derive") {
+        return Some(attr.clone());
+      }
+      let Ok(paths) = attr.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated) else {
+        return Some(attr.clone());
+      };
+      let kept: Vec<&Path> =
+        paths.iter().filter(|p| !matches!(p.segments.last(), Some(s) if s.ident == name)).collect();
+      if kept.is_empty() {
+        None
+      } else {
+        Some(parse_quote! { #[derive(#(#kept),*)] })
+      }
+    })
+    .collect()
+}
+
+/// The parameters of `generics` that `types` mention, with the bounds and where-clause
+/// predicates that mention no other parameter. A left-out parameter could not be declared
+/// without a use, so its bounds go with it: `U: From<T>` keeps `U` unbounded when `T` is
+/// not mentioned. Parameters are matched by name, which is all a macro can see.
+pub(crate) fn used_generics<'a>(
+  generics: &Generics,
+  types: impl IntoIterator<Item = &'a Type>,
+) -> Generics {
+  let mut mentioned = HashSet::new();
+  for ty in types {
+    collect_names(ty.to_token_stream(), &mut mentioned);
+  }
+  let unused: HashSet<_> =
+    generics.params.iter().map(param_name).filter(|name| !mentioned.contains(name)).collect();
+  let independent = |tokens: TokenStream2| {
+    let mut names = HashSet::new();
+    collect_names(tokens, &mut names);
+    names.is_disjoint(&unused)
+  };
+
+  let params = generics
+    .params
+    .iter()
+    .filter(|p| !unused.contains(&param_name(p)))
+    .cloned()
+    .map(|mut param| {
+      match &mut param {
+        GenericParam::Type(t) => {
+          t.bounds =
+            t.bounds.iter().filter(|b| independent(b.to_token_stream())).cloned().collect();
+        }
+        GenericParam::Lifetime(l) => {
+          l.bounds =
+            l.bounds.iter().filter(|b| independent(b.to_token_stream())).cloned().collect();
+        }
+        GenericParam::Const(_) => {}
+      }
+      param
+    })
+    .collect();
+  let where_clause = generics.where_clause.as_ref().map(|w| WhereClause {
+    where_token: w.where_token,
+    predicates: w
+      .predicates
+      .iter()
+      .filter(|predicate| independent(predicate.to_token_stream()))
+      .cloned()
+      .collect(),
+  });
+  Generics {
+    lt_token: generics.lt_token,
+    params,
+    gt_token: generics.gt_token,
+    where_clause: where_clause.filter(|w| !w.predicates.is_empty()),
+  }
+}
+
+/// Whether `ty` mentions any of `names`, e.g. one of the generated enums.
+pub(crate) fn mentions(ty: &Type, names: &[String]) -> bool {
+  let mut mentioned = HashSet::new();
+  collect_names(ty.to_token_stream(), &mut mentioned);
+  names.iter().any(|name| mentioned.contains(name))
+}
+
+/// The name a generic parameter is referred to by: `T`, `N` or `'a`.
+fn param_name(param: &GenericParam) -> String {
+  match param {
+    GenericParam::Type(t) => t.ident.to_string(),
+    GenericParam::Const(c) => c.ident.to_string(),
+    GenericParam::Lifetime(l) => l.lifetime.to_string(),
+  }
+}
+
+/// Collects every identifier and lifetime in `tokens`, lifetimes with their quote.
+fn collect_names(tokens: TokenStream2, out: &mut HashSet<String>) {
+  let mut quoted = false;
+  for tree in tokens {
+    match tree {
+      TokenTree::Group(group) => collect_names(group.stream(), out),
+      TokenTree::Ident(ident) if quoted => {
+        out.insert(format!("'{ident}"));
+      }
+      TokenTree::Ident(ident) => {
+        out.insert(ident.to_string());
+      }
+      TokenTree::Punct(ref punct) => {
+        quoted = punct.as_char() == '\'';
+        continue;
+      }
+      TokenTree::Literal(_) => {}
+    }
+    quoted = false;
+  }
+}
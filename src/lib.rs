@@ -42,6 +42,8 @@
 //! // - enum WireMsg { A(MsgA), B(MsgB), C(MsgC) }
 //! // - enum WireMsgGroup { Protocol(Protocol), Business(Business) }
 //! // - impl EnumGroup for WireMsg
+//! // - enum WireMsgKind { A, B, C }
+//! // - enum WireMsgGroupKind { Protocol, Business }
 //!
 //! fn handle_message(msg: WireMsg) {
 //!     match_enum_group!(msg, WireMsg, {
@@ -62,6 +64,24 @@
 //! - **Serde compatible**: Attributes like `#[serde(...)]` are propagated
 //! - **IDE support**: Full autocomplete and type checking
 //!
+//! ## Cargo Features
+//!
+//! - `doc-cfg`: emit `#[doc(cfg(...))]` next to every `#[cfg(...)]` the macros propagate,
+//!   so rustdoc shows which configuration a generated item requires. Nightly only; the
+//!   crate invoking the macros needs `#![feature(doc_cfg)]` (typically behind
+//!   `#![cfg_attr(docsrs, feature(doc_cfg))]`).
+//! - `avro`: support code for the Avro schema and encoding helpers `define_enum_group!`
+//!   generates with `#[enum_group(avro)]`.
+//! - `futures`: the `Sink` trait the fan-out router `define_enum_group!` generates with
+//!   `#[enum_group(sink_router)]` implements.
+//! - `json`: support code for the JSON helpers `define_enum_group!` generates on request,
+//!   such as `#[enum_group(sample_json)]`, `#[enum_group(deserialize_by_tag)]`, a bare
+//!   `#[enum_group(decode_by_tag)]` and
+//!   `#[enum_group(peek_kind)]`, which also uses the `WireFormat` and `PeekError` types
+//!   this feature adds.
+//! - `pyo3`: support code for the Python bindings `define_enum_group!` generates with
+//!   `#[enum_group(pyo3)]`. Implies `json`.
+//!
 //! ## How It Works
 //!
 //! The `define_enum_group!` macro generates:
@@ -70,17 +90,783 @@
 //! 2. **Wire enum**: A flat enum with all variants for serialization
 //! 3. **Group dispatch enum**: An enum wrapping group enums (e.g., `WireMsgGroup`)
 //! 4. **EnumGroup impl**: Conversion from wire enum to grouped representation
+//! 5. **Kind enums**: Fieldless `WireMsgKind` / `WireMsgGroupKind` identifiers
+//!
+//! `define_enum_group_pair!` expands two such definitions at once, such as commands and
+//! events, checking that their groups mirror each other.
+//!
+//! `#[derive(EnumGroup)]` generates the same items for an ordinary enum whose variants
+//! name their group with `#[group(Name)]`, for tools that work better on plain Rust than
+//! on macro bodies.
+//!
+//! `migrate_enum_group!` generates the conversion from one version of a wire enum to the
+//! next, checked at compile time to cover every old variant.
 //!
 //! The `match_enum_group!` macro expands to a match on the grouped representation,
 //! using the `EnumGroup` trait to access the `Group` type without explicit imports.
+//!
+//! ## Generated items
+//!
+//! Besides the group, wire, dispatch and kind enums, `define_enum_group!` generates:
+//!
+//! - `WireMsgGroupKind::ALL`, `WireMsg::GROUP_COUNT` and `WireMsg::group_names()` - every
+//!   group in declaration order, for setting up per-group state at startup
+//! - `WireMsg::VARIANT_COUNT`, `WireMsg::VARIANT_NAMES` and `WireMsg::GROUP_NAMES` - the
+//!   same as arrays, `[&'static str; VARIANT_COUNT]`, for sizing tables at compile time
+//! - `WireMsgKind::ALL` and `WireMsgKind::iter()`, `WireMsgGroupKind::iter()` - every kind
+//!   in declaration order, e.g. to register a metric per message type at startup
+//! - `msg.group_name()` and `WireMsgGroupKind::name()` - the declared name of a group as a
+//!   `&'static str`, for log fields and metric labels
+//! - `msg.is_protocol()`, ... - whether a message belongs to a group, e.g. for
+//!   `Iterator::filter`
+//! - `msg.variant_name()`, `WireMsgKind::name()` and `Protocol::variant_name()`, ... - the
+//!   same for the wire enum variant of a message, without formatting its payload
+//! - `msg.kind()` and `msg.group_kind()` - the kind of a message's variant and group,
+//!   without importing a trait
+//! - `impl EnumGroupKind for WireMsg` - `kind()` / `group_kind()` through a trait bound
+//! - `impl PartialEq<WireMsgKind> for WireMsg` (and the reverse) - `msg == WireMsgKind::A`
+//!   compares the kind only
+//! - `impl GroupOf<WireMsg> for Protocol`, ... - conversions between each group enum and
+//!   the wire enum
+//! - `impl TryFrom<WireMsg> for Protocol`, ... - the same check through the standard trait,
+//!   handing back the original message as the error when it belongs to another group
+//! - `impl VariantOf<WireMsg> for MsgA`, ... - conversions between each payload type and
+//!   the wire enum, for payload types that back a single variant
+//! - `impl PayloadOf<WireMsg> for MsgA`, ... - for the same payload types, naming their
+//!   group enum (`Group = Protocol`) and wrapping them in it with `into_group`. Payloads of
+//!   groups with a restricted visibility of their own are left out
+//! - `impl From<MsgA> for WireMsg`, ... - for the same payload types, so that
+//!   `tx.send(msg_a.into())` and `?` build messages. A payload field marked `#[from]` is
+//!   left to the derive asking for it (`thiserror`, `derive_more`)
+//! - `impl TryFrom<WireMsg> for MsgA`, ... - for the same payload types, extracting the
+//!   payload or handing back the original message. Payloads holding one of the generated
+//!   enums are left out, as the impl could overlap with the standard ones
+//! - `WireMsg::TAG_TABLE`, `Protocol::TAG_TABLE`, ... - `(kind, tag)` pairs using the
+//!   names serde serializes each variant under (`rename` / `rename_all` are honoured)
+//! - `WireMsg::WIRE_FINGERPRINT` - a `u64` hash of the group names, variant names and
+//!   tags in declaration order, to compare with peers at handshake
+//! - `WireMsg::assert_tags(&[("Protocol", "A"), ...])` - panics with a diff unless the
+//!   `(group, tag)` pairs of all variants equal the given list, to freeze tags in a test
+//!
+//! ## Declaring variants and groups
+//!
+//! Variants of the wire and group enums carry `#[doc(alias = "...")]` attributes for their
+//! serde tag, and the wire variants also for their group name, so rustdoc search finds a
+//! message by any of these names.
+//!
+//! Attributes of the payload field go inside the parentheses and are propagated to the
+//! variant on the wire and group enums alike, e.g.
+//! `Signature(#[serde(with = "serde_bytes")] Vec<u8>)`.
+//!
+//! Variants may also have named fields, `Connected { session_id: u64, addr: SocketAddr }`,
+//! or no payload at all, `Ping`, and are declared alike on the wire and group enums. Such a
+//! variant has no payload type, so `VariantOf` is not implemented for it and
+//! `variant_handler` passes its fields as one argument each, or none. Options decoding or
+//! converting a single payload (`avro`, `payload_any` and `legacy_of`) reject both, while
+//! `deserialize_by_tag` and `decode_by_tag` accept unit variants, which need nothing but
+//! their tag.
+//!
+//! `Ping()` is shorthand for a payload without data: the macro generates a unit struct
+//! `Ping` next to the enums and declares the variant as `Ping(Ping)`, so it gets a payload
+//! type like any other variant without one being written by hand. The struct derives
+//! `Debug`, `Clone`, `Copy`, `Default`, the comparison traits and `Hash`, plus serde's
+//! `Serialize` and `Deserialize` when the wire enum derives them.
+//!
+//! Groups may nest groups of their own, `Protocol { Handshake { Hello(Hello), Ack(Ack) },
+//! Ping(Ping) }`: the wire enum stays flat, while `Protocol` gets a `Handshake(Handshake)`
+//! variant wrapping a `Handshake` enum of its own, to any depth. Every nested group enum
+//! implements `GroupOf`, so a message converts straight into any level, and `group_modules`
+//! re-exports them along with their group. Kinds, handlers and the other options see the
+//! top-level groups only. A nested group is told apart from a variant with named fields by
+//! its body, which does not start with `field:`, so it serializes as a variant of its
+//! parent wrapping its own enum, and does not share the wire enum's serde layout.
+//!
+//! A variant may take another name on its group enum, `MsgTypeA(MsgA) as Login`, to drop the
+//! protocol prefixes of the wire names. Only the group enum variant is renamed: the wire
+//! enum, the kinds, handlers and other options keep the wire name, and a serializable group
+//! enum keeps its tag through `#[serde(rename = "...")]`, with a doc alias for the wire
+//! name.
+//!
+//! A group may be given its own visibility, `pub(crate) Internal { ... }`, to keep an
+//! internal category out of the public API while the wire enum stays `pub`; groups without
+//! one take the wire enum's. Its group tag and `group_modules` module take it as well.
+//! The dispatch enum and the generated traits still name it, with `private_interfaces`
+//! allowed, so their users outside its visibility can only match it with `_`.
+//!
+//! The wire enum may be generic, `enum WireMsg<'a, T: Codec> where T: Send { ... }`, const
+//! parameters included, `enum Packet<const N: usize> { Data { Raw([u8; N]) } }`. Each group
+//! enum takes the parameters its payloads mention, in type arguments and array lengths
+//! alike, along with the bounds and where-clause predicates that only involve those, so a
+//! group without generic payloads stays a plain enum and bounded payload wrappers need no
+//! bounds repeated; the dispatch
+//! enum and every impl take all of them. Payloads mentioning a parameter get no
+//! `VariantOf` impl and are not re-exported by `group_modules`, and shared groups cannot
+//! hold them. The `handler`, `partial_handler` and `variant_handler` traits take the same
+//! parameters as the wire enum, ahead of `Ctx`, so a zero-copy `enum Frame<'a>` dispatches
+//! to an `impl<'a> FrameHandler<'a> for Parser`. Other options generating traits, tables or
+//! foreign bindings over the wire enum (`router`, `handlers`, `avro`, `pyo3`, ...) are
+//! rejected on generic wire enums, as is `define_enum_group_pair!`.
+//!
+//! Groups may be given explicit discriminants, `Protocol = 1 { ... }`, either all of them or
+//! none. The dispatch enum then becomes `#[repr(u16)]` with these discriminants, and both
+//! it and the wire enum get `group_discriminant(&self) -> u16`, which stays stable when
+//! groups are reordered or inserted, for persisting group identity.
+//!
+//! The wire and group enums share the outer serde attributes, so every serde enum
+//! representation (externally, internally or adjacently tagged, and untagged) applies to
+//! both, and the generated helpers follow it. Layouts that would only fail at runtime are
+//! rejected: internally tagged enums with payloads that are not structs or maps, and
+//! deserializable untagged enums with a variant shadowed by an earlier one of the same
+//! payload type.
+//!
+//! A last group may be the catch-all `#[unknown] Unknown`, a group `Unknown` of its own
+//! holding a single variant `Unknown`, so that deserializing messages of newer peers does
+//! not fail outright. With serde derives, a unit catch-all is marked `#[serde(other)]`,
+//! which only sees the tag and thus requires an internally or adjacently tagged enum, while
+//! `#[unknown] Unknown(serde_json::Value)` is marked `#[serde(untagged)]` and captures the
+//! whole message in any layout, serializing it back unchanged. The catch-all has no tag of
+//! its own: it is left out of `TAG_TABLE` and `assert_tags`, and `peek_kind`,
+//! `deserialize_by_tag` and `decode_by_tag` return it for the tags (and variant indices) no
+//! other variant has. Given the tag separately, `deserialize_by_tag` hands a payload
+//! catch-all the message reassembled in the enum's layout, while `decode_by_tag` decodes it
+//! from the payload bytes alone.
+//!
+//! Payloads may contain the generated enums behind indirection (`Batch(Vec<WireMsg>)`,
+//! `Wrapped(Box<WireMsg>)`). Storing one inline in a way that would make the enums
+//! infinitely large is rejected with an error at the payload suggesting `Box`.
+//!
+//!
+//! ## Options
+//!
+//! Generation can be tuned with `#[enum_group(...)]` attributes placed next to the other
+//! attributes of the wire enum. They are consumed by the macro and never emitted on the
+//! generated types.
+//!
+//! - `wire_attr(...)`, `group_attr(...)`, `dispatch_attr(...)`, `kind_attr(...)`,
+//!   `group_kind_attr(...)` - extra attributes for one kind of generated enum only, e.g.
+//!   `dispatch_attr(derive(strum::IntoStaticStr), strum(serialize_all = "snake_case"))`.
+//!   The dispatch and kind enums do not inherit the outer attributes, so this is the way to
+//!   add derives (strum's or any other) to them.
+//! - `group_modules` - additionally generate one module per group, named after the group
+//!   in `snake_case`, re-exporting the group enum and its payload types
+//!   (`pub mod protocol { pub use super::{Protocol, MsgA, MsgB}; }`). Payload types that
+//!   are not nameable through the parent module (prelude types, types with generic
+//!   arguments) are left out. The modules name their items through `super::`, so the
+//!   macro has to be invoked at module level rather than inside a function body.
+//! - `groups_module(name)` - generate the group enums, nested ones included, inside a
+//!   module `name` with the visibility of the wire enum, rather than next to it, so that
+//!   dozens of groups neither crowd the surrounding module nor clash with its types. The
+//!   generated code, `group_modules` and `group_tags` name them as `name::Protocol`, as
+//!   does user code. Shared groups stay where they are declared. The module imports its
+//!   parent's items through `super::*`, so the macro has to be invoked at module level.
+//! - `group_tags` - additionally generate a zero-sized marker type per group, named after
+//!   the group with a `Tag` suffix (`pub struct ProtocolTag;`), implementing `GroupTag`
+//!   with `Wire = WireMsg` and `Group = Protocol`.
+//! - `as_group` - additionally generate borrowed counterparts of the group and dispatch
+//!   enums, `ProtocolRef<'msg>`, ..., `WireMsgGroupRef<'msg>`, whose variants hold
+//!   references to the fields, and `msg.as_group() -> WireMsgGroupRef<'_>`, to branch on
+//!   the group without moving or cloning the message, and likewise `ProtocolMut<'msg>`,
+//!   ..., `WireMsgGroupMut<'msg>` and `msg.as_group_mut()` with mutable references, to
+//!   modify a payload in place. Legacy variants are borrowed as they are rather than
+//!   converted; shared groups are not supported.
+//! - `accessors` - additionally generate `msg.as_a() -> Option<&MsgA>` and
+//!   `msg.into_a() -> Option<MsgA>` for every single-payload variant, named after the variant
+//!   in `snake_case`, like `enum-as-inner` does. `boxed` payloads are returned unboxed.
+//! - `display` - additionally implement `Display` for the wire enum, printing the group and
+//!   variant names of a message (`Protocol::A`) without requiring the payloads to implement
+//!   `Display`, for log lines and errors about unexpected messages.
+//! - `router` - additionally generate `WireMsgRouter<S, T = (), E = BoxError>`, holding
+//!   one handler `FnMut(&mut S, Group) -> Result<T, E>` per group (registered with
+//!   `.on_protocol(...)` etc.) and dispatching with `router.route(&mut state, msg)`.
+//!   Messages of a group without handler come back as `RouteError::Unhandled(msg)`.
+//!   `WireMsgAsyncRouter` is the same with handlers returning a `BoxFuture` and an
+//!   `async fn route`.
+//! - `filter` - additionally generate `WireMsgFilter`, a set of selected kinds parsed at
+//!   runtime with `WireMsg::parse_filter(s)` (or `s.parse::<WireMsgFilter>()`) and tested
+//!   with `filter.matches(&msg)`. A filter is a list of rules separated by commas or
+//!   whitespace: `*`, `Protocol` / `Protocol.*` (a whole group), `Business.C` (one
+//!   variant), each optionally negated with `!`. A message matches if it matches any
+//!   positive rule (or there is none) and no negated one, so `"Protocol.*, !Protocol.B"`
+//!   selects every protocol message but `B`. Unknown names are a `FilterError`.
+//! - `queues` - additionally generate `WireMsgQueues`, a buffer holding one public
+//!   `VecDeque` per group (`queues.protocol: VecDeque<Protocol>`), with `push(msg)` routing
+//!   a message to its group's queue, `pop_protocol()` etc. taking from one queue, and
+//!   `pop()` taking from the first non-empty queue in group declaration order, so groups
+//!   declared first take priority.
+//! - `handlers` - additionally generate `WireMsgHandlers<'a, T = ()>`, optional closures
+//!   for some groups built with `WireMsgHandlers::new().on_protocol(|p| ...)`, and
+//!   `handlers.handle(msg) -> T`. Messages of other groups go to a fallback, returning
+//!   `T::default()` by default or set with `WireMsgHandlers::otherwise(|msg| ...)`.
+//! - `kind_map` - additionally generate `WireMsgKindMap<V>`, a map from `WireMsgKind` to `V`
+//!   stored in a fixed-size array indexed by variant, with `get`, `get_mut`, `insert`,
+//!   `remove` and `iter()` yielding `(WireMsgKind, &V)` in declaration order.
+//! - `group_index_table` - additionally generate `WireMsg::GROUP_INDEX_TABLE`, the group
+//!   index of every variant, and `msg.group_index_fast() -> usize`, which reads the
+//!   discriminant and looks it up in the table instead of matching, for the hottest routing
+//!   loops. Requires an integer representation such as `#[repr(u16)]` or
+//!   `#[enum_group(wire_attr(repr(u16)))]` on the wire enum, whose variants get their
+//!   indices as explicit discriminants.
+//! - `payload_any` - additionally generate `msg.into_payload_any() -> (WireMsgKind,
+//!   Box<dyn Any + Send>)` and `msg.payload_downcast::<T>() -> Result<T, WireMsg>`, which
+//!   returns the message unchanged if its payload is not a `T`. Requires `'static + Send`
+//!   payloads.
+//! - `handler` - additionally generate a `WireMsgHandler` trait with one
+//!   `fn handle_protocol(&mut self, msg: Protocol) -> Self::Output` per group and
+//!   `msg.dispatch(&mut handler)`, plus its async counterpart `WireMsgAsyncHandler`, whose
+//!   methods return `impl Future<Output = Self::Output> + Send`, with
+//!   `msg.dispatch_async(&mut handler).await`. The async trait needs no boxing; implement
+//!   its methods as `async fn`.
+//!   `WireMsgTryHandler` / `WireMsgAsyncTryHandler` are the fallible variants, whose
+//!   methods return `Result<Self::Output, Self::Error>` and whose `on_error(group, error)`
+//!   hook sees every error before `msg.try_dispatch(&mut handler)` /
+//!   `msg.try_dispatch_async(&mut handler)` returns it.
+//!   With `handler(context)` all four traits are generic over a context type, e.g.
+//!   `WireMsgHandler<Ctx>` with `fn handle_protocol(&mut self, ctx: &mut Ctx, msg: Protocol)`,
+//!   and the dispatchers take the context after the handler
+//!   (`msg.dispatch(&mut handler, &mut ctx)`), for per-request state such as connection
+//!   info or a tracing span.
+//! - `partial_handler` - additionally generate a `WireMsgPartialHandler` trait whose
+//!   `handle_protocol(&mut self, msg: Protocol)` etc. default to the required
+//!   `handle_unhandled(&mut self, msg: WireMsg)`, and `msg.dispatch_partial(&mut handler)`.
+//!   Implementors override only the groups they care about but still decide what happens
+//!   to the rest.
+//! - `variant_handler` - additionally generate a stricter `WireMsgVariantHandler` trait with
+//!   exactly one required method per variant (`fn handle_a(&mut self, msg: MsgA) ->
+//!   Self::Output`, no defaults) and `msg.dispatch_variant(&mut handler)`, so adding a
+//!   variant breaks every implementor at compile time.
+//! - `method = "name"` - name of the generated inherent conversion method, for wire enums
+//!   that already have an `into_group` of their own. The `EnumGroup` trait method keeps
+//!   its name.
+//! - `dispatch_name = "Name"` - name of the dispatch enum, `WireMsgGroup` by default, for
+//!   crates that already have a type of that name
+//!   (`dispatch_name = "WireMsgByCategory"`). It must differ from the other generated
+//!   enums.
+//! - `bare_payloads` - a bare variant `MsgA` declares `MsgA(MsgA)`, for messages named
+//!   like their payload type, instead of a unit variant. Variants without a payload are
+//!   then written `Ping {}`, or `Ping()` for a generated payload struct. Shared groups
+//!   keep the usual meaning, as they are declared apart from the wire enums.
+//! - `skip_inherent` / `skip_trait` - generate only the `EnumGroup` impl, or only the
+//!   inherent method, for a minimal expansion. At most one of them can be given, and
+//!   `match_enum_group!` requires the trait impl.
+//! - `serialize_only` / `deserialize_only` - drop the `Deserialize` (respectively
+//!   `Serialize`) derive from the propagated attributes, e.g. for outbound-only enums that
+//!   must not be deserializable. Optionally restricted to some of the enums:
+//!   `serialize_only(wire)` or `serialize_only(groups)`.
+//! - `examples` - generate `WireMsg::examples() -> Vec<WireMsg>`, one instance per variant
+//!   built from the payload's `Default`, so conformance tests exercise every message type
+//!   (including newly added ones). Every payload type must implement `Default`; each one
+//!   that does not is reported as a separate error at its type. Put the function behind a
+//!   feature of your own crate with `examples(cfg(feature = "conformance"))`, or behind
+//!   any other predicate (`examples(cfg(test))`).
+//! - `sample_json` - generate `WireMsg::sample_json(kind) -> Option<String>`, the JSON
+//!   form of a `Default`-built message of that kind, for snapshotting the wire format of
+//!   every variant. Requires the `json` feature of `enum-group-macros`, `Serialize` on the
+//!   wire enum and `Default` payloads; accepts a `cfg(...)` predicate like `examples`.
+//! - `deserialize_by_tag` - generate `WireMsg::deserialize_by_tag(tag, payload) ->
+//!   Result<WireMsg, serde_json::Error>`, deserializing a message from its serde tag and a
+//!   `serde_json::Value` of its payload given separately, through a static table of the
+//!   payloads' `Deserialize` impls. Payload field attributes are not applied. Requires the
+//!   `json` feature of `enum-group-macros`; not available for `#[serde(untagged)]` enums.
+//!   Accepts a `cfg(...)` predicate like `examples`.
+//! - `decode_by_tag(with = bincode::deserialize, error = bincode::Error)` - generate
+//!   `impl TryFrom<(&str, &[u8])> for WireMsg`, decoding the payload bytes of the message
+//!   with the given serde tag with `with`, for transports carrying the tag in a header.
+//!   Fails with `TagDecodeError<error>`. A bare `decode_by_tag` decodes JSON with
+//!   `serde_json::from_slice` and requires the `json` feature of `enum-group-macros`. Not
+//!   available for `#[serde(untagged)]` enums; accepts a `cfg(...)` predicate like
+//!   `examples`.
+//! - `peek_kind` - generate `WireMsg::peek_kind(bytes, format) -> Result<WireMsgKind,
+//!   PeekError>`, reading only the tag of an encoded message: for `WireFormat::Json` the
+//!   externally tagged key or the `#[serde(tag = "...")]` field, skipping every other value
+//!   without building it; for `WireFormat::Bincode` / `WireFormat::BincodeVarint` the
+//!   leading variant index (internally tagged enums have none and get
+//!   `PeekError::UnsupportedFormat`). Requires the `json` feature of `enum-group-macros`;
+//!   not available for `#[serde(untagged)]` enums. Accepts a `cfg(...)` predicate like
+//!   `examples`.
+//! - `avro` - generate `WireMsg::avro_schema() -> apache_avro::Schema`, a union with one
+//!   record per variant named `WireMsg.Group.Variant` whose single `payload` field holds
+//!   the payload's schema, plus `msg.to_avro()` and `WireMsg::from_avro(bytes)` encoding
+//!   a single datum against it. Requires the `avro` feature of `enum-group-macros` and
+//!   payloads implementing `AvroSchema` (e.g. `#[derive(AvroSchema)]`), `Serialize` and
+//!   `Deserialize`; accepts a `cfg(...)` predicate like `examples`.
+//! - `pyo3` - expose `WireMsgKind` and `WireMsgGroupKind` to Python as `#[pyclass]`
+//!   enums (register them with `module.add_class::<WireMsgKind>()`) and generate
+//!   `msg.to_py(py)` / `WireMsg::from_py(obj)`, which convert through the serde layout of
+//!   the wire enum. Requires the `pyo3` feature of `enum-group-macros`, `Serialize` and
+//!   `Deserialize` on the wire enum; accepts a `cfg(...)` predicate like `examples`.
+//! - `sink_router` - generate `WireMsgSinkRouter<ProtocolSink, BusinessSink, ...>`, holding
+//!   one `Sink` per group enum (`WireMsgSinkRouter::new(protocol, business)`, or through its
+//!   public fields) and implementing `Sink<WireMsg>` by forwarding each message to the sink
+//!   of its group. It is ready, flushed or closed once all inner sinks are; they must share
+//!   one error type and be `Unpin`. Requires the `futures` feature of `enum-group-macros`;
+//!   accepts a `cfg(...)` predicate like `examples`.
+//!
+//! ## Variant options
+//!
+//! Variants accept `#[variant(...)]` attributes, also consumed by the macro. Their values
+//! end up in `WireMsg::METADATA` (one `VariantMetadata` per variant, declaration order),
+//! reachable for a given message through `msg.metadata()`.
+//!
+//! - `timeout_ms = N` / `retries = N` - per-message RPC settings, exposed through
+//!   `msg.timeout() -> Option<Duration>` and `msg.max_retries() -> u32` (generated when at
+//!   least one variant sets either option).
+//! - `legacy_of = NewVariant` - mark a variant as superseded by `NewVariant`. It still
+//!   deserializes, but `into_group` and `msg.normalize()` (generated when any variant is
+//!   marked) convert it to `NewVariant` through a `From` impl between the two payloads,
+//!   which you provide.
+//! - `tag = N` - a fixed numeric message id for binary protocols, given to either every
+//!   variant or none and unique among them. Generates `msg.tag() -> u16` and
+//!   `WireMsg::from_tag(id) -> Option<WireMsgKind>`, both `const`, with no table to keep in
+//!   sync by hand.
+//! - `boxed` - store the payload of a single-payload variant as `Box<Payload>` on the wire
+//!   and group enums, so that one large payload does not inflate every message. Matching
+//!   sees the box, while `VariantOf`, the variant handler, `examples`, `deserialize_by_tag`
+//!   and `payload_any` take and return the payload itself. A boxed payload may also hold
+//!   the generated enums.
+//!
+//! ## Group options
+//!
+//! Groups accept `#[group(...)]` attributes:
+//!
+//! - `requires_auth` / `role = "name"` - authorization requirements of every message in
+//!   the group, exposed through `msg.requires_auth() -> bool` and
+//!   `msg.required_role() -> Option<&'static str>`. A role implies `requires_auth`.
+//! - `copy` - derive `Copy` on this group enum, for groups whose payloads are all `Copy`
+//!   (deriving `Copy` on the wire enum makes every group enum `Copy` instead). The dispatch
+//!   enum derives `Copy` once every group enum does.
+//! - `skip_dispatch` - leave the group out of `WireMsgGroup`: its enum and its wire
+//!   variants are generated as usual, but `into_group` wraps its messages whole in a
+//!   `WireMsgGroup::Undispatched(WireMsg)` variant, usually matched by the `_` arm of
+//!   `match_enum_group!`. The generated dispatchers have no entry for the group: routers
+//!   return such messages as unhandled, `WireMsgHandlers` passes them to its fallback,
+//!   `WireMsgQueues` buffers them in one shared queue, the sink router drops them and the
+//!   handler traits get one more `handle_undispatched(msg: WireMsg)` method.
+//! - `strip_prefix = "Broker"` - drop the prefix from the variants starting with it on the
+//!   group enums, nested ones included, so `BrokerReport` is `Broker::Report`, as if
+//!   declared `BrokerReport(...) as Report`. The wire enum, the kinds and serde keep the
+//!   full name; variants renamed with `as` keep their own.
+//!
+//! Doc comments on a group also document its variant of the dispatch enum and of
+//! `WireMsgGroupKind`, and those on a variant its `WireMsgKind` variant, next to the
+//! variants of the wire and group enums; nested groups accept doc comments as well. A
+//! definition documented throughout passes `missing_docs`.
+//!
+//! Other attributes on a group, doc comments included, go to its enum alone, after the
+//! outer ones. A `#[derive(...)]` there replaces the outer derives for that group, e.g.
+//! `#[derive(Debug, Clone, PartialEq, Eq, Hash)] Protocol { ... }` to key a set by
+//! `Protocol` messages only, and must keep `Debug` and `Clone`, which the dispatch enum
+//! derives. Nested groups take the attributes of their group, besides their own doc
+//! comments.
+//!
+//! `#[non_exhaustive]` on the definition marks the wire, group and dispatch enums alike,
+//! so that other crates, which then need a `_` arm (also in `match_enum_group!`), keep
+//! compiling as variants and groups are added. The generated code matches within the
+//! defining crate and is not affected.
+//!
+//! A `#[deprecated]` variant stays deprecated on the wire and group enums, while the
+//! generated impls that match and build it allow the lint, so that only user code naming
+//! the variant warns.
+//!
+//! A `#[cfg(...)]` on a variant gates everything generated for it, from its kinds, match
+//! arms, table entries and share of `WIRE_FINGERPRINT` to its handler methods, so a variant
+//! compiled out leaves no trace and the tables indexed by kind stay aligned. The one
+//! exception is `GROUP_INDEX_TABLE`, indexed by discriminant, which keeps the slot of the
+//! variant so that the discriminants of the following ones still match.
+//!
+//! ## Unexpected messages
+//!
+//! The dispatchers that can let a message bypass the handler of its group expose an
+//! `on_unexpected` hook, seeing the message and an `UnexpectedReason` (`SkippedGroup` or
+//! `NoHandler`) right before it is handed back, passed to a fallback or dropped:
+//!
+//! - the routers and `WireMsgHandlers` take a closure with `.on_unexpected(|msg, reason|
+//!   ...)`;
+//! - the sink router takes a `fn(&WireMsg, UnexpectedReason)` with `.on_unexpected(...)`,
+//!   generated when some group is left out of dispatch;
+//! - `WireMsgPartialHandler` has an overridable `on_unexpected(&mut self, msg, reason)`
+//!   method called before `handle_unhandled`.
+//!
+//! Messages with an unknown tag never get this far: they fail to decode instead.
+//!
+//! ## Shared groups
+//!
+//! One invocation may define several wire enums. Groups used by more than one of them are
+//! declared once with `group Name { ... }`, carrying their own attributes and group
+//! options, and are included in each wire enum with `use Name` (or `use Name = N` to give
+//! the wire variant a discriminant):
+//!
+//! ```ignore
+//! define_enum_group! {
+//!     #[derive(Debug, Clone)]
+//!     pub group Heartbeat { Ping(Ping), Pong(Pong) }
+//!
+//!     pub enum ClientMsg { use Heartbeat, Request { Query(Query) } }
+//!     pub enum ServerMsg { use Heartbeat, Response { Answer(Answer) } }
+//! }
+//! ```
+//!
+//! The group enum is generated once and every wire enum converts to and from it. Items
+//! inherent to the group enum (`TAG_TABLE`, group tags and group modules) are not generated
+//! for shared groups, since they would differ between wire enums.
+//!
+//! ## Metadata
+//!
+//! Both groups and variants accept any number of free-form
+//! `#[meta(key = "queue", value = "bulk")]` attributes. They are looked up with
+//! `msg.meta("queue") -> Option<&'static str>`; a variant's entry overrides its group's
+//! entry with the same key.
 
 // Re-export the procedural macros
-pub use enum_group_macros_impl::{define_enum_group, match_enum_group};
+pub use enum_group_macros_impl::{
+  define_enum_group, define_enum_group_pair, match_enum_group, match_enum_group_pair,
+  migrate_enum_group, EnumGroup,
+};
+
+/// Support code referenced by the generated items. Not public API.
+#[doc(hidden)]
+pub mod __private {
+  #[cfg(feature = "avro")]
+  pub use apache_avro;
+  #[cfg(feature = "futures")]
+  pub use futures_sink;
+  #[cfg(feature = "pyo3")]
+  pub use pyo3;
+  #[cfg(feature = "json")]
+  pub use serde;
+  #[cfg(feature = "json")]
+  pub use serde_json;
+
+  /// Schema construction and datum encoding for the union of all variants of a wire enum.
+  ///
+  /// Every variant becomes a record named `Wire.Group.Variant` with a single `payload`
+  /// field, so that variants sharing a payload type stay distinguishable.
+  #[cfg(feature = "avro")]
+  pub mod avro {
+    use std::collections::HashSet;
+
+    use apache_avro::reader::datum::GenericDatumReader;
+    use apache_avro::schema::{Name, RecordField, RecordSchema, UnionSchema};
+    use apache_avro::types::Value;
+    use apache_avro::writer::datum::GenericDatumWriter;
+    use apache_avro::{AvroSchemaComponent, Error, Schema};
+
+    #[derive(Default)]
+    pub struct UnionBuilder {
+      named: HashSet<Name>,
+      variants: Vec<Schema>,
+    }
+
+    impl UnionBuilder {
+      pub fn variant<T: AvroSchemaComponent>(mut self, name: &str) -> Self {
+        let payload = T::get_schema_in_ctxt(&mut self.named, None);
+        let field = RecordField::builder().name("payload").schema(payload).build();
+        let name = Name::new(name).expect("variant names are valid Avro names");
+        self
+          .variants
+          .push(Schema::Record(RecordSchema::builder().name(name).fields(vec![field]).build()));
+        self
+      }
+
+      pub fn build(self) -> Schema {
+        Schema::Union(UnionSchema::new(self.variants).expect("variant records have distinct names"))
+      }
+    }
+
+    pub fn encode<T: serde::Serialize>(
+      schema: &Schema,
+      index: u32,
+      payload: &T,
+    ) -> Result<Vec<u8>, Error> {
+      let record = Value::Record(vec![("payload".into(), apache_avro::to_value(payload)?)]);
+      GenericDatumWriter::builder(schema)
+        .build()?
+        .write_value_to_vec(Value::Union(index, Box::new(record)))
+    }
+
+    pub fn decode(schema: &Schema, mut bytes: &[u8]) -> Result<(u32, Value), Error> {
+      match GenericDatumReader::builder(schema).build()?.read_value(&mut bytes)? {
+        Value::Union(index, record) => match *record {
+          Value::Record(mut fields) if fields.len() == 1 => Ok((index, fields.remove(0).1)),
+          _ => Err(serde::de::Error::custom("expected a variant record")),
+        },
+        _ => Err(serde::de::Error::custom("expected a union")),
+      }
+    }
+  }
+
+  /// Type-erased payloads (`#[enum_group(payload_any)]`).
+  pub mod any {
+    use std::any::Any;
+
+    /// `payload` as a `T` if it is one, or back unchanged.
+    pub fn downcast<P: 'static, T: 'static>(payload: P) -> Result<T, P> {
+      let mut slot = Some(payload);
+      if let Some(found) = (&mut slot as &mut dyn Any).downcast_mut::<Option<T>>() {
+        return Ok(found.take().expect("slot is filled"));
+      }
+      Err(slot.expect("slot is filled"))
+    }
+  }
+
+  /// Checking the compiled tags against a frozen list (`assert_tags`) and hashing them
+  /// (`WIRE_FINGERPRINT`).
+  pub mod tags {
+    use std::fmt::Write;
+
+    /// FNV-1a over the concatenation of `parts`, evaluated where the parts are gated by
+    /// the `#[cfg]` of their variant.
+    pub const fn fingerprint(parts: &[&[u8]]) -> u64 {
+      let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+      let mut i = 0;
+      while i < parts.len() {
+        let mut j = 0;
+        while j < parts[i].len() {
+          hash ^= parts[i][j] as u64;
+          hash = hash.wrapping_mul(0x0100_0000_01b3);
+          j += 1;
+        }
+        i += 1;
+      }
+      hash
+    }
+
+    /// Panics with a line diff unless `actual`, the `(group, tag)` pairs of `wire`, equals
+    /// `expected`.
+    pub fn assert_tags(wire: &str, actual: &[(&str, &str)], expected: &[(&str, &str)]) {
+      if actual == expected {
+        return;
+      }
+      // Longest common subsequence, so that only the changed entries show up in the diff
+      let (n, m) = (expected.len(), actual.len());
+      let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+      for i in (0..n).rev() {
+        for j in (0..m).rev() {
+          lcs[i][j] = if expected[i] == actual[j] {
+            lcs[i + 1][j + 1] + 1
+          } else {
+            lcs[i + 1][j].max(lcs[i][j + 1])
+          };
+        }
+      }
+      let mut diff = String::new();
+      let (mut i, mut j) = (0, 0);
+      while i < n || j < m {
+        let line = if i < n && j < m && expected[i] == actual[j] {
+          i += 1;
+          j += 1;
+          ("  ", actual[j - 1])
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+          j += 1;
+          ("+ ", actual[j - 1])
+        } else {
+          i += 1;
+          ("- ", expected[i - 1])
+        };
+        let (marker, (group, tag)) = line;
+        let _ = writeln!(diff, "{marker}{group} {tag}");
+      }
+      panic!("tags of `{wire}` differ from the expected list (- expected, + actual):\n{diff}");
+    }
+  }
+
+  /// Parsing of the subscription filters generated with `#[enum_group(filter)]`.
+  pub mod filter {
+    use crate::FilterError;
+
+    /// Evaluates `source` against `names`, the `(group, variant)` names of every kind in
+    /// declaration order, returning whether each kind is selected.
+    pub fn parse(source: &str, names: &[(&str, &str)]) -> Result<Vec<bool>, FilterError> {
+      let (mut include, mut exclude) = (None::<Vec<bool>>, vec![false; names.len()]);
+      for rule in source.split(|c: char| c == ',' || c.is_whitespace()).filter(|r| !r.is_empty()) {
+        let (negated, pattern) = match rule.strip_prefix('!') {
+          Some(pattern) => (true, pattern),
+          None => (false, rule),
+        };
+        let selected = select(pattern, names)?;
+        let target = if negated {
+          &mut exclude
+        } else {
+          include.get_or_insert_with(|| vec![false; names.len()])
+        };
+        target.iter_mut().zip(selected).for_each(|(t, s)| *t |= s);
+      }
+      let include = include.unwrap_or_else(|| vec![true; names.len()]);
+      Ok(include.into_iter().zip(exclude).map(|(i, e)| i && !e).collect())
+    }
+
+    /// The kinds matched by one `*`, `Group`, `Group.*` or `Group.Variant` pattern.
+    fn select(pattern: &str, names: &[(&str, &str)]) -> Result<Vec<bool>, FilterError> {
+      if pattern == "*" {
+        return Ok(vec![true; names.len()]);
+      }
+      let (group, variant) = match pattern.split_once('.') {
+        Some((group, variant)) => (group, variant),
+        None => (pattern, "*"),
+      };
+      if group.is_empty() || variant.is_empty() {
+        return Err(FilterError::Syntax(pattern.to_string()));
+      }
+      if !names.iter().any(|(g, _)| *g == group) {
+        return Err(FilterError::UnknownGroup(group.to_string()));
+      }
+      let selected: Vec<bool> =
+        names.iter().map(|(g, v)| *g == group && (variant == "*" || *v == variant)).collect();
+      if !selected.contains(&true) {
+        return Err(FilterError::UnknownVariant(pattern.to_string()));
+      }
+      Ok(selected)
+    }
+  }
+
+  /// Deserializing a message from its tag and payload given separately.
+  #[cfg(feature = "json")]
+  pub mod by_tag {
+    /// The error of a tag naming none of the `expected` variants.
+    pub fn unknown_tag(tag: &str, expected: &'static [&'static str]) -> serde_json::Error {
+      serde::de::Error::unknown_variant(tag, expected)
+    }
+
+    /// The message of `tag` and `payload` laid out as serde serializes the wire enum:
+    /// externally tagged without `tag_field`, adjacently tagged with a `content_field` and
+    /// internally tagged otherwise, the tag then joining the fields of the payload.
+    pub fn message(
+      tag: &str,
+      payload: &serde_json::Value,
+      tag_field: Option<&str>,
+      content_field: Option<&str>,
+    ) -> serde_json::Value {
+      use serde_json::{Map, Value};
+
+      let mut message = Map::new();
+      match (tag_field, content_field) {
+        (None, _) => {
+          message.insert(tag.to_string(), payload.clone());
+        }
+        (Some(tag_field), Some(content_field)) => {
+          message.insert(tag_field.to_string(), Value::from(tag));
+          message.insert(content_field.to_string(), payload.clone());
+        }
+        (Some(tag_field), None) => {
+          if let Value::Object(fields) = payload {
+            message.clone_from(fields);
+          }
+          message.insert(tag_field.to_string(), Value::from(tag));
+        }
+      }
+      Value::Object(message)
+    }
+  }
+
+  /// Reading the variant tag or index of an encoded message without decoding it.
+  #[cfg(feature = "json")]
+  pub mod peek {
+    use std::fmt;
+
+    use serde::de::{self, DeserializeSeed, IgnoredAny, MapAccess, Visitor};
+
+    use crate::{PeekError, WireFormat};
+
+    /// The serde tag of a JSON-encoded message, read from the first key of an externally
+    /// tagged object or from the `tag_field` of an internally or adjacently tagged one.
+    /// Every other value is skipped without being built.
+    pub fn json_tag(bytes: &[u8], tag_field: Option<&str>) -> Result<String, PeekError> {
+      let mut de = serde_json::Deserializer::from_slice(bytes);
+      let tag = de::Deserializer::deserialize_any(&mut de, TagSeed(tag_field))
+        .map_err(|e| PeekError::Malformed(e.to_string()))?;
+      tag.ok_or(PeekError::MissingTag)
+    }
+
+    /// The variant index at the start of a bincode-encoded message.
+    pub fn variant_index(bytes: &[u8], format: WireFormat) -> Result<usize, PeekError> {
+      let index = match format {
+        WireFormat::Bincode => {
+          let head = bytes.get(..4).ok_or(PeekError::Truncated)?;
+          u32::from_le_bytes(head.try_into().expect("four bytes"))
+        }
+        WireFormat::BincodeVarint => match *bytes.first().ok_or(PeekError::Truncated)? {
+          small @ 0..=250 => u32::from(small),
+          251 => {
+            let head = bytes.get(1..3).ok_or(PeekError::Truncated)?;
+            u32::from(u16::from_le_bytes(head.try_into().expect("two bytes")))
+          }
+          252 => {
+            let head = bytes.get(1..5).ok_or(PeekError::Truncated)?;
+            u32::from_le_bytes(head.try_into().expect("four bytes"))
+          }
+          _ => return Err(PeekError::Malformed("variant index wider than u32".into())),
+        },
+        // JSON messages carry tags, not indices
+        WireFormat::Json => return Err(PeekError::UnsupportedFormat(format)),
+      };
+      Ok(index as usize)
+    }
+
+    struct TagSeed<'a>(Option<&'a str>);
+
+    impl<'de> DeserializeSeed<'de> for TagSeed<'_> {
+      type Value = Option<String>;
+
+      fn deserialize<D: de::Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        de.deserialize_any(self)
+      }
+    }
+
+    impl<'de> Visitor<'de> for TagSeed<'_> {
+      type Value = Option<String>;
+
+      fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a tagged enum")
+      }
+
+      // Externally tagged unit variants are plain strings
+      fn visit_str<E: de::Error>(self, tag: &str) -> Result<Self::Value, E> {
+        Ok(self.0.is_none().then(|| tag.to_string()))
+      }
+
+      fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut tag = None;
+        while let Some(key) = map.next_key::<String>()? {
+          match self.0 {
+            None if tag.is_none() => {
+              map.next_value::<IgnoredAny>()?;
+              tag = Some(key);
+            }
+            Some(field) if field == key && tag.is_none() => tag = Some(map.next_value()?),
+            _ => {
+              map.next_value::<IgnoredAny>()?;
+            }
+          }
+        }
+        Ok(tag)
+      }
+    }
+  }
+
+  /// Conversions between serde types and Python objects, via Python's `json` module.
+  #[cfg(feature = "pyo3")]
+  pub mod py {
+    use pyo3::exceptions::PyValueError;
+    use pyo3::prelude::*;
+
+    pub fn to_py<'py, T: serde::Serialize>(
+      py: Python<'py>,
+      value: &T,
+    ) -> PyResult<Bound<'py, PyAny>> {
+      let json = serde_json::to_string(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+      py.import("json")?.call_method1("loads", (json,))
+    }
+
+    pub fn from_py<T: serde::de::DeserializeOwned>(obj: &Bound<'_, PyAny>) -> PyResult<T> {
+      let json: String = obj.py().import("json")?.call_method1("dumps", (obj,))?.extract()?;
+      serde_json::from_str(&json).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+  }
+}
 
 /// Trait for enums with grouped variants.
 ///
 /// This trait is automatically implemented by `define_enum_group!` and provides
-/// a way to convert a flat wire enum into its grouped representation.
+/// a way to convert a flat wire enum into its grouped representation and back.
 ///
 /// You typically don't need to interact with this trait directly - it's used
 /// internally by `match_enum_group!` to access the `Group` type.
@@ -109,4 +895,389 @@ pub trait EnumGroup {
   /// This method matches on each variant and wraps it in the appropriate
   /// group enum, then wraps that in the `Group` enum.
   fn into_group(self) -> Self::Group;
+
+  /// Convert a grouped representation back into this enum.
+  ///
+  /// This is the inverse of [`into_group`](EnumGroup::into_group), letting generic code
+  /// regroup, transform and re-flatten messages.
+  fn from_group(group: Self::Group) -> Self;
+}
+
+/// Values `match_enum_group!(box value, ...)` takes the wire enum `W` out of.
+///
+/// A `Box<W>` is unboxed. A shared `Arc<W>` or `Rc<W>` cannot be moved out of implicitly;
+/// wrap it in [`UnwrapOrClone`] to take the message if the pointer is unique and clone it
+/// otherwise, or pass `Arc::try_unwrap(arc)` without `box` and handle the still shared
+/// pointer in an `Err(arc)` arm.
+pub trait Scrutinee<W> {
+  /// Takes the wire message out of this value.
+  fn into_wire(self) -> W;
+}
+
+impl<W> Scrutinee<W> for Box<W> {
+  fn into_wire(self) -> W {
+    *self
+  }
 }
+
+/// Strategy for matching a shared message with `match_enum_group!`: the message is moved
+/// out of the pointer if it is the only one, and cloned otherwise.
+///
+/// # Example
+///
+/// ```ignore
+/// match_enum_group!(box UnwrapOrClone(arc), WireMsg, {
+///     Protocol(p) => handle_protocol(p),
+///     Business(b) => handle_business(b),
+/// })
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnwrapOrClone<P>(pub P);
+
+impl<W: Clone> Scrutinee<W> for UnwrapOrClone<std::sync::Arc<W>> {
+  fn into_wire(self) -> W {
+    std::sync::Arc::unwrap_or_clone(self.0)
+  }
+}
+
+impl<W: Clone> Scrutinee<W> for UnwrapOrClone<std::rc::Rc<W>> {
+  fn into_wire(self) -> W {
+    std::rc::Rc::unwrap_or_clone(self.0)
+  }
+}
+
+/// Trait for enums whose variants and groups have fieldless kind identifiers.
+///
+/// This trait is automatically implemented by `define_enum_group!`, with `Kind` and
+/// `GroupKind` set to the generated `{Wire}Kind` and `{Wire}GroupKind` enums. It lets
+/// generic infrastructure (metrics, routing tables, ...) classify messages through a trait
+/// bound instead of per-enum inherent methods.
+///
+/// # Example
+///
+/// ```ignore
+/// use enum_group_macros::EnumGroupKind;
+///
+/// fn count<M: EnumGroupKind>(msgs: &[M]) -> HashMap<M::GroupKind, usize>
+/// where
+///     M::GroupKind: Eq + Hash,
+/// {
+///     let mut counts = HashMap::new();
+///     for msg in msgs {
+///         *counts.entry(msg.group_kind()).or_default() += 1;
+///     }
+///     counts
+/// }
+/// ```
+pub trait EnumGroupKind {
+  /// Fieldless identifier of a variant, e.g. `WireMsgKind`.
+  type Kind;
+
+  /// Fieldless identifier of a group, e.g. `WireMsgGroupKind`.
+  type GroupKind;
+
+  /// Returns the kind of this message's variant.
+  fn kind(&self) -> Self::Kind;
+
+  /// Returns the kind of the group this message belongs to.
+  fn group_kind(&self) -> Self::GroupKind;
+}
+
+/// Trait implemented by every group enum generated by `define_enum_group!`, linking it to
+/// its wire enum `W`.
+///
+/// This lets generic pipeline stages accept "some group of `W`" as a type parameter and
+/// move messages between the flat and the categorical representation.
+///
+/// # Example
+///
+/// ```ignore
+/// use enum_group_macros::GroupOf;
+///
+/// fn only<G: GroupOf<WireMsg>>(msgs: Vec<WireMsg>) -> (Vec<G>, Vec<WireMsg>) {
+///     let (mut matched, mut rest) = (Vec::new(), Vec::new());
+///     for msg in msgs {
+///         match G::try_from_wire(msg) {
+///             Ok(g) => matched.push(g),
+///             Err(msg) => rest.push(msg),
+///         }
+///     }
+///     (matched, rest)
+/// }
+/// ```
+pub trait GroupOf<W>: Sized {
+  /// Converts a wire message into this group, handing it back unchanged if it belongs to
+  /// another group.
+  fn try_from_wire(wire: W) -> Result<Self, W>;
+
+  /// Converts this group enum back into the flat wire enum.
+  fn into_wire(self) -> W;
+}
+
+/// Trait implemented by payload types of an enum generated by `define_enum_group!`,
+/// linking them to the wire enum `W`.
+///
+/// The impl is only generated for payload types that back exactly one variant of `W`;
+/// a type used by several variants cannot know which of them to convert into.
+///
+/// # Example
+///
+/// ```ignore
+/// use enum_group_macros::VariantOf;
+///
+/// fn send<P: VariantOf<WireMsg>>(tx: &Sender<WireMsg>, payload: P) {
+///     tx.send(payload.into_wire()).unwrap();
+/// }
+/// ```
+pub trait VariantOf<W>: Sized {
+  /// Extracts this payload from a wire message, handing the message back unchanged if it
+  /// holds a different variant.
+  fn try_from_wire(wire: W) -> Result<Self, W>;
+
+  /// Wraps this payload in its variant of the wire enum.
+  fn into_wire(self) -> W;
+}
+
+/// Trait implemented by the same payload types as [`VariantOf`], naming the group enum of
+/// their variant at the type level.
+///
+/// A bound such as `P: PayloadOf<WireMsg, Group = Protocol>` makes a function accept the
+/// payloads of one group only, checked by the compiler. Payloads of nested groups name
+/// their top-level group.
+///
+/// # Example
+///
+/// ```ignore
+/// use enum_group_macros::PayloadOf;
+///
+/// fn send_protocol<P: PayloadOf<WireMsg, Group = Protocol>>(tx: &Sender<Protocol>, payload: P) {
+///     tx.send(payload.into_group()).unwrap();
+/// }
+/// ```
+pub trait PayloadOf<W>: VariantOf<W> {
+  /// The group enum holding the variant of this payload, e.g. `Protocol`.
+  type Group: GroupOf<W>;
+
+  /// Wraps this payload in its variant of the group enum.
+  fn into_group(self) -> Self::Group;
+}
+
+/// Trait implemented by the zero-sized group tags `define_enum_group!` generates with
+/// `#[enum_group(group_tags)]` (`ProtocolTag` for the group `Protocol`), linking each tag
+/// to its group enum and wire enum.
+///
+/// Tags let generic code name a group purely at the type level, e.g. to key a registry by
+/// `TypeId` instead of by string.
+///
+/// # Example
+///
+/// ```ignore
+/// use enum_group_macros::{GroupOf, GroupTag};
+///
+/// impl Registry {
+///     fn handler<T: GroupTag<Wire = WireMsg>>(&self) -> Option<&dyn Fn(T::Group)> {
+///         self.handlers.get(&TypeId::of::<T>()).map(|h| h.downcast_ref().unwrap())
+///     }
+/// }
+/// ```
+pub trait GroupTag: 'static {
+  /// The wire enum the group belongs to.
+  type Wire;
+
+  /// The group enum this tag stands for.
+  type Group: GroupOf<Self::Wire>;
+}
+
+/// Static description of one variant of an enum defined with `define_enum_group!`.
+///
+/// The macro emits one entry per variant, in declaration order, as `WireMsg::METADATA`.
+/// Use `WireMsg::metadata(&self)` to look up the entry of a particular message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VariantMetadata {
+  /// Name of the variant in the wire enum.
+  pub name: &'static str,
+  /// Name of the group the variant belongs to.
+  pub group: &'static str,
+  /// Tag the variant is serialized under.
+  pub tag: &'static str,
+  /// Value of `#[variant(timeout_ms = ...)]`, if any.
+  pub timeout_ms: Option<u64>,
+  /// Value of `#[variant(retries = ...)]`, or `0`.
+  pub max_retries: u32,
+  /// Whether the group has `#[group(requires_auth)]` or `#[group(role = ...)]`.
+  pub requires_auth: bool,
+  /// Value of the group's `#[group(role = ...)]`, if any.
+  pub required_role: Option<&'static str>,
+  /// `#[meta(key = ..., value = ...)]` pairs of the variant, followed by those of its
+  /// group that the variant does not override.
+  pub meta: &'static [(&'static str, &'static str)],
+}
+
+impl VariantMetadata {
+  /// Looks up a `#[meta(...)]` value by key.
+  pub fn meta(&self, key: &str) -> Option<&'static str> {
+    self.meta.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+  }
+}
+
+/// Error type handlers of the routers generated with `#[enum_group(router)]` return by
+/// default.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Boxed future returned by the handlers of the async routers generated with
+/// `#[enum_group(router)]`.
+pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+/// Error returned by the `route` method of the routers generated with
+/// `#[enum_group(router)]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteError<W, E> {
+  /// No handler is registered for the group of the message, which is handed back.
+  Unhandled(W),
+  /// The handler of the message's group failed.
+  Handler(E),
+}
+
+impl<W, E: std::fmt::Display> std::fmt::Display for RouteError<W, E> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      RouteError::Unhandled(_) => f.write_str("no handler registered for the message's group"),
+      RouteError::Handler(e) => write!(f, "handler failed: {e}"),
+    }
+  }
+}
+
+impl<W: std::fmt::Debug, E: std::error::Error + 'static> std::error::Error for RouteError<W, E> {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      RouteError::Unhandled(_) => None,
+      RouteError::Handler(e) => Some(e),
+    }
+  }
+}
+
+/// Error of the `TryFrom<(&str, &[u8])>` impl generated with
+/// `#[enum_group(decode_by_tag)]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagDecodeError<E> {
+  /// No variant is serialized under this tag.
+  UnknownTag(String),
+  /// The payload bytes did not decode as the variant's payload type.
+  Payload(E),
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for TagDecodeError<E> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      TagDecodeError::UnknownTag(tag) => write!(f, "unknown message tag `{tag}`"),
+      TagDecodeError::Payload(error) => write!(f, "invalid message payload: {error}"),
+    }
+  }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for TagDecodeError<E> {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      TagDecodeError::UnknownTag(_) => None,
+      TagDecodeError::Payload(error) => Some(error),
+    }
+  }
+}
+
+/// Why a generated dispatcher passes a message to its `on_unexpected` hook rather than to
+/// a handler of its group.
+///
+/// Messages with an unknown tag never reach a dispatcher: they already fail to decode,
+/// e.g. with [`TagDecodeError::UnknownTag`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnexpectedReason {
+  /// The message's group is left out of dispatch with `#[group(skip_dispatch)]`.
+  SkippedGroup,
+  /// No handler is set for the message's group, so it falls through to the fallback.
+  NoHandler,
+}
+
+impl std::fmt::Display for UnexpectedReason {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      UnexpectedReason::SkippedGroup => f.write_str("the message's group is not dispatched"),
+      UnexpectedReason::NoHandler => f.write_str("no handler is set for the message's group"),
+    }
+  }
+}
+
+/// Encoding of a message passed to the `peek_kind` function generated with
+/// `#[enum_group(peek_kind)]`.
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WireFormat {
+  /// `serde_json`, in any tagged enum representation.
+  Json,
+  /// bincode with fixed-width integers (bincode 1.x, or bincode 2 with a legacy config):
+  /// the variant index is the leading little-endian `u32`.
+  Bincode,
+  /// bincode 2 with its standard varint encoding of the leading variant index.
+  BincodeVarint,
+}
+
+/// Error returned by the `peek_kind` function generated with `#[enum_group(peek_kind)]`.
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeekError {
+  /// The input ends before the tag or variant index.
+  Truncated,
+  /// The input is not a valid encoding.
+  Malformed(String),
+  /// The JSON input has no tag.
+  MissingTag,
+  /// The tag names no variant.
+  UnknownTag(String),
+  /// The variant index is out of range.
+  UnknownIndex(usize),
+  /// Messages of this enum carry no variant index in the given format: internally tagged
+  /// enums only support `WireFormat::Json`, and JSON messages carry tags instead.
+  UnsupportedFormat(WireFormat),
+}
+
+#[cfg(feature = "json")]
+impl std::fmt::Display for PeekError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      PeekError::Truncated => f.write_str("message truncated before its tag"),
+      PeekError::Malformed(e) => write!(f, "malformed message: {e}"),
+      PeekError::MissingTag => f.write_str("message has no tag"),
+      PeekError::UnknownTag(tag) => write!(f, "unknown tag `{tag}`"),
+      PeekError::UnknownIndex(index) => write!(f, "unknown variant index {index}"),
+      PeekError::UnsupportedFormat(format) => {
+        write!(f, "cannot peek the variant index of messages in {format:?}")
+      }
+    }
+  }
+}
+
+#[cfg(feature = "json")]
+impl std::error::Error for PeekError {}
+
+/// Error returned when parsing a subscription filter generated with
+/// `#[enum_group(filter)]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterError {
+  /// A rule is not of the form `*`, `Group`, `Group.*` or `Group.Variant`.
+  Syntax(String),
+  /// A rule names a group that does not exist.
+  UnknownGroup(String),
+  /// A `Group.Variant` rule names a variant its group does not have.
+  UnknownVariant(String),
+}
+
+impl std::fmt::Display for FilterError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      FilterError::Syntax(rule) => write!(f, "invalid filter rule `{rule}`"),
+      FilterError::UnknownGroup(group) => write!(f, "unknown group `{group}`"),
+      FilterError::UnknownVariant(rule) => write!(f, "unknown variant `{rule}`"),
+    }
+  }
+}
+
+impl std::error::Error for FilterError {}
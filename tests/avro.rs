@@ -0,0 +1,129 @@
+//! Tests for the helpers enabled by the `avro` feature.
+//!
+//! Run with `cargo test --features avro`.
+
+#![cfg(feature = "avro")]
+#![allow(dead_code)] // Generated enum variants are intentionally not fully used in tests
+
+use apache_avro::schema::UnionSchema;
+use apache_avro::{AvroSchema, Schema};
+use enum_group_macros::define_enum_group;
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// Test Helper Types
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, AvroSchema)]
+pub struct Login {
+  pub user: String,
+  pub attempts: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, AvroSchema)]
+pub struct Logout {
+  pub reason: Option<String>,
+}
+
+define_enum_group! {
+  #[enum_group(avro)]
+  #[derive(Debug, Clone, PartialEq)]
+  pub enum SessionMsg {
+    Auth {
+      SignIn(Login),
+      SignOut(Logout),
+    },
+    Stats {
+      Count(i64),
+      Total(i64),
+    }
+  }
+}
+
+fn variant_names(union: &UnionSchema) -> Vec<String> {
+  union
+    .variants()
+    .iter()
+    .map(|s| match s {
+      Schema::Record(r) => r.name.fullname(None),
+      other => panic!("expected a record, got {other:?}"),
+    })
+    .collect()
+}
+
+// =============================================================================
+// Section A: Schema
+// =============================================================================
+
+/// Test: the schema is a union of one record per variant, namespaced by group.
+#[test]
+fn test_avro_schema() {
+  let Schema::Union(union) = SessionMsg::avro_schema() else {
+    panic!("expected a union schema");
+  };
+  assert_eq!(
+    variant_names(&union),
+    [
+      "SessionMsg.Auth.SignIn",
+      "SessionMsg.Auth.SignOut",
+      "SessionMsg.Stats.Count",
+      "SessionMsg.Stats.Total"
+    ]
+  );
+}
+
+// =============================================================================
+// Section B: Encoding
+// =============================================================================
+
+/// Test: messages survive a `to_avro` / `from_avro` roundtrip.
+///
+/// `Count` and `Total` share a payload type and must still decode to the right variant.
+#[test]
+fn test_avro_roundtrip() {
+  let msgs = [
+    SessionMsg::SignIn(Login { user: "alice".into(), attempts: 2 }),
+    SessionMsg::SignOut(Logout { reason: None }),
+    SessionMsg::Count(7),
+    SessionMsg::Total(7),
+  ];
+  for msg in msgs {
+    let bytes = msg.to_avro().unwrap();
+    assert_eq!(SessionMsg::from_avro(&bytes).unwrap(), msg);
+  }
+}
+
+/// Test: truncated input is reported as an error.
+#[test]
+fn test_avro_truncated() {
+  let bytes = SessionMsg::SignIn(Login { user: "alice".into(), attempts: 2 }).to_avro().unwrap();
+  assert!(SessionMsg::from_avro(&bytes[..bytes.len() - 2]).is_err());
+}
+
+/// Test: variants removed by `#[cfg]` leave no gap in the union.
+#[test]
+fn test_avro_cfg_variants() {
+  define_enum_group! {
+    #[enum_group(avro)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum GatedMsg {
+      Auth {
+        #[cfg(any())]
+        SignIn(Login),
+        SignOut(Logout),
+      },
+      Stats {
+        Count(i64),
+      }
+    }
+  }
+
+  let Schema::Union(union) = GatedMsg::avro_schema() else {
+    panic!("expected a union schema");
+  };
+  assert_eq!(variant_names(&union), ["GatedMsg.Auth.SignOut", "GatedMsg.Stats.Count"]);
+  for msg in [GatedMsg::SignOut(Logout { reason: None }), GatedMsg::Count(3)] {
+    let bytes = msg.to_avro().unwrap();
+    assert_eq!(GatedMsg::from_avro(&bytes).unwrap(), msg);
+  }
+}
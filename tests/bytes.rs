@@ -0,0 +1,114 @@
+//! Tests for binary payloads: `bytes::Bytes` and `serde_bytes`-annotated fields.
+//!
+//! Binary payloads must survive every conversion of the grouping layer without being
+//! copied, and keep their compact serde encoding.
+
+#![allow(dead_code)] // Generated enum variants are intentionally not fully used in tests
+
+use bytes::Bytes;
+use enum_group_macros::{define_enum_group, GroupOf, VariantOf};
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// Test Helper Types
+// =============================================================================
+
+/// Chunk of a larger binary object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chunk {
+  pub offset: u64,
+  pub data: Bytes,
+}
+
+/// Digest serialized as a byte string rather than a sequence of numbers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Digest {
+  #[serde(with = "serde_bytes")]
+  pub sha256: Vec<u8>,
+}
+
+define_enum_group! {
+  #[enum_group(peek_kind(cfg(feature = "json")))]
+  #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+  pub enum BlobMsg {
+    Transfer {
+      Data(Chunk),
+      Raw(Bytes),
+    },
+    Integrity {
+      Check(Digest),
+      Signature(#[serde(with = "serde_bytes")] Vec<u8>),
+    }
+  }
+}
+
+fn chunk() -> Chunk {
+  Chunk { offset: 16, data: Bytes::from(vec![7u8; 1024]) }
+}
+
+// =============================================================================
+// Section A: Zero-Copy Conversions
+// =============================================================================
+
+/// Test: `Bytes` payloads keep their buffer through `into_group` and back.
+#[test]
+fn test_bytes_not_copied() {
+  let chunk = chunk();
+  let ptr = chunk.data.as_ptr();
+
+  let BlobMsgGroup::Transfer(group) = BlobMsg::Data(chunk).into_group() else {
+    panic!("expected the transfer group");
+  };
+  let wire = group.into_wire();
+  let Ok(chunk) = Chunk::try_from_wire(wire) else {
+    panic!("expected a chunk");
+  };
+  assert_eq!(chunk.data.as_ptr(), ptr);
+}
+
+// =============================================================================
+// Section B: Serialization
+// =============================================================================
+
+/// Test: `serde_bytes` on a variant payload is propagated to the generated enums.
+///
+/// Verifies bincode encodes the signature as a length-prefixed byte string.
+#[test]
+fn test_serde_bytes_payload_attribute() {
+  let msg = BlobMsg::Signature(vec![1, 2, 3]);
+  let bytes = bincode::serialize(&msg).unwrap();
+  assert_eq!(bytes, [3, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3]);
+  assert_eq!(bincode::deserialize::<BlobMsg>(&bytes).unwrap(), msg);
+
+  let group = Integrity::Signature(vec![4]);
+  assert_eq!(bincode::serialize(&group).unwrap(), [1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 4]);
+}
+
+/// Test: binary payloads roundtrip through JSON and bincode.
+#[test]
+fn test_binary_roundtrip() {
+  let msgs = [
+    BlobMsg::Data(chunk()),
+    BlobMsg::Raw(Bytes::from_static(b"raw")),
+    BlobMsg::Check(Digest { sha256: vec![0xab; 32] }),
+    BlobMsg::Signature(vec![9; 64]),
+  ];
+  for msg in msgs {
+    let json = serde_json::to_string(&msg).unwrap();
+    assert_eq!(serde_json::from_str::<BlobMsg>(&json).unwrap(), msg);
+    let bytes = bincode::serialize(&msg).unwrap();
+    assert_eq!(bincode::deserialize::<BlobMsg>(&bytes).unwrap(), msg);
+  }
+}
+
+/// Test: `peek_kind` reads the kind of binary messages without decoding the payload.
+#[cfg(feature = "json")]
+#[test]
+fn test_peek_binary_kind() {
+  use enum_group_macros::WireFormat;
+
+  let bytes = bincode::serialize(&BlobMsg::Raw(Bytes::from_static(b"raw"))).unwrap();
+  assert_eq!(BlobMsg::peek_kind(&bytes, WireFormat::Bincode), Ok(BlobMsgKind::Raw));
+  let json = serde_json::to_vec(&BlobMsg::Data(chunk())).unwrap();
+  assert_eq!(BlobMsg::peek_kind(&json, WireFormat::Json), Ok(BlobMsgKind::Data));
+}
@@ -165,6 +165,596 @@ fn test_empty_group() {
   assert!(matches!(msg.into_group(), EmptyGroupMsgGroup::NonEmptyGroup(_)));
 }
 
+/// Test: Variants with named fields.
+///
+/// Verifies they are declared alike on the wire and group enums, that conversions in
+/// both directions keep their fields, and that the variant handler and `examples()` take
+/// them field by field.
+#[test]
+fn test_struct_variants() {
+  use enum_group_macros::GroupOf;
+  use std::net::{Ipv4Addr, SocketAddr};
+
+  define_enum_group! {
+    #[enum_group(variant_handler, examples)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum SessionMsg {
+      Lifecycle {
+        Connected { session_id: u64, addr: Option<SocketAddr> },
+        Closed(u32),
+      },
+      Data {
+        Chunk { r#type: String, bytes: Vec<u8> },
+      }
+    }
+  }
+
+  let addr = Some(SocketAddr::from((Ipv4Addr::LOCALHOST, 80)));
+  let msg = SessionMsg::Connected { session_id: 7, addr };
+  let SessionMsgGroup::Lifecycle(Lifecycle::Connected { session_id, addr: a }) =
+    msg.clone().into_group()
+  else {
+    panic!("expected a connected message");
+  };
+  assert_eq!((session_id, a), (7, addr));
+  assert_eq!(SessionMsg::from_group(msg.clone().into_group()), msg);
+  assert_eq!(
+    Lifecycle::try_from_wire(msg.clone()),
+    Ok(Lifecycle::Connected { session_id: 7, addr })
+  );
+  assert_eq!(msg, SessionMsgKind::Connected);
+
+  struct Sizes;
+
+  impl SessionMsgVariantHandler for Sizes {
+    type Output = usize;
+
+    fn handle_connected(&mut self, session_id: u64, addr: Option<SocketAddr>) -> usize {
+      session_id as usize + usize::from(addr.is_some())
+    }
+
+    fn handle_closed(&mut self, _: u32) -> usize {
+      0
+    }
+
+    fn handle_chunk(&mut self, r#type: String, bytes: Vec<u8>) -> usize {
+      r#type.len() + bytes.len()
+    }
+  }
+
+  assert_eq!(msg.dispatch_variant(&mut Sizes), 8);
+  let chunk = SessionMsg::Chunk { r#type: "raw".into(), bytes: vec![1, 2] };
+  assert_eq!(chunk.dispatch_variant(&mut Sizes), 5);
+  assert_eq!(
+    SessionMsg::examples()[2],
+    SessionMsg::Chunk { r#type: String::new(), bytes: Vec::new() }
+  );
+}
+
+/// Test: Unit variants without payload.
+///
+/// Verifies they are accepted next to payload variants, with or without a trailing comma,
+/// and convert through the group enum and the variant handler.
+#[test]
+fn test_unit_variants() {
+  define_enum_group! {
+    #[enum_group(variant_handler, examples)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum SignalMsg {
+      Signals {
+        Ping,
+        Shutdown
+      },
+      Data {
+        Chunk(Vec<u8>),
+      }
+    }
+  }
+
+  assert!(matches!(SignalMsg::Ping.into_group(), SignalMsgGroup::Signals(Signals::Ping)));
+  assert_eq!(SignalMsg::from_group(SignalMsg::Shutdown.into_group()), SignalMsg::Shutdown);
+  assert_eq!(SignalMsg::Shutdown, SignalMsgKind::Shutdown);
+  assert_eq!(SignalMsg::examples()[..2], [SignalMsg::Ping, SignalMsg::Shutdown]);
+
+  struct Names;
+
+  impl SignalMsgVariantHandler for Names {
+    type Output = &'static str;
+
+    fn handle_ping(&mut self) -> &'static str {
+      "ping"
+    }
+
+    fn handle_shutdown(&mut self) -> &'static str {
+      "shutdown"
+    }
+
+    fn handle_chunk(&mut self, _: Vec<u8>) -> &'static str {
+      "chunk"
+    }
+  }
+
+  assert_eq!(SignalMsg::Ping.dispatch_variant(&mut Names), "ping");
+}
+
+/// Test: `Variant(Type) as GroupName` renames a variant on its group enums only.
+///
+/// Verifies conversions in both directions, nested groups, and that a serializable group
+/// enum keeps the tag of the wire name.
+#[test]
+fn test_renamed_group_variants() {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+  struct Credentials {
+    user: String,
+  }
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    enum ExternalMsg {
+      Incoming {
+        MsgTypeA(Credentials) as Login,
+        Handshake {
+          MsgTypeB(u8) as Hello,
+        },
+      },
+      Outgoing {
+        MsgTypeC(u8),
+      }
+    }
+  }
+
+  let credentials = Credentials { user: "root".into() };
+  let msg = ExternalMsg::MsgTypeA(credentials.clone());
+  let grouped = msg.clone().into_group();
+  assert!(matches!(&grouped, ExternalMsgGroup::Incoming(Incoming::Login(c)) if *c == credentials));
+  assert_eq!(ExternalMsg::from_group(grouped), msg);
+  assert!(matches!(
+    ExternalMsg::MsgTypeB(1).into_group(),
+    ExternalMsgGroup::Incoming(Incoming::Handshake(Handshake::Hello(1)))
+  ));
+
+  let login = Incoming::Login(credentials);
+  let json = serde_json::to_string(&login).unwrap();
+  assert_eq!(json, serde_json::to_string(&msg).unwrap());
+  assert_eq!(json, r#"{"MSG_TYPE_A":{"user":"root"}}"#);
+  assert_eq!(serde_json::from_str::<Incoming>(&json).unwrap(), login);
+}
+
+/// Test: `#[group(strip_prefix = "...")]` drops a common prefix on the group enum.
+///
+/// Verifies prefixed variants are renamed, nested ones too, while variants without the
+/// prefix or renamed with `as` are kept and the group enum serializes like the wire enum.
+#[test]
+fn test_strip_prefix() {
+  use serde::{Deserialize, Serialize};
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum ClusterMsg {
+      #[group(strip_prefix = "Broker")]
+      Broker {
+        BrokerReport(u32),
+        BrokerHeartbeat,
+        BrokerLegacyPing as Ping,
+        Shutdown,
+        Election {
+          BrokerVote(u8),
+        },
+      },
+      Client {
+        ClientHello(u8),
+      }
+    }
+  }
+
+  assert!(matches!(
+    ClusterMsg::BrokerReport(3).into_group(),
+    ClusterMsgGroup::Broker(Broker::Report(3))
+  ));
+  let vote = Broker::Election(Election::Vote(1));
+  assert_eq!(ClusterMsg::from_group(ClusterMsgGroup::Broker(vote)), ClusterMsg::BrokerVote(1));
+  assert!(matches!(Broker::Ping, Broker::Ping));
+  assert!(matches!(Broker::Shutdown, Broker::Shutdown));
+  assert!(matches!(Client::ClientHello(1), Client::ClientHello(_)));
+
+  let json = serde_json::to_string(&Broker::Heartbeat).unwrap();
+  assert_eq!(json, r#""BrokerHeartbeat""#);
+  assert_eq!(serde_json::from_str::<ClusterMsg>(&json).unwrap(), ClusterMsg::BrokerHeartbeat);
+  assert_eq!(serde_json::from_str::<Broker>(&json).unwrap(), Broker::Heartbeat);
+}
+
+/// Test: `Variant()` declares its payload as a generated unit struct of the same name.
+///
+/// Verifies the marker struct is the payload type on every generated enum, with the
+/// standard traits and the serde derives of the wire enum.
+#[test]
+fn test_marker_payloads() {
+  use enum_group_macros::VariantOf;
+  use serde::{Deserialize, Serialize};
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum HeartbeatMsg {
+      Liveness {
+        Ping(),
+        Pong(),
+      },
+      Data {
+        Chunk(u8),
+      }
+    }
+  }
+
+  let msg: HeartbeatMsg = Ping.into_wire();
+  assert!(matches!(msg.clone().into_group(), HeartbeatMsgGroup::Liveness(Liveness::Ping(Ping))));
+  assert_eq!(Ping::try_from_wire(msg), Ok(Ping));
+  let pong = Pong;
+  assert_eq!(HeartbeatMsg::Pong(pong), HeartbeatMsg::Pong(pong));
+  assert_eq!(serde_json::to_string(&HeartbeatMsg::Pong(Pong)).unwrap(), r#"{"Pong":null}"#);
+}
+
+/// Test: Generic parameters on the wire enum.
+///
+/// Verifies group enums carry only the parameters they use, with their where-clause
+/// predicates, and that conversions and kinds work for every instantiation.
+#[test]
+fn test_generic_wire_enum() {
+  use enum_group_macros::{EnumGroupKind, GroupOf, VariantOf};
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum GenericMsg<'a, T>
+    where
+      T: Clone,
+    {
+      Control {
+        Start(MsgA),
+        Stop,
+      },
+      Custom {
+        Payload(T),
+        Label(&'a str),
+      }
+    }
+  }
+
+  let msg: GenericMsg<'_, u32> = GenericMsg::Payload(7);
+  assert!(matches!(msg.clone().into_group(), GenericMsgGroup::Custom(Custom::Payload(7))));
+  assert_eq!(GenericMsg::from_group(msg.clone().into_group()), msg);
+  assert_eq!(EnumGroupKind::kind(&msg), GenericMsgKind::Payload);
+  assert_eq!(
+    GenericMsg::<String>::group_of_kind(GenericMsgKind::Stop),
+    GenericMsgGroupKind::Control
+  );
+
+  // `Control` uses none of the parameters and stays a plain enum
+  let control: Control = Control::Start(MsgA { value: 1 });
+  let wire: GenericMsg<'_, Vec<u8>> = control.clone().into_wire();
+  assert_eq!(wire, GenericMsg::Start(MsgA { value: 1 }));
+  assert_eq!(Control::try_from_wire(GenericMsg::<bool>::Stop), Ok(Control::Stop));
+  assert_eq!(MsgA::try_from_wire(GenericMsg::<bool>::Stop), Err(GenericMsg::Stop));
+
+  let label: Custom<'_, String> = Custom::Label("x");
+  assert_eq!(GenericMsg::from_group(GenericMsgGroup::Custom(label)), GenericMsg::Label("x"));
+}
+
+/// Test: Const generic parameters and array payloads.
+///
+/// Verifies a wire enum parameterized by a const, with a default, gives its parameter to
+/// the groups whose payloads use it, in a type argument or an array length, while fixed
+/// arrays and instantiated const generics are plain payloads with `VariantOf` impls.
+#[test]
+fn test_const_generic_payloads() {
+  use enum_group_macros::{GroupOf, VariantOf};
+
+  #[derive(Debug, Clone, PartialEq)]
+  struct Frame<const N: usize>([u8; N]);
+
+  define_enum_group! {
+    #[enum_group(handler)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum PacketMsg<const N: usize = 4> {
+      Data {
+        Chunk(Frame<N>),
+        Raw([u8; N]),
+      },
+      Keys {
+        Key([u8; 32]),
+        Digest(Frame<16>),
+        Pair {
+          Public([u16; 2 * 4]),
+        },
+      }
+    }
+  }
+
+  struct Sizes;
+  impl<const N: usize> PacketMsgHandler<N> for Sizes {
+    type Output = usize;
+    fn handle_data(&mut self, _: Data<N>) -> usize {
+      N
+    }
+    fn handle_keys(&mut self, _: Keys) -> usize {
+      0
+    }
+  }
+
+  let msg: PacketMsg<3> = PacketMsg::Raw([1, 2, 3]);
+  assert!(matches!(msg.clone().into_group(), PacketMsgGroup::Data(Data::Raw([1, 2, 3]))));
+  assert_eq!(msg.dispatch(&mut Sizes), 3);
+  let chunk: PacketMsg = Data::Chunk(Frame([0; 4])).into_wire();
+  assert_eq!(chunk, PacketMsg::<4>::Chunk(Frame([0; 4])));
+
+  // `Keys` uses none of the parameters and stays a plain enum
+  let keys: Keys = Keys::Pair(Pair::Public([7; 8]));
+  assert_eq!(keys.clone().into_wire(), PacketMsg::<2>::Public([7; 8]));
+  assert_eq!(<[u8; 32]>::try_from_wire(PacketMsg::<1>::Key([9; 32])), Ok([9; 32]));
+  assert_eq!(
+    Frame::<16>::try_from_wire(PacketMsg::<1>::Digest(Frame([1; 16]))),
+    Ok(Frame([1; 16]))
+  );
+}
+
+/// Test: Lifetime parameters for borrowed payloads.
+///
+/// Verifies a zero-copy wire enum dispatches through the group, partial and variant
+/// handler traits, which carry the wire enum's lifetime.
+#[test]
+fn test_borrowed_payloads() {
+  define_enum_group! {
+    #[enum_group(handler(context), partial_handler, variant_handler)]
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum FrameMsg<'a> {
+      Binary {
+        Raw(&'a [u8]),
+      },
+      Textual {
+        Text(&'a str),
+        Line { number: u32, text: &'a str },
+      },
+      Control {
+        Close,
+      }
+    }
+  }
+
+  struct Lengths;
+
+  impl<'a> FrameMsgHandler<'a, usize> for Lengths {
+    type Output = usize;
+
+    fn handle_binary(&mut self, total: &mut usize, msg: Binary<'a>) -> usize {
+      let Binary::Raw(bytes) = msg;
+      *total += bytes.len();
+      bytes.len()
+    }
+
+    fn handle_textual(&mut self, total: &mut usize, msg: Textual<'a>) -> usize {
+      let len = match msg {
+        Textual::Text(text) | Textual::Line { text, .. } => text.len(),
+      };
+      *total += len;
+      len
+    }
+
+    fn handle_control(&mut self, _: &mut usize, _: Control) -> usize {
+      0
+    }
+  }
+
+  let buffer = String::from("hello world");
+  let mut total = 0;
+  assert_eq!(FrameMsg::Raw(buffer.as_bytes()).dispatch(&mut Lengths, &mut total), 11);
+  assert_eq!(FrameMsg::Text(&buffer[..5]).dispatch(&mut Lengths, &mut total), 5);
+  assert_eq!(total, 16);
+
+  struct Unhandled;
+
+  impl<'a> FrameMsgPartialHandler<'a> for Unhandled {
+    type Output = Option<&'a str>;
+
+    fn handle_unhandled(&mut self, _: FrameMsg<'a>) -> Option<&'a str> {
+      None
+    }
+
+    fn handle_textual(&mut self, msg: Textual<'a>) -> Option<&'a str> {
+      match msg {
+        Textual::Text(text) | Textual::Line { text, .. } => Some(text),
+      }
+    }
+  }
+
+  let text = FrameMsg::Line { number: 1, text: &buffer[6..] }.dispatch_partial(&mut Unhandled);
+  assert_eq!(text, Some("world"));
+  assert_eq!(FrameMsg::Close.dispatch_partial(&mut Unhandled), None);
+
+  struct First;
+
+  impl<'a> FrameMsgVariantHandler<'a> for First {
+    type Output = Option<u8>;
+
+    fn handle_raw(&mut self, msg: &'a [u8]) -> Option<u8> {
+      msg.first().copied()
+    }
+
+    fn handle_text(&mut self, msg: &'a str) -> Option<u8> {
+      msg.bytes().next()
+    }
+
+    fn handle_line(&mut self, _: u32, text: &'a str) -> Option<u8> {
+      text.bytes().next()
+    }
+
+    fn handle_close(&mut self) -> Option<u8> {
+      None
+    }
+  }
+
+  assert_eq!(FrameMsg::Text(&buffer).dispatch_variant(&mut First), Some(b'h'));
+}
+
+/// Test: Where clauses on the enum definition.
+///
+/// Verifies the predicates reach every group enum whose payloads need them, so payload
+/// wrappers with bounds of their own compile without repeating them, and that bounds
+/// naming a parameter a group does not use are left off that group.
+#[test]
+fn test_where_clause() {
+  use enum_group_macros::GroupOf;
+
+  #[derive(Debug, Clone, PartialEq)]
+  struct Wrapper<T: Clone + Send>(T);
+
+  define_enum_group! {
+    #[enum_group(handler)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum BoundMsg<T, U: From<T>>
+    where
+      T: Clone + Send,
+    {
+      Wrapped {
+        Value(Wrapper<T>),
+      },
+      Converted {
+        Into(U),
+      }
+    }
+  }
+
+  // `Converted` only uses `U`, so it cannot carry `U: From<T>`
+  let converted: Converted<u64> = Converted::Into(7);
+  let msg: BoundMsg<u32, u64> = converted.into_wire();
+  assert_eq!(msg, BoundMsg::Into(7));
+
+  struct Unwrap;
+
+  impl BoundMsgHandler<u8, u16> for Unwrap {
+    type Output = u16;
+
+    fn handle_wrapped(&mut self, msg: Wrapped<u8>) -> u16 {
+      let Wrapped::Value(Wrapper(value)) = msg;
+      value.into()
+    }
+
+    fn handle_converted(&mut self, msg: Converted<u16>) -> u16 {
+      let Converted::Into(value) = msg;
+      value
+    }
+  }
+
+  assert_eq!(BoundMsg::Value(Wrapper(3)).dispatch(&mut Unwrap), 3);
+}
+
+/// Test: Variants configured out with `#[cfg(...)]`.
+///
+/// Verifies the variant disappears from every generated enum, match and table alike, and
+/// that the tables indexed by kind stay aligned with the remaining variants.
+#[test]
+fn test_cfg_variants() {
+  define_enum_group! {
+    #[enum_group(kind_map, examples, variant_handler, filter)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum GatedMsg {
+      Core {
+        #[cfg(any())]
+        Debug(String),
+        #[variant(retries = 3)]
+        Start(u32),
+      },
+      Extra {
+        #[cfg(all())]
+        Stop,
+      }
+    }
+  }
+
+  assert!(matches!(GatedMsg::Start(1).into_group(), GatedMsgGroup::Core(Core::Start(1))));
+  assert_eq!(GatedMsg::from_group(GatedMsg::Stop.into_group()), GatedMsg::Stop);
+  assert_eq!(GatedMsg::METADATA.len(), 2);
+  assert_eq!(GatedMsg::Start(1).metadata().name, "Start");
+  assert_eq!(GatedMsg::Start(1).metadata().max_retries, 3);
+  assert_eq!(GatedMsg::TAG_TABLE, &[(GatedMsgKind::Start, "Start"), (GatedMsgKind::Stop, "Stop")]);
+  assert_eq!(GatedMsgGroupKind::Core.variants(), &[GatedMsgKind::Start]);
+  assert_eq!(GatedMsg::examples(), [GatedMsg::Start(0), GatedMsg::Stop]);
+  assert!(GatedMsg::parse_filter("Core.*").unwrap().matches(&GatedMsg::Start(1)));
+
+  let map: GatedMsgKindMap<u8> = [(GatedMsgKind::Stop, 1)].into_iter().collect();
+  assert_eq!(map.iter().collect::<Vec<_>>(), [(GatedMsgKind::Stop, &1)]);
+
+  struct Names;
+
+  impl GatedMsgVariantHandler for Names {
+    type Output = &'static str;
+
+    fn handle_start(&mut self, _: u32) -> &'static str {
+      "start"
+    }
+
+    fn handle_stop(&mut self) -> &'static str {
+      "stop"
+    }
+  }
+
+  assert_eq!(GatedMsg::Stop.dispatch_variant(&mut Names), "stop");
+}
+
+/// Test: Nested sub-groups.
+///
+/// Verifies nested groups get enums of their own, wrapped by their parent group, and that
+/// the wire enum converts to and from every level.
+#[test]
+fn test_nested_groups() {
+  use enum_group_macros::GroupOf;
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum LayeredMsg {
+      Protocol {
+        Handshake {
+          Hello(MsgA),
+          Ack,
+        },
+        Data {
+          Frame(Vec<u8>),
+          Stream {
+            Open { id: u32 },
+            Close { id: u32 },
+          },
+        },
+        Ping,
+      },
+      App {
+        Command(String),
+      }
+    }
+  }
+
+  let msg = LayeredMsg::Hello(MsgA { value: 1 });
+  assert!(matches!(
+    msg.clone().into_group(),
+    LayeredMsgGroup::Protocol(Protocol::Handshake(Handshake::Hello(MsgA { value: 1 })))
+  ));
+  assert_eq!(LayeredMsg::from_group(msg.clone().into_group()), msg);
+
+  let open = LayeredMsg::Open { id: 3 };
+  assert_eq!(
+    Protocol::try_from_wire(open.clone()),
+    Ok(Protocol::Data(Data::Stream(Stream::Open { id: 3 })))
+  );
+  assert_eq!(Data::try_from_wire(open.clone()), Ok(Data::Stream(Stream::Open { id: 3 })));
+  assert_eq!(Stream::try_from_wire(open.clone()), Ok(Stream::Open { id: 3 }));
+  assert_eq!(Handshake::try_from_wire(open.clone()), Err(open.clone()));
+  assert_eq!(Stream::Open { id: 3 }.into_wire(), open);
+  assert_eq!(Protocol::Ping.into_wire(), LayeredMsg::Ping);
+
+  // Kinds stay flat and top-level
+  assert_eq!(LayeredMsg::group_of_kind(LayeredMsgKind::Close), LayeredMsgGroupKind::Protocol);
+  assert_eq!(LayeredMsgGroupKind::Protocol.variants().len(), 6);
+}
+
 // =============================================================================
 // Section B: Visibility Modifiers
 // =============================================================================
@@ -235,6 +825,55 @@ fn test_private_visibility() {
   let _grouped: PrivateMsgGroup = msg.into_group();
 }
 
+/// Test: A group may be given its own visibility, narrower than the wire enum's.
+///
+/// Verifies the group enum, its tag and re-export module take the group's visibility while
+/// the public items naming it do not warn, and that the other groups keep the wire
+/// enum's.
+#[test]
+fn test_group_visibility() {
+  #[deny(private_interfaces)]
+  mod inner {
+    use enum_group_macros::define_enum_group;
+
+    #[derive(Debug, Clone)]
+    pub struct Request(pub u8);
+
+    #[derive(Debug, Clone)]
+    pub struct Audit(pub String);
+
+    define_enum_group! {
+      #[enum_group(handler, group_tags, group_modules)]
+      #[derive(Debug, Clone)]
+      pub enum ExportedMsg {
+        Public {
+          Get(Request),
+        },
+        pub(self) Internal {
+          Log(Audit),
+        }
+      }
+    }
+
+    pub fn audit_text(msg: ExportedMsg) -> Option<String> {
+      let _tag = InternalTag;
+      match msg.into_group() {
+        ExportedMsgGroup::Internal(internal::Internal::Log(audit)) => Some(audit.0),
+        ExportedMsgGroup::Public(_) => None,
+      }
+    }
+  }
+
+  let msg = inner::ExportedMsg::Log(inner::Audit("login".into()));
+  assert_eq!(inner::audit_text(msg), Some("login".into()));
+  let public = inner::ExportedMsg::Get(inner::Request(1));
+  assert!(matches!(
+    public.into_group(),
+    inner::ExportedMsgGroup::Public(inner::public::Public::Get(_))
+  ));
+  let _tag = inner::PublicTag;
+}
+
 // =============================================================================
 // Section C: Type Variations
 // =============================================================================
@@ -385,29 +1024,184 @@ fn test_variant_level_attributes() {
   assert!(!json.contains("\"OriginalName\""));
 }
 
-// =============================================================================
-// Section E: Generated Code Structure
-// =============================================================================
-
-/// Test: Group enums are generated and accessible.
+/// Test: Per-group attributes.
 ///
-/// Verifies each group becomes its own enum type.
+/// Verifies the derives on a group replace the outer ones for its enum only, and that other
+/// attributes, doc comments included, are added to it.
 #[test]
-fn test_group_enums_exist() {
+fn test_group_attributes() {
+  /// A payload that cannot be compared.
+  #[derive(Debug, Clone)]
+  struct Opaque;
+
   define_enum_group! {
     #[derive(Debug, Clone)]
-    enum StructureMsg {
-      Alpha {
-        A1(MsgA),
-        A2(MsgB),
+    enum OverrideMsg {
+      /// Messages that can be compared.
+      #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+      Comparable {
+        Count(u32),
       },
-      Beta {
-        B1(MsgC),
+      Other {
+        Blob(Opaque),
       }
     }
   }
 
-  // Directly instantiate group enums (not through wire enum)
+  let mut seen = std::collections::HashSet::new();
+  seen.insert(Comparable::Count(1));
+  assert!(seen.contains(&Comparable::Count(1)));
+  assert!(matches!(OverrideMsg::Blob(Opaque).into_group(), OverrideMsgGroup::Other(_)));
+}
+
+/// Doc comments of the invocation, checked by `missing_docs` on the generated public types.
+#[deny(missing_docs)]
+pub mod documented {
+  use enum_group_macros::define_enum_group;
+
+  /// A handshake payload.
+  #[derive(Debug, Clone)]
+  pub struct Hello;
+
+  define_enum_group! {
+    /// Messages of the documented protocol.
+    #[derive(Debug, Clone)]
+    pub enum DocumentedMsg {
+      /// Connection management.
+      Connection {
+        /// Opening a session.
+        Handshake {
+          /// Greets the peer.
+          Hello(Hello),
+        },
+        /// Closes the session.
+        Close,
+      }
+    }
+  }
+}
+
+/// Test: Doc comments on groups and variants.
+///
+/// Verifies the documented definition above compiles under `deny(missing_docs)`, so doc
+/// comments reach the group, nested group and kind enums and their variants.
+#[test]
+fn test_doc_comments() {
+  use documented::{Connection, DocumentedMsg, DocumentedMsgKind};
+
+  assert!(matches!(
+    DocumentedMsg::Close.into_group(),
+    documented::DocumentedMsgGroup::Connection(Connection::Close)
+  ));
+  assert_eq!(DocumentedMsg::Close, DocumentedMsgKind::Close);
+}
+
+/// Test: `#[non_exhaustive]` on the definition.
+///
+/// Verifies the attribute reaches the wire, group and dispatch enums without breaking the
+/// generated conversions and dispatchers, which match within the defining crate.
+#[test]
+fn test_non_exhaustive() {
+  define_enum_group! {
+    #[enum_group(handler, kind_map, filter)]
+    #[derive(Debug, Clone)]
+    #[non_exhaustive]
+    pub enum EvolvingMsg {
+      Protocol {
+        A(MsgA),
+        Handshake {
+          B(MsgB),
+        },
+      },
+      Business {
+        C(MsgC),
+      }
+    }
+  }
+
+  struct Names;
+  impl EvolvingMsgHandler for Names {
+    type Output = &'static str;
+    fn handle_protocol(&mut self, _: Protocol) -> &'static str {
+      "protocol"
+    }
+    fn handle_business(&mut self, _: Business) -> &'static str {
+      "business"
+    }
+  }
+
+  let msg = EvolvingMsg::B(MsgB { text: "b".into() });
+  assert!(matches!(
+    msg.clone().into_group(),
+    EvolvingMsgGroup::Protocol(Protocol::Handshake(Handshake::B(_)))
+  ));
+  assert_eq!(msg.dispatch(&mut Names), "protocol");
+}
+
+/// Test: `#[deprecated]` variants.
+///
+/// Verifies the generated code builds under `deny(deprecated)`, so only user code naming
+/// a deprecated variant warns.
+#[test]
+#[deny(deprecated)]
+fn test_deprecated_variants() {
+  #[derive(Debug, Clone, PartialEq)]
+  struct Count(i32);
+
+  #[derive(Debug, Clone, PartialEq)]
+  struct Label(String);
+
+  impl From<Count> for Label {
+    fn from(count: Count) -> Self {
+      Label(count.0.to_string())
+    }
+  }
+
+  define_enum_group! {
+    #[enum_group(handler, variant_handler, kind_map, filter, payload_any)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum AgingMsg {
+      Protocol {
+        #[deprecated(note = "use `B`")]
+        #[variant(legacy_of = B)]
+        A(Count),
+        B(Label),
+      },
+      Business {
+        C(MsgC),
+      }
+    }
+  }
+
+  #[allow(deprecated)]
+  let old = AgingMsg::A(Count(4));
+  assert_eq!(old.normalize(), AgingMsg::B(Label("4".into())));
+  assert_eq!(AgingMsg::C(MsgC { flag: true }), AgingMsgKind::C);
+}
+
+// =============================================================================
+// Section E: Generated Code Structure
+// =============================================================================
+
+/// Test: Group enums are generated and accessible.
+///
+/// Verifies each group becomes its own enum type.
+#[test]
+fn test_group_enums_exist() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum StructureMsg {
+      Alpha {
+        A1(MsgA),
+        A2(MsgB),
+      },
+      Beta {
+        B1(MsgC),
+      }
+    }
+  }
+
+  // Directly instantiate group enums (not through wire enum)
   let alpha: Alpha = Alpha::A1(MsgA { value: 1 });
   let beta: Beta = Beta::B1(MsgC { flag: true });
 
@@ -516,6 +1310,311 @@ fn test_enum_group_trait_impl() {
   let _: <TraitImplMsg as EnumGroup>::Group = TraitImplMsg::OnlyVar(MsgA { value: 1 }).into_group();
 }
 
+/// Test: `EnumGroup::from_group` reverses `into_group`.
+///
+/// Verifies a generic function can regroup, transform and re-flatten messages through the
+/// trait alone.
+#[test]
+fn test_enum_group_from_group() {
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum RoundTripMsg {
+      Left {
+        L1(MsgA),
+      },
+      Right {
+        R1(MsgB),
+      }
+    }
+  }
+
+  fn round_trip<T: EnumGroup>(val: T, map: impl FnOnce(T::Group) -> T::Group) -> T {
+    T::from_group(map(val.into_group()))
+  }
+
+  let msg = RoundTripMsg::R1(MsgB { text: "x".to_string() });
+  assert_eq!(round_trip(msg.clone(), |g| g), msg);
+
+  let bumped = round_trip(RoundTripMsg::L1(MsgA { value: 1 }), |g| match g {
+    RoundTripMsgGroup::Left(Left::L1(a)) => {
+      RoundTripMsgGroup::Left(Left::L1(MsgA { value: a.value + 1 }))
+    }
+    other => other,
+  });
+  assert_eq!(bumped, RoundTripMsg::L1(MsgA { value: 2 }));
+}
+
+/// Test: `GroupOf<Wire>` is implemented for every group enum.
+///
+/// Verifies a generic function can split wire messages by group and convert them back.
+#[test]
+fn test_group_of_trait_impl() {
+  use enum_group_macros::GroupOf;
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum GroupOfMsg {
+      Left {
+        L1(MsgA),
+        L2(MsgB),
+      },
+      Right {
+        R1(MsgC),
+      }
+    }
+  }
+
+  fn partition<G: GroupOf<GroupOfMsg>>(msgs: Vec<GroupOfMsg>) -> (Vec<G>, Vec<GroupOfMsg>) {
+    let mut matched = Vec::new();
+    let mut rest = Vec::new();
+    for msg in msgs {
+      match G::try_from_wire(msg) {
+        Ok(group) => matched.push(group),
+        Err(msg) => rest.push(msg),
+      }
+    }
+    (matched, rest)
+  }
+
+  let msgs = vec![
+    GroupOfMsg::L1(MsgA { value: 1 }),
+    GroupOfMsg::R1(MsgC { flag: true }),
+    GroupOfMsg::L2(MsgB { text: "b".to_string() }),
+  ];
+
+  let (lefts, rest) = partition::<Left>(msgs);
+  assert_eq!(lefts.len(), 2);
+  assert_eq!(rest, vec![GroupOfMsg::R1(MsgC { flag: true })]);
+
+  let back: Vec<GroupOfMsg> = lefts.into_iter().map(GroupOf::into_wire).collect();
+  assert_eq!(back[0], GroupOfMsg::L1(MsgA { value: 1 }));
+  assert_eq!(back[1], GroupOfMsg::L2(MsgB { text: "b".to_string() }));
+}
+
+/// Test: `TryFrom<Wire>` is implemented for every group enum.
+///
+/// Verifies a group-only subsystem accepts wire messages through `try_into` and gets the
+/// original message back for the other groups.
+#[test]
+fn test_group_try_from_wire() {
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum SubsystemMsg {
+      Control {
+        Start(MsgA),
+        Stop(MsgB),
+      },
+      Data {
+        Chunk(MsgD),
+      }
+    }
+  }
+
+  fn accept(msg: SubsystemMsg) -> Result<&'static str, SubsystemMsg> {
+    let control: Control = msg.try_into()?;
+    Ok(match control {
+      Control::Start(_) => "start",
+      Control::Stop(_) => "stop",
+    })
+  }
+
+  assert_eq!(accept(SubsystemMsg::Stop(MsgB { text: "s".into() })), Ok("stop"));
+  let chunk = SubsystemMsg::Chunk(MsgD { data: vec![1, 2] });
+  assert_eq!(accept(chunk.clone()), Err(chunk.clone()));
+  assert_eq!(Data::try_from(chunk), Ok(Data::Chunk(MsgD { data: vec![1, 2] })));
+}
+
+/// Test: `VariantOf<Wire>` is implemented for payload types backing a single variant.
+///
+/// Verifies a generic helper can accept any payload of the wire enum, and that a payload
+/// type shared by two variants gets no impl (a hand-written one would otherwise conflict).
+#[test]
+fn test_variant_of_trait_impl() {
+  use enum_group_macros::VariantOf;
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum VariantOfMsg {
+      Requests {
+        Get(MsgA),
+        Put(MsgB),
+      },
+      Responses {
+        Ok(MsgC),
+        Failed(MsgC),
+      }
+    }
+  }
+
+  impl VariantOf<VariantOfMsg> for MsgC {
+    fn try_from_wire(wire: VariantOfMsg) -> Result<Self, VariantOfMsg> {
+      match wire {
+        VariantOfMsg::Ok(c) | VariantOfMsg::Failed(c) => Ok(c),
+        other => Err(other),
+      }
+    }
+
+    fn into_wire(self) -> VariantOfMsg {
+      VariantOfMsg::Ok(self)
+    }
+  }
+
+  fn wrap<P: VariantOf<VariantOfMsg>>(payload: P) -> VariantOfMsg {
+    payload.into_wire()
+  }
+
+  assert_eq!(wrap(MsgA { value: 1 }), VariantOfMsg::Get(MsgA { value: 1 }));
+  assert_eq!(
+    wrap(MsgB { text: "p".to_string() }),
+    VariantOfMsg::Put(MsgB { text: "p".to_string() })
+  );
+
+  assert_eq!(MsgA::try_from_wire(VariantOfMsg::Get(MsgA { value: 2 })), Ok(MsgA { value: 2 }));
+  assert_eq!(
+    MsgA::try_from_wire(VariantOfMsg::Put(MsgB { text: "q".to_string() })),
+    Err(VariantOfMsg::Put(MsgB { text: "q".to_string() }))
+  );
+}
+
+/// Test: `PayloadOf<Wire>` names the group of a payload at the type level.
+///
+/// Verifies a sender bounded on one group accepts that group's payloads, nested and boxed
+/// ones included, and wraps them in the group enum.
+#[test]
+fn test_payload_of_trait_impl() {
+  use enum_group_macros::PayloadOf;
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum SentMsg {
+      Protocol {
+        Handshake {
+          Hello(MsgA),
+        },
+        #[variant(boxed)]
+        Blob(MsgD),
+      },
+      Business {
+        Order(MsgB),
+      }
+    }
+  }
+
+  fn send_protocol<P: PayloadOf<SentMsg, Group = Protocol>>(outbox: &mut Vec<Protocol>, p: P) {
+    outbox.push(p.into_group());
+  }
+
+  let mut outbox = Vec::new();
+  send_protocol(&mut outbox, MsgA { value: 1 });
+  send_protocol(&mut outbox, MsgD { data: vec![2] });
+  assert_eq!(
+    outbox,
+    [
+      Protocol::Handshake(Handshake::Hello(MsgA { value: 1 })),
+      Protocol::Blob(Box::new(MsgD { data: vec![2] })),
+    ]
+  );
+  // Payload types shared between wire enums pick theirs through the trait parameter
+  let order = PayloadOf::<SentMsg>::into_group(MsgB { text: "o".into() });
+  assert_eq!(order, Business::Order(MsgB { text: "o".into() }));
+}
+
+/// Test: `From<Dispatch>` converts the dispatch enum back into the wire enum.
+///
+/// Verifies a regroup, transform and flatten pipeline round-trips every message, without
+/// the `EnumGroup` trait impl, and messages of a group left out of dispatch too.
+#[test]
+fn test_from_dispatch_enum() {
+  define_enum_group! {
+    #[enum_group(skip_trait)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum PipelineMsg {
+      Requests {
+        Get(MsgA),
+      },
+      #[group(skip_dispatch)]
+      Audit {
+        Logged(MsgB),
+      }
+    }
+  }
+
+  let msgs = [PipelineMsg::Get(MsgA { value: 1 }), PipelineMsg::Logged(MsgB { text: "l".into() })];
+  let regrouped: Vec<PipelineMsg> = msgs
+    .iter()
+    .cloned()
+    .map(|msg| match msg.into_group() {
+      PipelineMsgGroup::Requests(Requests::Get(a)) => {
+        PipelineMsgGroup::Requests(Requests::Get(MsgA { value: a.value * 10 }))
+      }
+      other => other,
+    })
+    .map(PipelineMsg::from)
+    .collect();
+  assert_eq!(regrouped[0], PipelineMsg::Get(MsgA { value: 10 }));
+  assert_eq!(regrouped[1], msgs[1]);
+}
+
+/// Test: `From<Payload>` is implemented for the wire enum alongside `VariantOf`.
+///
+/// Verifies `.into()` and `?` build messages from payloads, boxed ones too.
+#[test]
+fn test_from_payload() {
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum SendMsg {
+      Requests {
+        Get(MsgA),
+        #[variant(boxed)]
+        Put(MsgB),
+      },
+      Responses {
+        Ok(MsgC),
+        Failed(MsgC),
+      }
+    }
+  }
+
+  fn send(msg: impl Into<SendMsg>) -> SendMsg {
+    msg.into()
+  }
+  fn reject(value: i32) -> Result<(), SendMsg> {
+    Err(MsgA { value })?
+  }
+
+  assert_eq!(send(MsgA { value: 1 }), SendMsg::Get(MsgA { value: 1 }));
+  let put: SendMsg = MsgB { text: "p".into() }.into();
+  assert_eq!(put, SendMsg::Put(Box::new(MsgB { text: "p".into() })));
+  assert_eq!(reject(2), Err(SendMsg::Get(MsgA { value: 2 })));
+}
+
+/// Test: `TryFrom<Wire>` is implemented for payload types backing a single variant.
+///
+/// Verifies boxed payloads are unboxed, other variants are handed back, and a payload
+/// holding the wire enum itself still compiles without the impl.
+#[test]
+fn test_payload_try_from_wire() {
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum ExtractMsg {
+      Requests {
+        Get(MsgA),
+        #[variant(boxed)]
+        Put(MsgB),
+      },
+      Control {
+        Wrapped(Box<ExtractMsg>),
+      }
+    }
+  }
+
+  assert_eq!(MsgA::try_from(ExtractMsg::Get(MsgA { value: 1 })), Ok(MsgA { value: 1 }));
+  let put = ExtractMsg::Put(Box::new(MsgB { text: "p".into() }));
+  let b: MsgB = put.clone().try_into().unwrap();
+  assert_eq!(b, MsgB { text: "p".into() });
+  assert_eq!(MsgA::try_from(put.clone()), Err(put));
+}
+
 // =============================================================================
 // Section F: Serde Integration
 // =============================================================================
@@ -550,6 +1649,49 @@ fn test_serde_serialize() {
   assert!(json.contains("\"id\":123"));
 }
 
+/// Test: Generic code regroups, processes and re-serializes through `EnumGroup` alone.
+///
+/// Verifies a relay decoding, transforming and encoding messages round-trips the JSON.
+#[test]
+fn test_serde_relay_from_group() {
+  use serde::de::DeserializeOwned;
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+  struct Counter {
+    n: u32,
+  }
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum RelayMsg {
+      Counting {
+        Count(Counter),
+      },
+      Control {
+        Reset,
+      }
+    }
+  }
+
+  fn relay<T: EnumGroup + Serialize + DeserializeOwned>(
+    json: &str,
+    process: impl FnOnce(T::Group) -> T::Group,
+  ) -> String {
+    let msg: T = serde_json::from_str(json).unwrap();
+    serde_json::to_string(&T::from_group(process(msg.into_group()))).unwrap()
+  }
+
+  let bumped = relay::<RelayMsg>(r#"{"Count":{"n":1}}"#, |group| match group {
+    RelayMsgGroup::Counting(Counting::Count(c)) => {
+      RelayMsgGroup::Counting(Counting::Count(Counter { n: c.n + 1 }))
+    }
+    other => other,
+  });
+  assert_eq!(bumped, r#"{"Count":{"n":2}}"#);
+  assert_eq!(relay::<RelayMsg>(r#""Reset""#, |group| group), r#""Reset""#);
+}
+
 /// Test: Wire enum deserializes correctly.
 ///
 /// Verifies deserialization from JSON works.
@@ -611,3 +1753,2567 @@ fn test_serde_roundtrip() {
 
   assert_eq!(original, restored);
 }
+
+// =============================================================================
+// Section G: Generated Group Modules
+// =============================================================================
+
+/// Test: `group_modules` re-exports each group enum and its payload types.
+///
+/// Verifies a module named after the group (in snake_case) exposes the group enum and
+/// every payload type behind it, including payloads used by several variants.
+#[test]
+fn test_group_modules() {
+  mod inner {
+    use enum_group_macros::define_enum_group;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Hello;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Frame(pub u32);
+
+    define_enum_group! {
+      #[enum_group(group_modules)]
+      #[derive(Debug, Clone)]
+      pub enum ModMsg {
+        Handshake {
+          Hello(Hello),
+        },
+        DataPlane {
+          First(Frame),
+          Second(Frame),
+          Raw(Vec<u8>),
+        }
+      }
+    }
+  }
+
+  use inner::data_plane::{DataPlane, Frame};
+  use inner::handshake::{Handshake, Hello};
+
+  let hello: Handshake = Handshake::Hello(Hello);
+  assert!(matches!(hello, Handshake::Hello(Hello)));
+
+  let msg = inner::ModMsg::Second(Frame(7));
+  assert!(matches!(msg.into_group(), inner::ModMsgGroup::DataPlane(DataPlane::Second(Frame(7)))));
+}
+
+/// Test: `group_modules` with inherited visibility.
+///
+/// Verifies the re-exports are reachable from the module that invoked the macro even when
+/// the wire enum is private.
+#[test]
+fn test_group_modules_private_visibility() {
+  mod inner {
+    use super::MsgA;
+    use enum_group_macros::define_enum_group;
+
+    define_enum_group! {
+      #[enum_group(group_modules)]
+      #[derive(Debug, Clone)]
+      enum PrivateModMsg {
+        Alpha {
+          A(MsgA),
+        }
+      }
+    }
+
+    pub fn build() -> bool {
+      let value = alpha::Alpha::A(MsgA { value: 3 });
+      matches!(value, Alpha::A(MsgA { value: 3 }))
+    }
+  }
+
+  assert!(inner::build());
+}
+
+/// Test: `groups_module(name)` generates the group enums inside a module of their own.
+///
+/// Verifies the group enums, nested ones included, are only reachable through the module,
+/// next to a user type of the same name, and that conversions, handlers, group tags and
+/// `group_modules` name them through it.
+#[test]
+fn test_groups_module() {
+  mod inner {
+    use enum_group_macros::define_enum_group;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Hello;
+
+    /// Named like a group, which stays out of this module.
+    pub struct Control;
+
+    define_enum_group! {
+      #[enum_group(groups_module(groups), group_modules, group_tags, handler)]
+      #[derive(Debug, Clone, PartialEq)]
+      enum ScopedMsg {
+        Control {
+          Ping(u8),
+          Handshake {
+            Hello(Hello),
+          },
+        },
+        Data {
+          Chunk(u32),
+        }
+      }
+    }
+
+    struct Names;
+    impl ScopedMsgHandler for Names {
+      type Output = &'static str;
+      fn handle_control(&mut self, _: groups::Control) -> &'static str {
+        "control"
+      }
+      fn handle_data(&mut self, _: groups::Data) -> &'static str {
+        "data"
+      }
+    }
+
+    pub fn build() -> bool {
+      use enum_group_macros::{EnumGroup, GroupOf, GroupTag};
+
+      let _user_type = Control;
+      let msg = ScopedMsg::Hello(Hello);
+      let grouped = msg.clone().into_group();
+      let nested = groups::Control::Handshake(groups::Handshake::Hello(Hello));
+      let tag: <ControlTag as GroupTag>::Group = groups::Control::Ping(1);
+      matches!(&grouped, ScopedMsgGroup::Control(control) if *control == nested)
+        && groups::Handshake::try_from_wire(msg.clone()) == Ok(groups::Handshake::Hello(Hello))
+        && ScopedMsg::from_group(grouped) == msg
+        && msg.dispatch(&mut Names) == "control"
+        && matches!(control::Handshake::Hello(Hello), groups::Handshake::Hello(_))
+        && tag.into_wire() == ScopedMsg::Ping(1)
+    }
+  }
+
+  assert!(inner::build());
+}
+
+// =============================================================================
+// Section H: Kind Enums
+// =============================================================================
+
+/// Test: `group_of_kind` maps every variant kind to its group kind.
+///
+/// Verifies the mapping is usable in const context, without constructing a message.
+#[test]
+fn test_group_of_kind() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum KindMsg {
+      Control {
+        Start(MsgA),
+        Stop(MsgB),
+      },
+      Data {
+        Chunk(MsgD),
+      }
+    }
+  }
+
+  const CHUNK_GROUP: KindMsgGroupKind = KindMsg::group_of_kind(KindMsgKind::Chunk);
+  assert_eq!(CHUNK_GROUP, KindMsgGroupKind::Data);
+
+  assert_eq!(KindMsg::group_of_kind(KindMsgKind::Start), KindMsgGroupKind::Control);
+  assert_eq!(KindMsg::group_of_kind(KindMsgKind::Stop), KindMsgGroupKind::Control);
+}
+
+/// Test: `GroupKind::variants` lists the member kinds of each group.
+///
+/// Verifies membership and declaration order, including for an empty group.
+#[test]
+fn test_group_kind_variants() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum MemberMsg {
+      Control {
+        Start(MsgA),
+        Stop(MsgB),
+      },
+      Data {
+        Chunk(MsgD),
+      },
+      Reserved {}
+    }
+  }
+
+  assert_eq!(MemberMsgGroupKind::Control.variants(), &[MemberMsgKind::Start, MemberMsgKind::Stop]);
+  assert_eq!(MemberMsgGroupKind::Data.variants(), &[MemberMsgKind::Chunk]);
+  assert!(MemberMsgGroupKind::Reserved.variants().is_empty());
+}
+
+/// Test: `Kind::ALL` and `iter()` list every kind in declaration order.
+///
+/// Verifies variants configured away are left out, and that every kind maps to a group.
+#[test]
+fn test_kind_iter() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum IterMsg {
+      Control {
+        Start(MsgA),
+        #[cfg(any())]
+        Gone(MsgB),
+      },
+      Data {
+        Chunk(MsgD),
+        Flush,
+      }
+    }
+  }
+
+  assert_eq!(IterMsgKind::ALL, &[IterMsgKind::Start, IterMsgKind::Chunk, IterMsgKind::Flush]);
+  let names: Vec<_> = IterMsgKind::iter().map(IterMsgKind::name).collect();
+  assert_eq!(names, ["Start", "Chunk", "Flush"]);
+  let groups: Vec<_> = IterMsgGroupKind::iter().collect();
+  assert_eq!(groups, IterMsgGroupKind::ALL);
+  assert!(IterMsgKind::iter().all(|kind| groups.contains(&IterMsg::group_of_kind(kind))));
+}
+
+/// Test: `EnumGroupKind` is implemented with the generated kind enums.
+///
+/// Verifies `kind()` and `group_kind()` through a generic function bounded on the trait.
+#[test]
+fn test_enum_group_kind_trait() {
+  use enum_group_macros::EnumGroupKind;
+  use std::collections::HashMap;
+  use std::hash::Hash;
+
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum KindTraitMsg {
+      Control {
+        Start(MsgA),
+        Stop(MsgB),
+      },
+      Data {
+        Chunk(MsgD),
+      }
+    }
+  }
+
+  fn count_by_group<M: EnumGroupKind>(msgs: &[M]) -> HashMap<M::GroupKind, usize>
+  where
+    M::GroupKind: Eq + Hash,
+  {
+    let mut counts = HashMap::new();
+    for msg in msgs {
+      *counts.entry(msg.group_kind()).or_default() += 1;
+    }
+    counts
+  }
+
+  let msgs = [
+    KindTraitMsg::Start(MsgA { value: 1 }),
+    KindTraitMsg::Chunk(MsgD { data: vec![1] }),
+    KindTraitMsg::Stop(MsgB { text: "x".to_string() }),
+  ];
+
+  assert_eq!(msgs[0].kind(), KindTraitMsgKind::Start);
+  assert_eq!(msgs[1].kind(), KindTraitMsgKind::Chunk);
+  assert_eq!(msgs[2].group_kind(), KindTraitMsgGroupKind::Control);
+
+  let counts = count_by_group(&msgs);
+  assert_eq!(counts[&KindTraitMsgGroupKind::Control], 2);
+  assert_eq!(counts[&KindTraitMsgGroupKind::Data], 1);
+}
+
+/// Test: The kind enum is a cheap `Copy + Eq + Hash` key.
+///
+/// Verifies messages deduplicate by kind without touching their payloads, calling the
+/// inherent `kind()` without importing `EnumGroupKind`.
+#[test]
+fn test_kind_as_key() {
+  use std::collections::HashSet;
+
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum DedupMsg {
+      Control {
+        Start(MsgA),
+        Stop(MsgB),
+      }
+    }
+  }
+
+  let msgs = [
+    DedupMsg::Start(MsgA { value: 1 }),
+    DedupMsg::Start(MsgA { value: 2 }),
+    DedupMsg::Stop(MsgB { text: "x".to_string() }),
+  ];
+  let mut seen = HashSet::new();
+  let first: Vec<_> = msgs.iter().filter(|msg| seen.insert(msg.kind())).collect();
+  assert_eq!(first.len(), 2);
+  let kind = msgs[2].kind();
+  let copied = kind;
+  assert_eq!((kind, copied), (DedupMsgKind::Stop, DedupMsgKind::Stop));
+}
+
+/// Test: The kind enums are ordered keys whatever the wire enum derives.
+///
+/// Verifies a wire enum deriving neither `Eq` nor `Ord` still gets kinds usable in a
+/// `BTreeMap`, ordered by declaration.
+#[test]
+fn test_kind_ordered_key() {
+  use std::collections::BTreeMap;
+
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum OrderedMsg {
+      Data {
+        Sample(MsgD),
+      },
+      Control {
+        Stop,
+        Start(MsgA),
+      }
+    }
+  }
+
+  let msgs = [
+    OrderedMsg::Start(MsgA { value: 1 }),
+    OrderedMsg::Stop,
+    OrderedMsg::Sample(MsgD { data: vec![] }),
+    OrderedMsg::Start(MsgA { value: 2 }),
+  ];
+  let mut per_kind = BTreeMap::new();
+  let mut per_group = BTreeMap::new();
+  for msg in &msgs {
+    *per_kind.entry(msg.kind()).or_insert(0) += 1;
+    *per_group.entry(msg.group_kind()).or_insert(0) += 1;
+  }
+  assert_eq!(
+    per_kind.into_iter().collect::<Vec<_>>(),
+    [(OrderedMsgKind::Sample, 1), (OrderedMsgKind::Stop, 1), (OrderedMsgKind::Start, 2)]
+  );
+  assert_eq!(
+    per_group.into_iter().collect::<Vec<_>>(),
+    [(OrderedMsgGroupKind::Data, 1), (OrderedMsgGroupKind::Control, 3)]
+  );
+  assert!(OrderedMsgKind::Stop < OrderedMsgKind::Start);
+}
+
+/// Test: `group_kind()` classifies a borrowed message into its group.
+///
+/// Verifies messages are counted per group through a reference, leaving them in place, by
+/// the inherent `group_kind()` without importing `EnumGroupKind`.
+#[test]
+fn test_group_kind_classifies() {
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum ClassifiedMsg {
+      Control {
+        Start(MsgA),
+      },
+      Data {
+        Chunk(MsgD),
+        Flush,
+      }
+    }
+  }
+
+  let msgs = [
+    ClassifiedMsg::Chunk(MsgD { data: vec![1] }),
+    ClassifiedMsg::Start(MsgA { value: 1 }),
+    ClassifiedMsg::Flush,
+  ];
+  let data = msgs.iter().filter(|msg| msg.group_kind() == ClassifiedMsgGroupKind::Data).count();
+  assert_eq!(data, 2);
+  assert_eq!(msgs[1].group_kind(), ClassifiedMsgGroupKind::Control);
+  assert_eq!(msgs[2], ClassifiedMsg::Flush);
+}
+
+/// Test: `group_name()` returns the declared name of a message's group.
+///
+/// Verifies it agrees with `GroupKind::name()` and `group_names()`, raw identifiers
+/// included.
+#[test]
+fn test_group_name() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum LabelledMsg {
+      Control {
+        Start(MsgA),
+      },
+      r#Data {
+        Chunk(MsgD),
+      }
+    }
+  }
+
+  assert_eq!(LabelledMsg::Start(MsgA { value: 1 }).group_name(), "Control");
+  assert_eq!(LabelledMsg::Chunk(MsgD { data: vec![] }).group_name(), "Data");
+  const NAME: &str = LabelledMsgGroupKind::Data.name();
+  assert_eq!(NAME, LabelledMsg::group_names()[1]);
+}
+
+/// Test: `variant_name()` returns the declared name of a message's variant.
+///
+/// Verifies the wire enum, group enums, nested ones and renamed variants agree on the name
+/// of the wire enum variant.
+#[test]
+fn test_variant_name() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum NamedMsg {
+      Control {
+        Handshake {
+          Hello(MsgA),
+        },
+        Stop(MsgB) as Halt,
+      },
+      Data {
+        r#Chunk(MsgD),
+        Flush,
+      }
+    }
+  }
+
+  assert_eq!(NamedMsg::Hello(MsgA { value: 1 }).variant_name(), "Hello");
+  assert_eq!(Control::Handshake(Handshake::Hello(MsgA { value: 1 })).variant_name(), "Hello");
+  assert_eq!(Handshake::Hello(MsgA { value: 1 }).variant_name(), "Hello");
+  assert_eq!(Control::Halt(MsgB { text: "h".into() }).variant_name(), "Stop");
+  assert_eq!(NamedMsg::Chunk(MsgD { data: vec![] }).variant_name(), "Chunk");
+  assert_eq!(Data::Flush.variant_name(), "Flush");
+  const NAME: &str = NamedMsgKind::Flush.name();
+  assert_eq!(NAME, "Flush");
+}
+
+/// Test: `is_<group>()` tells whether a message belongs to a group.
+///
+/// Verifies filtering by group, with multi-word and raw group names in `snake_case`.
+#[test]
+fn test_group_predicates() {
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum FilteredMsg {
+      ControlPlane {
+        Start(MsgA),
+      },
+      r#Data {
+        Chunk(MsgD),
+        Flush,
+      }
+    }
+  }
+
+  let msgs = [
+    FilteredMsg::Chunk(MsgD { data: vec![1] }),
+    FilteredMsg::Start(MsgA { value: 1 }),
+    FilteredMsg::Flush,
+  ];
+  let control: Vec<_> = msgs.iter().filter(|msg| msg.is_control_plane()).collect();
+  assert_eq!(control, [&FilteredMsg::Start(MsgA { value: 1 })]);
+  assert_eq!(msgs.iter().filter(|msg| msg.is_data()).count(), 2);
+}
+
+/// Test: Messages compare equal to their kind.
+///
+/// Verifies `msg == Kind::X` and `Kind::X == msg` both compare the kind only.
+#[test]
+fn test_msg_eq_kind() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum KindEqMsg {
+      Control {
+        Heartbeat(MsgA),
+        Stop(MsgB),
+      }
+    }
+  }
+
+  let msg = KindEqMsg::Heartbeat(MsgA { value: 7 });
+  assert!(msg == KindEqMsgKind::Heartbeat);
+  assert!(msg != KindEqMsgKind::Stop);
+  assert!(KindEqMsgKind::Heartbeat == msg);
+  assert!(KindEqMsgKind::Stop != msg);
+}
+
+// =============================================================================
+// Section I: Tag Tables
+// =============================================================================
+
+/// Test: `TAG_TABLE` lists the serialized tag of every variant.
+///
+/// Verifies `rename_all` on the container and `rename` on a variant are both honoured,
+/// and that each group enum exposes the subset of its own variants.
+#[test]
+fn test_tag_table() {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, Clone, Serialize, Deserialize)]
+  struct Payload {
+    id: u32,
+  }
+
+  define_enum_group! {
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(tag = "type", content = "payload", rename_all = "snake_case")]
+    enum TagMsg {
+      Session {
+        OpenSession(Payload),
+        #[serde(rename = "bye")]
+        CloseSession(Payload),
+      },
+      Data {
+        DataChunk(Payload),
+      }
+    }
+  }
+
+  assert_eq!(
+    TagMsg::TAG_TABLE,
+    &[
+      (TagMsgKind::OpenSession, "open_session"),
+      (TagMsgKind::CloseSession, "bye"),
+      (TagMsgKind::DataChunk, "data_chunk"),
+    ]
+  );
+  assert_eq!(
+    Session::TAG_TABLE,
+    &[(TagMsgKind::OpenSession, "open_session"), (TagMsgKind::CloseSession, "bye")]
+  );
+  assert_eq!(Data::TAG_TABLE, &[(TagMsgKind::DataChunk, "data_chunk")]);
+
+  // The table agrees with what serde actually emits
+  for (kind, tag) in TagMsg::TAG_TABLE {
+    let msg = match kind {
+      TagMsgKind::OpenSession => TagMsg::OpenSession(Payload { id: 1 }),
+      TagMsgKind::CloseSession => TagMsg::CloseSession(Payload { id: 1 }),
+      TagMsgKind::DataChunk => TagMsg::DataChunk(Payload { id: 1 }),
+    };
+    let json = serde_json::to_value(&msg).expect("serialize failed");
+    assert_eq!(json["type"], *tag);
+  }
+}
+
+/// Test: `WIRE_FINGERPRINT` tracks the layout of the wire enum.
+///
+/// Verifies identical definitions agree, and that reordering variants, moving a variant to
+/// another group or renaming a tag all change the fingerprint.
+#[test]
+fn test_wire_fingerprint() {
+  mod base {
+    use super::*;
+    define_enum_group! {
+      #[derive(Debug, Clone)]
+      pub enum Msg {
+        Control { Start(u8), Stop(u16) },
+        Data { Chunk(u32) }
+      }
+    }
+  }
+  mod same {
+    use super::*;
+    define_enum_group! {
+      #[derive(Debug, Clone)]
+      pub enum Msg {
+        Control { Start(u8), Stop(u16) },
+        Data { Chunk(u32) }
+      }
+    }
+  }
+  mod reordered {
+    use super::*;
+    define_enum_group! {
+      #[derive(Debug, Clone)]
+      pub enum Msg {
+        Control { Stop(u16), Start(u8) },
+        Data { Chunk(u32) }
+      }
+    }
+  }
+  mod regrouped {
+    use super::*;
+    define_enum_group! {
+      #[derive(Debug, Clone)]
+      pub enum Msg {
+        Control { Start(u8) },
+        Data { Stop(u16), Chunk(u32) }
+      }
+    }
+  }
+  mod renamed {
+    use super::*;
+    use serde::Serialize;
+    define_enum_group! {
+      #[derive(Debug, Clone, Serialize)]
+      pub enum Msg {
+        Control { Start(u8), #[serde(rename = "halt")] Stop(u16) },
+        Data { Chunk(u32) }
+      }
+    }
+  }
+
+  assert_eq!(base::Msg::WIRE_FINGERPRINT, same::Msg::WIRE_FINGERPRINT);
+  assert_ne!(base::Msg::WIRE_FINGERPRINT, reordered::Msg::WIRE_FINGERPRINT);
+  assert_ne!(base::Msg::WIRE_FINGERPRINT, regrouped::Msg::WIRE_FINGERPRINT);
+  assert_ne!(base::Msg::WIRE_FINGERPRINT, renamed::Msg::WIRE_FINGERPRINT);
+}
+
+/// Test: `WIRE_FINGERPRINT` only covers the variants compiled in.
+///
+/// Verifies a variant configured out by `#[cfg]` leaves the fingerprint of the definition
+/// without it unchanged, while the same variant compiled in changes it.
+#[test]
+fn test_wire_fingerprint_cfg() {
+  mod base {
+    use super::*;
+    define_enum_group! {
+      #[derive(Debug, Clone)]
+      pub enum Msg {
+        Control { Start(u8), Stop(u16) }
+      }
+    }
+  }
+  mod configured_out {
+    use super::*;
+    define_enum_group! {
+      #[derive(Debug, Clone)]
+      pub enum Msg {
+        Control { Start(u8), #[cfg(any())] Pause(u32), Stop(u16) }
+      }
+    }
+  }
+  mod configured_in {
+    use super::*;
+    define_enum_group! {
+      #[derive(Debug, Clone)]
+      pub enum Msg {
+        Control { Start(u8), #[cfg(all())] Pause(u32), Stop(u16) }
+      }
+    }
+  }
+
+  assert_eq!(base::Msg::WIRE_FINGERPRINT, configured_out::Msg::WIRE_FINGERPRINT);
+  assert_ne!(base::Msg::WIRE_FINGERPRINT, configured_in::Msg::WIRE_FINGERPRINT);
+}
+
+/// Test: `assert_tags` accepts the compiled tags and reports deviations as a diff.
+///
+/// Verifies a renamed tag shows up as a removed and an added line, with unchanged entries as
+/// context.
+#[test]
+fn test_assert_tags() {
+  use serde::Serialize;
+
+  define_enum_group! {
+    #[derive(Debug, Clone, Serialize)]
+    #[serde(rename_all = "snake_case")]
+    enum FrozenMsg {
+      Control { StartNow(u8), Stop(u16) },
+      Data { Chunk(u32) }
+    }
+  }
+
+  FrozenMsg::assert_tags(&[("Control", "start_now"), ("Control", "stop"), ("Data", "chunk")]);
+
+  let panic = std::panic::catch_unwind(|| {
+    FrozenMsg::assert_tags(&[("Control", "start_now"), ("Control", "halt"), ("Data", "chunk")]);
+  })
+  .expect_err("renamed tag is detected");
+  let message = panic.downcast_ref::<String>().expect("formatted panic message");
+  assert!(message.starts_with("tags of `FrozenMsg` differ"), "{message}");
+  assert!(message.contains("\n  Control start_now\n"), "{message}");
+  assert!(message.contains("\n- Control halt\n"), "{message}");
+  assert!(message.contains("\n+ Control stop\n"), "{message}");
+  assert!(message.ends_with("\n  Data chunk\n"), "{message}");
+}
+
+/// Test: Tags rustdoc rejects as aliases still compile.
+///
+/// Verifies the `doc(alias)` attributes skip tags with spaces or quotes, tags equal to the
+/// variant name and group names equal to a tag.
+#[test]
+fn test_doc_aliases() {
+  use serde::{Deserialize, Serialize};
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum AliasMsg {
+      Session {
+        #[serde(rename = "open session")]
+        Open(u8),
+        #[serde(rename = "it's closed")]
+        Close(u8),
+        #[serde(rename = "Session")]
+        Ping(u8),
+        Pong(u8),
+      }
+    }
+  }
+
+  let msg: AliasMsg = serde_json::from_str(r#"{"open session":1}"#).unwrap();
+  assert_eq!(msg, AliasMsg::Open(1));
+}
+
+/// Test: Explicit group discriminants.
+///
+/// Verifies `group_discriminant()` on the dispatch and wire enums returns the declared
+/// values regardless of declaration order.
+#[test]
+fn test_group_discriminants() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum DiscriminantMsg {
+      Business = 2 {
+        Order(MsgA),
+      },
+      Protocol = 1 {
+        Ping(MsgB),
+        Pong(MsgC),
+      },
+      Audit = 40 {}
+    }
+  }
+
+  let order = DiscriminantMsg::Order(MsgA { value: 1 });
+  let pong = DiscriminantMsg::Pong(MsgC { flag: true });
+  assert_eq!(order.group_discriminant(), 2);
+  assert_eq!(pong.group_discriminant(), 1);
+  assert_eq!(order.into_group().group_discriminant(), 2);
+  assert_eq!(pong.into_group().group_discriminant(), 1);
+}
+
+/// Test: `#[variant(tag = N)]` gives every variant a fixed numeric message id.
+///
+/// Verifies `tag()` on messages, `from_tag()` on known and unknown ids, and that the ids do
+/// not follow declaration order.
+#[test]
+fn test_numeric_tags() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum FramedMsg {
+      Protocol {
+        #[variant(tag = 17)]
+        Ping(MsgA),
+        #[variant(tag = 3)]
+        Close,
+      },
+      Business {
+        #[variant(tag = 0x100)]
+        Order { id: u64 },
+      }
+    }
+  }
+
+  const PING: u16 = FramedMsg::Ping(MsgA { value: 1 }).tag();
+  assert_eq!(PING, 17);
+  assert_eq!(FramedMsg::Close.tag(), 3);
+  assert_eq!(FramedMsg::Order { id: 9 }.tag(), 256);
+  assert_eq!(FramedMsg::from_tag(17), Some(FramedMsgKind::Ping));
+  assert_eq!(FramedMsg::from_tag(256), Some(FramedMsgKind::Order));
+  assert_eq!(FramedMsg::from_tag(4), None);
+}
+
+// =============================================================================
+// Section J: Options
+// =============================================================================
+
+/// Test: `method = "..."` renames the inherent conversion method.
+///
+/// Verifies the renamed method coexists with a user-defined `into_group` and that the
+/// `EnumGroup` trait still uses its own method name.
+#[test]
+fn test_rename_inherent_method() {
+  define_enum_group! {
+    #[enum_group(method = "to_grouped")]
+    #[derive(Debug, Clone)]
+    enum RenamedMethodMsg {
+      Alpha {
+        A(MsgA),
+      }
+    }
+  }
+
+  impl RenamedMethodMsg {
+    fn into_group(self) -> &'static str {
+      "user-defined"
+    }
+  }
+
+  let msg = RenamedMethodMsg::A(MsgA { value: 1 });
+  assert!(matches!(msg.clone().to_grouped(), RenamedMethodMsgGroup::Alpha(Alpha::A(_))));
+  assert_eq!(msg.clone().into_group(), "user-defined");
+  assert!(matches!(EnumGroup::into_group(msg), RenamedMethodMsgGroup::Alpha(_)));
+}
+
+/// Test: `bare_payloads` declares a bare variant name as a payload of the same type.
+///
+/// Verifies bare variants hold their payload type, nested too, while `Name {}` and `Name()`
+/// still declare variants without a payload of the user's.
+#[test]
+fn test_bare_payloads() {
+  use enum_group_macros::VariantOf;
+
+  define_enum_group! {
+    #[enum_group(bare_payloads)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum ShortMsg {
+      Protocol {
+        MsgA,
+        Handshake {
+          MsgB,
+        },
+      },
+      Control {
+        MsgC,
+        Ping(),
+        Shutdown {},
+      }
+    }
+  }
+
+  let msg = ShortMsg::MsgA(MsgA { value: 1 });
+  assert_eq!(MsgA::try_from_wire(msg.clone()), Ok(MsgA { value: 1 }));
+  assert!(matches!(msg.into_group(), ShortMsgGroup::Protocol(Protocol::MsgA(_))));
+  let hello = MsgB { text: "hi".into() }.into_wire();
+  assert!(matches!(hello, ShortMsg::MsgB(_)));
+  assert!(matches!(ShortMsg::Ping(Ping).into_group(), ShortMsgGroup::Control(Control::Ping(_))));
+  assert!(matches!(
+    ShortMsg::Shutdown {}.into_group(),
+    ShortMsgGroup::Control(Control::Shutdown {})
+  ));
+}
+
+/// Test: `dispatch_name = "..."` renames the dispatch enum.
+///
+/// Verifies the renamed enum coexists with a user type named `{Wire}Group` and is used by
+/// the conversions, the handler traits and `match_enum_group!`.
+#[test]
+fn test_rename_dispatch_enum() {
+  use enum_group_macros::match_enum_group;
+
+  struct CategorizedMsgGroup;
+
+  define_enum_group! {
+    #[enum_group(dispatch_name = "CategorizedMsgByCategory", handler)]
+    #[derive(Debug, Clone)]
+    enum CategorizedMsg {
+      Alpha {
+        A(MsgA),
+      },
+      Beta {
+        B(MsgB),
+      }
+    }
+  }
+
+  struct Names;
+  impl CategorizedMsgHandler for Names {
+    type Output = &'static str;
+    fn handle_alpha(&mut self, _: Alpha) -> &'static str {
+      "alpha"
+    }
+    fn handle_beta(&mut self, _: Beta) -> &'static str {
+      "beta"
+    }
+  }
+
+  let _user_type = CategorizedMsgGroup;
+  let msg = CategorizedMsg::B(MsgB { text: "b".into() });
+  let grouped: CategorizedMsgByCategory = msg.clone().into_group();
+  assert!(matches!(grouped, CategorizedMsgByCategory::Beta(Beta::B(_))));
+  assert!(matches!(
+    CategorizedMsg::from_group(grouped),
+    CategorizedMsg::B(MsgB { ref text }) if text == "b"
+  ));
+  assert_eq!(msg.clone().dispatch(&mut Names), "beta");
+  let group = match_enum_group!(msg, CategorizedMsg, {
+    Alpha(_) => "alpha",
+    Beta(_) => "beta",
+  });
+  assert_eq!(group, "beta");
+}
+
+/// Test: `skip_inherent` generates only the `EnumGroup` impl.
+///
+/// Verifies the conversion is still reachable through the trait, and that a user method
+/// named `into_group` no longer collides with generated code.
+#[test]
+fn test_skip_inherent() {
+  define_enum_group! {
+    #[enum_group(skip_inherent)]
+    #[derive(Debug, Clone)]
+    enum TraitOnlyMsg {
+      Alpha {
+        A(MsgA),
+      }
+    }
+  }
+
+  impl TraitOnlyMsg {
+    fn into_group(self) -> i32 {
+      match self {
+        TraitOnlyMsg::A(a) => a.value,
+      }
+    }
+  }
+
+  let msg = TraitOnlyMsg::A(MsgA { value: 9 });
+  assert_eq!(msg.clone().into_group(), 9);
+  assert!(matches!(EnumGroup::into_group(msg), TraitOnlyMsgGroup::Alpha(Alpha::A(_))));
+}
+
+/// Test: `skip_trait` generates only the inherent method.
+///
+/// Verifies the inherent method works and leaves `EnumGroup` free to be implemented by hand.
+#[test]
+fn test_skip_trait() {
+  define_enum_group! {
+    #[enum_group(skip_trait)]
+    #[derive(Debug, Clone)]
+    enum InherentOnlyMsg {
+      Alpha {
+        A(MsgA),
+      }
+    }
+  }
+
+  // A hand-written impl would conflict with a generated one
+  impl EnumGroup for InherentOnlyMsg {
+    type Group = ();
+
+    fn into_group(self) -> Self::Group {}
+
+    fn from_group((): Self::Group) -> Self {
+      InherentOnlyMsg::A(MsgA { value: 0 })
+    }
+  }
+
+  let msg = InherentOnlyMsg::A(MsgA { value: 9 });
+  assert!(matches!(msg.into_group(), InherentOnlyMsgGroup::Alpha(_)));
+}
+
+/// Test: `serialize_only` drops `Deserialize` from every generated enum.
+///
+/// Verifies serialization still works and `Deserialize` stays free to be implemented by
+/// hand (a derived impl would conflict).
+#[test]
+fn test_serialize_only() {
+  use serde::{Deserialize, Deserializer, Serialize};
+
+  #[derive(Debug, Clone, Serialize, Deserialize)]
+  struct Payload {
+    id: u32,
+  }
+
+  define_enum_group! {
+    #[enum_group(serialize_only)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(tag = "type", content = "payload")]
+    enum OutboundMsg {
+      Events {
+        Created(Payload),
+      }
+    }
+  }
+
+  impl<'de> Deserialize<'de> for OutboundMsg {
+    fn deserialize<D: Deserializer<'de>>(_: D) -> Result<Self, D::Error> {
+      Err(serde::de::Error::custom("outbound only"))
+    }
+  }
+  impl<'de> Deserialize<'de> for Events {
+    fn deserialize<D: Deserializer<'de>>(_: D) -> Result<Self, D::Error> {
+      Err(serde::de::Error::custom("outbound only"))
+    }
+  }
+
+  let json = serde_json::to_string(&OutboundMsg::Created(Payload { id: 1 })).expect("serialize");
+  assert_eq!(json, r#"{"type":"Created","payload":{"id":1}}"#);
+  assert!(serde_json::from_str::<OutboundMsg>(&json).is_err());
+}
+
+/// Test: `deserialize_only(wire)` only affects the wire enum.
+///
+/// Verifies the group enums keep both derives while the wire enum loses `Serialize`.
+#[test]
+fn test_deserialize_only_wire() {
+  use serde::{Deserialize, Serialize, Serializer};
+
+  #[derive(Debug, Clone, Serialize, Deserialize)]
+  struct Payload {
+    id: u32,
+  }
+
+  define_enum_group! {
+    #[enum_group(deserialize_only(wire))]
+    #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+    enum InboundMsg {
+      Commands {
+        Create(Payload),
+      }
+    }
+  }
+
+  impl Serialize for InboundMsg {
+    fn serialize<S: Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+      Err(serde::ser::Error::custom("inbound only"))
+    }
+  }
+
+  let msg: InboundMsg = serde_json::from_str(r#"{"Create":{"id":2}}"#).expect("deserialize");
+  let json = serde_json::to_string(&Commands::Create(Payload { id: 2 })).expect("serialize");
+  assert_eq!(json, r#"{"Create":{"id":2}}"#);
+  assert!(serde_json::to_string(&msg).is_err());
+}
+
+/// Test: `group_tags` generates a marker type per group linked to its enums.
+///
+/// Verifies generic code can reach the group enum and the wire enum through the tag.
+#[test]
+fn test_group_tags() {
+  use enum_group_macros::{GroupOf, GroupTag};
+  use std::any::TypeId;
+
+  define_enum_group! {
+    #[enum_group(group_tags)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum TaggedMsg {
+      Protocol {
+        A(MsgA),
+      },
+      Business {
+        B(MsgB),
+      }
+    }
+  }
+
+  fn take<T: GroupTag<Wire = TaggedMsg>>(msg: TaggedMsg) -> Option<T::Group> {
+    T::Group::try_from_wire(msg).ok()
+  }
+
+  assert_eq!(
+    take::<ProtocolTag>(TaggedMsg::A(MsgA { value: 1 })),
+    Some(Protocol::A(MsgA { value: 1 }))
+  );
+  assert_eq!(take::<BusinessTag>(TaggedMsg::A(MsgA { value: 1 })), None);
+  assert_ne!(TypeId::of::<ProtocolTag>(), TypeId::of::<BusinessTag>());
+  assert_eq!(std::mem::size_of::<ProtocolTag>(), 0);
+}
+
+/// Test: `as_group` borrows a message in the enum of its group.
+///
+/// Verifies every variant shape, boxed payloads, nested groups and `skip_dispatch` groups,
+/// with the message still usable afterwards.
+#[test]
+fn test_as_group() {
+  define_enum_group! {
+    #[enum_group(as_group)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum BorrowedMsg {
+      Protocol {
+        Handshake {
+          Hello(MsgA),
+          Ack,
+        },
+        #[variant(boxed)]
+        Blob(MsgD),
+      },
+      Business {
+        Closed { code: u16 },
+      },
+      #[group(skip_dispatch)]
+      Audit {
+        Logged(MsgB),
+      }
+    }
+  }
+
+  let hello = BorrowedMsg::Hello(MsgA { value: 1 });
+  let view = hello.as_group();
+  assert_eq!(
+    view,
+    BorrowedMsgGroupRef::Protocol(ProtocolRef::Handshake(HandshakeRef::Hello(&MsgA { value: 1 })))
+  );
+  let copied = view;
+  assert_eq!(copied, view);
+  assert_eq!(hello, BorrowedMsg::Hello(MsgA { value: 1 }));
+
+  let blob = BorrowedMsg::Blob(Box::new(MsgD { data: vec![7] }));
+  let BorrowedMsgGroupRef::Protocol(ProtocolRef::Blob(payload)) = blob.as_group() else {
+    panic!("expected a blob");
+  };
+  assert_eq!(payload.data, [7]);
+  assert!(matches!(
+    BorrowedMsg::Closed { code: 4 }.as_group(),
+    BorrowedMsgGroupRef::Business(BusinessRef::Closed { code: 4 })
+  ));
+  let logged = BorrowedMsg::Logged(MsgB { text: "l".into() });
+  assert_eq!(logged.as_group(), BorrowedMsgGroupRef::Undispatched(&logged));
+}
+
+/// Test: `as_group_mut` mutably borrows a message in the enum of its group.
+///
+/// Verifies payloads, named fields, boxed payloads and `skip_dispatch` messages are
+/// modified in place.
+#[test]
+fn test_as_group_mut() {
+  define_enum_group! {
+    #[enum_group(as_group)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum PatchedMsg {
+      Protocol {
+        Handshake {
+          Hello(MsgA),
+        },
+        #[variant(boxed)]
+        Blob(MsgD),
+      },
+      Business {
+        Closed { code: u16 },
+      },
+      #[group(skip_dispatch)]
+      Audit {
+        Logged(MsgB),
+      }
+    }
+  }
+
+  fn patch(msg: &mut PatchedMsg) {
+    match msg.as_group_mut() {
+      PatchedMsgGroupMut::Protocol(ProtocolMut::Handshake(HandshakeMut::Hello(a))) => a.value += 1,
+      PatchedMsgGroupMut::Protocol(ProtocolMut::Blob(d)) => d.data.push(0),
+      PatchedMsgGroupMut::Business(BusinessMut::Closed { code }) => *code = 0,
+      PatchedMsgGroupMut::Undispatched(msg) => *msg = PatchedMsg::Closed { code: 9 },
+    }
+  }
+
+  let mut msgs = [
+    PatchedMsg::Hello(MsgA { value: 1 }),
+    PatchedMsg::Blob(Box::new(MsgD { data: vec![1] })),
+    PatchedMsg::Closed { code: 4 },
+    PatchedMsg::Logged(MsgB { text: "l".into() }),
+  ];
+  msgs.iter_mut().for_each(patch);
+  assert_eq!(
+    msgs,
+    [
+      PatchedMsg::Hello(MsgA { value: 2 }),
+      PatchedMsg::Blob(Box::new(MsgD { data: vec![1, 0] })),
+      PatchedMsg::Closed { code: 0 },
+      PatchedMsg::Closed { code: 9 },
+    ]
+  );
+}
+
+/// Test: `as_group` on a generic wire enum carries its generics onto the borrowed enums.
+#[test]
+fn test_as_group_generics() {
+  define_enum_group! {
+    #[enum_group(as_group)]
+    #[derive(Debug, Clone)]
+    enum GenericBorrowedMsg<'msg, T> {
+      Data {
+        Borrowed(&'msg str),
+        Owned(T),
+      },
+      Control {
+        Stop,
+      }
+    }
+  }
+
+  let msg: GenericBorrowedMsg<'_, Vec<u8>> = GenericBorrowedMsg::Owned(vec![1]);
+  match msg.as_group() {
+    GenericBorrowedMsgGroupRef::Data(DataRef::Owned(data)) => assert_eq!(data, &[1]),
+    other => panic!("unexpected {other:?}"),
+  }
+  let stop = GenericBorrowedMsg::<u8>::Stop;
+  assert!(matches!(stop.as_group(), GenericBorrowedMsgGroupRef::Control(ControlRef::Stop)));
+}
+
+/// Test: `accessors` generates `as_<variant>()` and `into_<variant>()`.
+///
+/// Verifies the payload is returned for its own variant only, unboxed for `boxed` ones, and
+/// that variants without a single payload get no accessors.
+#[test]
+fn test_accessors() {
+  define_enum_group! {
+    #[enum_group(accessors)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum AccessedMsg<T> {
+      Protocol {
+        LoginRequest(MsgA),
+        #[variant(boxed)]
+        Blob(MsgD),
+        Generic(T),
+      },
+      Business {
+        Closed { code: u16 },
+        Ping,
+      }
+    }
+  }
+
+  let login: AccessedMsg<u8> = AccessedMsg::LoginRequest(MsgA { value: 1 });
+  assert_eq!(login.as_login_request(), Some(&MsgA { value: 1 }));
+  assert_eq!(login.as_blob(), None);
+  assert_eq!(login.clone().into_login_request(), Some(MsgA { value: 1 }));
+  assert_eq!(login.into_generic(), None);
+
+  let blob: AccessedMsg<u8> = AccessedMsg::Blob(Box::new(MsgD { data: vec![1] }));
+  assert_eq!(blob.as_blob().map(|d| d.data.len()), Some(1));
+  assert_eq!(blob.into_blob(), Some(MsgD { data: vec![1] }));
+  assert_eq!(AccessedMsg::Generic(7u8).into_generic(), Some(7));
+}
+
+/// Test: `display` prints the group and variant names of a message.
+///
+/// Verifies payloads without `Display` (including generic ones) are not printed, and that
+/// nested variants print their top-level group.
+#[test]
+fn test_display() {
+  #[derive(Debug, Clone)]
+  struct Opaque;
+
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    #[enum_group(display)]
+    enum DisplayMsg<T> {
+      Protocol {
+        Handshake {
+          Hello(MsgA),
+        },
+        Raw(T),
+      },
+      Control {
+        Stop,
+      }
+    }
+  }
+
+  let hello: DisplayMsg<Opaque> = DisplayMsg::Hello(MsgA { value: 1 });
+  assert_eq!(hello.to_string(), "Protocol::Hello");
+  assert_eq!(DisplayMsg::Raw(Opaque).to_string(), "Protocol::Raw");
+  let stop: DisplayMsg<Opaque> = DisplayMsg::Stop;
+  assert_eq!(format!("unexpected message {}", stop), "unexpected message Control::Stop");
+}
+
+/// Test: `router` dispatches messages to per-group handlers sharing a state.
+///
+/// Verifies unhandled groups hand the message back and handler errors are wrapped.
+#[test]
+fn test_router() {
+  use enum_group_macros::RouteError;
+
+  define_enum_group! {
+    #[enum_group(router)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum RoutedMsg {
+      Protocol {
+        A(MsgA),
+        B(MsgB),
+      },
+      Business {
+        C(MsgC),
+      }
+    }
+  }
+
+  let mut router = RoutedMsgRouter::<Vec<i32>, usize, String>::new().on_protocol(|seen, msg| {
+    match msg {
+      Protocol::A(a) => seen.push(a.value),
+      Protocol::B(b) => return Err(b.text),
+    }
+    Ok(seen.len())
+  });
+
+  let mut seen = Vec::new();
+  assert_eq!(router.route(&mut seen, RoutedMsg::A(MsgA { value: 3 })), Ok(1));
+  assert_eq!(
+    router.route(&mut seen, RoutedMsg::B(MsgB { text: "bad".into() })),
+    Err(RouteError::Handler("bad".to_string()))
+  );
+  assert_eq!(
+    router.route(&mut seen, RoutedMsg::C(MsgC { flag: true })),
+    Err(RouteError::Unhandled(RoutedMsg::C(MsgC { flag: true })))
+  );
+  assert_eq!(seen, vec![3]);
+}
+
+/// Test: the async router awaits the handler of the message's group.
+#[test]
+fn test_async_router() {
+  use std::future::Future;
+  use std::task::{Context, Poll, Waker};
+
+  define_enum_group! {
+    #[enum_group(router)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum AsyncRoutedMsg {
+      Protocol {
+        A(MsgA),
+      },
+      Business {
+        C(MsgC),
+      }
+    }
+  }
+
+  let mut router = AsyncRoutedMsgAsyncRouter::<u32>::new()
+    .on_protocol(|count, _| {
+      Box::pin(async move {
+        *count += 1;
+        Ok(())
+      })
+    })
+    .on_business(|count, _| {
+      Box::pin(async move {
+        *count += 10;
+        Ok(())
+      })
+    });
+
+  let mut fut = std::pin::pin!(async move {
+    let mut count = 0;
+    router.route(&mut count, AsyncRoutedMsg::A(MsgA { value: 1 })).await.unwrap();
+    router.route(&mut count, AsyncRoutedMsg::C(MsgC { flag: false })).await.unwrap();
+    count
+  });
+  assert_eq!(fut.as_mut().poll(&mut Context::from_waker(Waker::noop())), Poll::Ready(11));
+}
+
+/// Test: `handler` generates sync and async handler traits with dispatchers.
+///
+/// Verifies both dispatchers call the method of the message's group, and that async
+/// dispatch stays `Send` for `Send` handlers.
+#[test]
+fn test_handler_traits() {
+  use std::future::Future;
+  use std::task::{Context, Poll, Waker};
+
+  define_enum_group! {
+    #[enum_group(handler)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum HandledMsg {
+      Protocol {
+        A(MsgA),
+      },
+      Business {
+        C(MsgC),
+      }
+    }
+  }
+
+  struct Log(Vec<String>);
+
+  impl HandledMsgHandler for Log {
+    type Output = usize;
+
+    fn handle_protocol(&mut self, msg: Protocol) -> usize {
+      self.0.push(format!("{msg:?}"));
+      self.0.len()
+    }
+
+    fn handle_business(&mut self, _: Business) -> usize {
+      0
+    }
+  }
+
+  impl HandledMsgAsyncHandler for Log {
+    type Output = &'static str;
+
+    async fn handle_protocol(&mut self, _: Protocol) -> &'static str {
+      "protocol"
+    }
+
+    async fn handle_business(&mut self, _: Business) -> &'static str {
+      "business"
+    }
+  }
+
+  fn assert_send<T: Send>(t: T) -> T {
+    t
+  }
+
+  let mut log = Log(Vec::new());
+  assert_eq!(HandledMsg::A(MsgA { value: 1 }).dispatch(&mut log), 1);
+  assert_eq!(HandledMsg::C(MsgC { flag: true }).dispatch(&mut log), 0);
+
+  let mut fut =
+    std::pin::pin!(assert_send(HandledMsg::C(MsgC { flag: true }).dispatch_async(&mut log)));
+  assert_eq!(fut.as_mut().poll(&mut Context::from_waker(Waker::noop())), Poll::Ready("business"));
+}
+
+/// Test: the fallible handler traits thread one error type through dispatch.
+///
+/// Verifies `?` converts group-specific errors and `on_error` sees the failing group.
+#[test]
+fn test_try_handler_traits() {
+  use std::future::Future;
+  use std::task::{Context, Poll, Waker};
+
+  define_enum_group! {
+    #[enum_group(handler)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum FallibleMsg {
+      Protocol {
+        A(MsgA),
+      },
+      Business {
+        C(MsgC),
+      }
+    }
+  }
+
+  struct ParseError;
+
+  impl From<ParseError> for String {
+    fn from(_: ParseError) -> String {
+      "parse error".to_string()
+    }
+  }
+
+  struct Checker;
+
+  impl FallibleMsgTryHandler for Checker {
+    type Output = i32;
+    type Error = String;
+
+    fn handle_protocol(&mut self, msg: Protocol) -> Result<i32, String> {
+      let Protocol::A(a) = msg;
+      if a.value < 0 {
+        Err(ParseError)?;
+      }
+      Ok(a.value)
+    }
+
+    fn handle_business(&mut self, _: Business) -> Result<i32, String> {
+      Err("unsupported".to_string())
+    }
+
+    fn on_error(&mut self, group: FallibleMsgGroupKind, error: String) -> String {
+      format!("{group:?}: {error}")
+    }
+  }
+
+  impl FallibleMsgAsyncTryHandler for Checker {
+    type Output = ();
+    type Error = String;
+
+    async fn handle_protocol(&mut self, _: Protocol) -> Result<(), String> {
+      Ok(())
+    }
+
+    async fn handle_business(&mut self, _: Business) -> Result<(), String> {
+      Err("offline".to_string())
+    }
+  }
+
+  assert_eq!(FallibleMsg::A(MsgA { value: 4 }).try_dispatch(&mut Checker), Ok(4));
+  assert_eq!(
+    FallibleMsg::A(MsgA { value: -1 }).try_dispatch(&mut Checker),
+    Err("Protocol: parse error".to_string())
+  );
+  assert_eq!(
+    FallibleMsg::C(MsgC { flag: true }).try_dispatch(&mut Checker),
+    Err("Business: unsupported".to_string())
+  );
+
+  let mut checker = Checker;
+  let mut fut =
+    std::pin::pin!(FallibleMsg::C(MsgC { flag: true }).try_dispatch_async(&mut checker));
+  assert_eq!(
+    fut.as_mut().poll(&mut Context::from_waker(Waker::noop())),
+    Poll::Ready(Err("offline".to_string()))
+  );
+}
+
+/// Test: `handler(context)` threads a caller-provided context through dispatch.
+///
+/// Verifies one handler can serve several context types.
+#[test]
+fn test_handler_context() {
+  define_enum_group! {
+    #[enum_group(handler(context))]
+    #[derive(Debug, Clone, PartialEq)]
+    enum ContextMsg {
+      Protocol {
+        A(MsgA),
+      },
+      Business {
+        C(MsgC),
+      }
+    }
+  }
+
+  struct Connection {
+    peer: &'static str,
+    seen: u32,
+  }
+
+  struct Service;
+
+  impl ContextMsgHandler<Connection> for Service {
+    type Output = String;
+
+    fn handle_protocol(&mut self, ctx: &mut Connection, msg: Protocol) -> String {
+      ctx.seen += 1;
+      format!("{} sent {msg:?}", ctx.peer)
+    }
+
+    fn handle_business(&mut self, ctx: &mut Connection, _: Business) -> String {
+      ctx.seen += 1;
+      format!("{} sent business", ctx.peer)
+    }
+  }
+
+  impl ContextMsgTryHandler<Vec<i32>> for Service {
+    type Output = ();
+    type Error = ();
+
+    fn handle_protocol(&mut self, ctx: &mut Vec<i32>, msg: Protocol) -> Result<(), ()> {
+      let Protocol::A(a) = msg;
+      ctx.push(a.value);
+      Ok(())
+    }
+
+    fn handle_business(&mut self, _: &mut Vec<i32>, _: Business) -> Result<(), ()> {
+      Err(())
+    }
+  }
+
+  let mut conn = Connection { peer: "10.0.0.1", seen: 0 };
+  assert_eq!(
+    ContextMsg::C(MsgC { flag: true }).dispatch(&mut Service, &mut conn),
+    "10.0.0.1 sent business"
+  );
+  assert_eq!(conn.seen, 1);
+
+  let mut values = Vec::new();
+  assert_eq!(ContextMsg::A(MsgA { value: 5 }).try_dispatch(&mut Service, &mut values), Ok(()));
+  assert_eq!(values, vec![5]);
+}
+
+/// Test: `variant_handler` dispatches payloads to one method per variant.
+#[test]
+fn test_variant_handler() {
+  define_enum_group! {
+    #[enum_group(variant_handler)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum StrictMsg {
+      Protocol {
+        A(MsgA),
+        B(MsgB),
+      },
+      Business {
+        C(MsgC),
+      }
+    }
+  }
+
+  struct Describe;
+
+  impl StrictMsgVariantHandler for Describe {
+    type Output = String;
+
+    fn handle_a(&mut self, msg: MsgA) -> String {
+      format!("a={}", msg.value)
+    }
+
+    fn handle_b(&mut self, msg: MsgB) -> String {
+      format!("b={}", msg.text)
+    }
+
+    fn handle_c(&mut self, msg: MsgC) -> String {
+      format!("c={}", msg.flag)
+    }
+  }
+
+  assert_eq!(StrictMsg::A(MsgA { value: 7 }).dispatch_variant(&mut Describe), "a=7");
+  assert_eq!(StrictMsg::B(MsgB { text: "x".into() }).dispatch_variant(&mut Describe), "b=x");
+  assert_eq!(StrictMsg::C(MsgC { flag: false }).dispatch_variant(&mut Describe), "c=false");
+}
+
+/// Test: `queues` buffers messages per group.
+///
+/// Verifies `push` routes by group, `pop_*` preserve FIFO order and `pop` prefers groups
+/// declared first.
+#[test]
+fn test_queues() {
+  define_enum_group! {
+    #[enum_group(queues)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum QueuedMsg {
+      Control {
+        A(MsgA),
+      },
+      Data {
+        B(MsgB),
+        C(MsgC),
+      }
+    }
+  }
+
+  let mut queues = QueuedMsgQueues::new();
+  queues.push(QueuedMsg::B(MsgB { text: "first".into() }));
+  queues.push(QueuedMsg::C(MsgC { flag: true }));
+  queues.push(QueuedMsg::A(MsgA { value: 1 }));
+  assert_eq!(queues.len(), 3);
+  assert_eq!(queues.data.len(), 2);
+
+  assert!(matches!(queues.pop(), Some(QueuedMsgGroup::Control(Control::A(_)))));
+  assert_eq!(queues.pop_control(), None);
+  assert_eq!(queues.pop_data(), Some(Data::B(MsgB { text: "first".into() })));
+  assert!(matches!(queues.pop(), Some(QueuedMsgGroup::Data(Data::C(_)))));
+  assert!(queues.is_empty());
+}
+
+/// Test: `filter` parses subscription filters and evaluates them against messages.
+///
+/// Verifies group wildcards, single variants, negation and error reporting.
+#[test]
+fn test_filter() {
+  use enum_group_macros::FilterError;
+
+  define_enum_group! {
+    #[enum_group(filter)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum FilteredMsg {
+      Protocol {
+        A(MsgA),
+        B(MsgB),
+      },
+      Business {
+        C(MsgC),
+        D(MsgD),
+      }
+    }
+  }
+
+  let a = FilteredMsg::A(MsgA { value: 1 });
+  let b = FilteredMsg::B(MsgB { text: String::new() });
+  let c = FilteredMsg::C(MsgC { flag: true });
+  let d = FilteredMsg::D(MsgD { data: Vec::new() });
+  let selected = |filter: &str| {
+    let filter = FilteredMsg::parse_filter(filter).unwrap();
+    [&a, &b, &c, &d].map(|msg| filter.matches(msg))
+  };
+
+  assert_eq!(selected("Protocol.*"), [true, true, false, false]);
+  assert_eq!(selected("Protocol.*, Business.C"), [true, true, true, false]);
+  assert_eq!(selected("Protocol !Protocol.B"), [true, false, false, false]);
+  assert_eq!(selected("!Business.D"), [true, true, true, false]);
+  assert_eq!(selected(""), [true, true, true, true]);
+  assert!("*".parse::<FilteredMsgFilter>().unwrap().matches_kind(FilteredMsgKind::D));
+
+  assert_eq!(FilteredMsg::parse_filter("Admin.*"), Err(FilterError::UnknownGroup("Admin".into())));
+  assert_eq!(
+    FilteredMsg::parse_filter("Protocol.C"),
+    Err(FilterError::UnknownVariant("Protocol.C".into()))
+  );
+  assert_eq!(FilteredMsg::parse_filter("Protocol."), Err(FilterError::Syntax("Protocol.".into())));
+}
+
+/// Test: `Copy` on all-`Copy` groups.
+///
+/// Verifies `#[group(copy)]` makes a single group enum `Copy`, and that deriving `Copy` on
+/// the wire enum extends to the group and dispatch enums.
+#[test]
+fn test_copy_groups() {
+  fn assert_copy<T: Copy>(value: T) -> (T, T) {
+    (value, value)
+  }
+
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum MixedCopyMsg {
+      #[group(copy)]
+      Control {
+        Start(u32),
+        Stop(()),
+      },
+      Data {
+        Chunk(Vec<u8>),
+      }
+    }
+  }
+
+  define_enum_group! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    enum CopyMsg {
+      #[group(copy)]
+      Ticks {
+        Tick(u64),
+      },
+      Flags {
+        Set(bool),
+      }
+    }
+  }
+
+  let (a, b) = assert_copy(Control::Start(1));
+  assert!(matches!((a, b), (Control::Start(1), Control::Start(1))));
+  assert!(matches!(MixedCopyMsg::Chunk(vec![1]).into_group(), MixedCopyMsgGroup::Data(_)));
+
+  let (msg, _) = assert_copy(CopyMsg::Set(true));
+  let (group, _) = assert_copy(msg.into_group());
+  assert!(matches!(group, CopyMsgGroup::Flags(Flags::Set(true))));
+  assert_copy(Ticks::Tick(1));
+}
+
+/// Test: group constants list every group in declaration order.
+#[test]
+fn test_group_constants() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum CountedMsg {
+      Protocol {
+        A(MsgA),
+      },
+      Business {
+        B(MsgB),
+        C(MsgC),
+      },
+      r#Ops {
+        D(MsgD),
+      }
+    }
+  }
+
+  assert_eq!(CountedMsg::GROUP_COUNT, 3);
+  assert_eq!(
+    CountedMsgGroupKind::ALL,
+    &[CountedMsgGroupKind::Protocol, CountedMsgGroupKind::Business, CountedMsgGroupKind::Ops]
+  );
+  assert_eq!(CountedMsg::group_names(), &["Protocol", "Business", "Ops"]);
+  for (kind, name) in CountedMsgGroupKind::ALL.iter().zip(CountedMsg::group_names()) {
+    assert_eq!(format!("{:?}", kind), *name);
+  }
+}
+
+/// Test: variant and group counts and names are usable as array lengths and static tables.
+///
+/// Verifies variants configured away are not counted, on a generic wire enum too.
+#[test]
+fn test_count_constants() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum SizedMsg<T> {
+      Protocol {
+        A(MsgA),
+        #[cfg(any())]
+        Gone(MsgB),
+      },
+      Business {
+        Generic(T),
+        r#Ping,
+      }
+    }
+  }
+
+  const ROUTES: [&str; SizedMsg::<()>::VARIANT_COUNT] = SizedMsg::<()>::VARIANT_NAMES;
+  assert_eq!(ROUTES, ["A", "Generic", "Ping"]);
+  let per_group = [0u32; SizedMsg::<()>::GROUP_COUNT];
+  assert_eq!(per_group.len(), 2);
+  assert_eq!(SizedMsg::<u8>::GROUP_NAMES, ["Protocol", "Business"]);
+  assert_eq!(SizedMsg::<u8>::GROUP_NAMES, SizedMsg::<u8>::group_names());
+}
+
+/// Test: `handlers` runs the closure of a message's group or the fallback.
+///
+/// Verifies closures borrowing local state, the `T::default()` fallback of `new()` and a
+/// custom fallback set with `otherwise()`.
+#[test]
+fn test_closure_handlers() {
+  define_enum_group! {
+    #[enum_group(handlers)]
+    #[derive(Debug, Clone)]
+    enum ClosureMsg {
+      Control {
+        Start(MsgA),
+      },
+      Data {
+        Chunk(MsgD),
+      },
+      Audit {
+        Note(MsgB),
+      }
+    }
+  }
+
+  let mut starts = 0;
+  {
+    let mut handlers = ClosureMsgHandlers::new().on_control(|_| starts += 1);
+    handlers.handle(ClosureMsg::Start(MsgA { value: 1 }));
+    handlers.handle(ClosureMsg::Chunk(MsgD { data: vec![1] }));
+    handlers.handle(ClosureMsg::Start(MsgA { value: 2 }));
+  }
+  assert_eq!(starts, 2);
+
+  let mut handlers = ClosureMsgHandlers::otherwise(|msg| {
+    format!("ignored {:?}", enum_group_macros::EnumGroupKind::kind(&msg))
+  })
+  .on_data(|Data::Chunk(d)| format!("{} bytes", d.data.len()));
+  assert_eq!(handlers.handle(ClosureMsg::Chunk(MsgD { data: vec![1, 2] })), "2 bytes");
+  assert_eq!(handlers.handle(ClosureMsg::Note(MsgB { text: "x".into() })), "ignored Note");
+}
+
+/// Test: `partial_handler` methods default to `handle_unhandled`.
+#[test]
+fn test_partial_handler() {
+  define_enum_group! {
+    #[enum_group(partial_handler)]
+    #[derive(Debug, Clone)]
+    enum PartialMsg {
+      Control {
+        Start(MsgA),
+      },
+      Data {
+        Chunk(MsgD),
+      }
+    }
+  }
+
+  struct ChunkCounter {
+    bytes: usize,
+    ignored: Vec<PartialMsgKind>,
+  }
+
+  impl PartialMsgPartialHandler for ChunkCounter {
+    type Output = ();
+
+    fn handle_unhandled(&mut self, msg: PartialMsg) {
+      self.ignored.push(enum_group_macros::EnumGroupKind::kind(&msg));
+    }
+
+    fn handle_data(&mut self, Data::Chunk(d): Data) {
+      self.bytes += d.data.len();
+    }
+  }
+
+  let mut counter = ChunkCounter { bytes: 0, ignored: Vec::new() };
+  PartialMsg::Chunk(MsgD { data: vec![1, 2, 3] }).dispatch_partial(&mut counter);
+  PartialMsg::Start(MsgA { value: 1 }).dispatch_partial(&mut counter);
+  assert_eq!(counter.bytes, 3);
+  assert_eq!(counter.ignored, vec![PartialMsgKind::Start]);
+}
+
+/// Test: `kind_map` generates an array-backed map keyed by kind.
+#[test]
+fn test_kind_map() {
+  define_enum_group! {
+    #[enum_group(kind_map)]
+    #[derive(Debug, Clone)]
+    enum MappedMsg {
+      Control {
+        Start(MsgA),
+        Stop(MsgB),
+      },
+      Data {
+        Chunk(MsgD),
+      }
+    }
+  }
+
+  let mut counts = MappedMsgKindMap::<u32>::new();
+  assert!(counts.is_empty());
+  assert_eq!(counts.insert(MappedMsgKind::Chunk, 1), None);
+  assert_eq!(counts.insert(MappedMsgKind::Start, 2), None);
+  assert_eq!(counts.insert(MappedMsgKind::Chunk, 3), Some(1));
+  *counts.get_mut(MappedMsgKind::Start).unwrap() += 10;
+
+  assert_eq!(counts.len(), 2);
+  assert_eq!(counts.get(MappedMsgKind::Start), Some(&12));
+  assert_eq!(counts.get(MappedMsgKind::Stop), None);
+  assert!(counts.contains_key(MappedMsgKind::Chunk));
+  assert_eq!(
+    counts.iter().collect::<Vec<_>>(),
+    vec![(MappedMsgKind::Start, &12), (MappedMsgKind::Chunk, &3)]
+  );
+  assert_eq!(format!("{:?}", counts), "{Start: 12, Chunk: 3}");
+
+  assert_eq!(counts.remove(MappedMsgKind::Start), Some(12));
+  let collected: MappedMsgKindMap<u32> = [(MappedMsgKind::Chunk, 3)].into_iter().collect();
+  assert_eq!(counts, collected);
+}
+
+/// Test: `group_index_fast()` agrees with `group_kind()` for every variant.
+///
+/// Verifies both ways of giving the wire enum an integer representation.
+#[test]
+fn test_group_index_table() {
+  define_enum_group! {
+    #[enum_group(group_index_table)]
+    #[derive(Debug, Clone)]
+    #[repr(u8)]
+    enum IndexedMsg {
+      Control {
+        Start(MsgA),
+        Stop(MsgB),
+      },
+      Data {
+        Chunk(MsgD),
+      },
+      Audit {
+        Note(MsgB),
+        Trace(MsgC),
+      }
+    }
+  }
+
+  define_enum_group! {
+    #[enum_group(group_index_table, wire_attr(repr(u32)))]
+    #[derive(Debug, Clone)]
+    enum WideIndexedMsg {
+      Only {
+        One(MsgA),
+      },
+      Other {
+        Two(MsgB),
+      }
+    }
+  }
+
+  assert_eq!(IndexedMsg::GROUP_INDEX_TABLE, [0, 0, 1, 2, 2]);
+  let msgs = [
+    IndexedMsg::Start(MsgA { value: 1 }),
+    IndexedMsg::Stop(MsgB { text: "stop".into() }),
+    IndexedMsg::Chunk(MsgD { data: vec![] }),
+    IndexedMsg::Note(MsgB { text: "note".into() }),
+    IndexedMsg::Trace(MsgC { flag: true }),
+  ];
+  for msg in &msgs {
+    let expected = IndexedMsgGroupKind::ALL
+      .iter()
+      .position(|kind| *kind == enum_group_macros::EnumGroupKind::group_kind(msg));
+    assert_eq!(Some(msg.group_index_fast()), expected);
+  }
+
+  assert_eq!(WideIndexedMsg::Two(MsgB { text: String::new() }).group_index_fast(), 1);
+  assert_eq!(WideIndexedMsg::One(MsgA { value: 0 }).group_index_fast(), 0);
+}
+
+/// Test: `skip_dispatch` leaves a group out of the dispatch enum.
+///
+/// Verifies its messages become `Undispatched`, convert back unchanged, and reach the
+/// fallback paths of the router, the closure handlers, the queues and the handler trait,
+/// and that the `Undispatched` variant gets a discriminant of its own.
+#[test]
+fn test_skip_dispatch() {
+  use enum_group_macros::RouteError;
+
+  define_enum_group! {
+    #[enum_group(router, handlers, queues, handler)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum DiagMsg {
+      Control = 1 {
+        Start(MsgA),
+      },
+      #[group(skip_dispatch)]
+      Diagnostics = 7 {
+        Probe(MsgC),
+      },
+      Data = 2 {
+        Chunk(MsgD),
+      }
+    }
+  }
+
+  let probe = || DiagMsg::Probe(MsgC { flag: true });
+  let DiagMsgGroup::Undispatched(msg) = probe().into_group() else {
+    panic!("diagnostics are not dispatched");
+  };
+  assert_eq!(msg, probe());
+  assert_eq!(<DiagMsg as EnumGroup>::from_group(probe().into_group()), probe());
+  assert_eq!(probe().into_group().group_discriminant(), 7);
+  // The group still exists everywhere else
+  assert_eq!(enum_group_macros::EnumGroupKind::group_kind(&probe()), DiagMsgGroupKind::Diagnostics);
+  assert_eq!(Diagnostics::Probe(MsgC { flag: false }), Diagnostics::Probe(MsgC { flag: false }));
+
+  let mut router =
+    DiagMsgRouter::<(), u8, ()>::new().on_control(|_, _| Ok(1)).on_data(|_, _| Ok(2));
+  assert_eq!(router.route(&mut (), DiagMsg::Start(MsgA { value: 0 })), Ok(1));
+  assert_eq!(router.route(&mut (), probe()), Err(RouteError::Unhandled(probe())));
+
+  let mut handlers = DiagMsgHandlers::otherwise(|msg| format!("fallback {:?}", msg))
+    .on_control(|_| "control".to_string());
+  assert_eq!(handlers.handle(probe()), "fallback Probe(MsgC { flag: true })");
+
+  let mut queues = DiagMsgQueues::new();
+  queues.push(probe());
+  queues.push(DiagMsg::Chunk(MsgD { data: vec![] }));
+  assert_eq!(queues.len(), 2);
+  assert!(matches!(queues.pop(), Some(DiagMsgGroup::Data(_))));
+  assert_eq!(queues.pop_undispatched(), Some(probe()));
+  assert!(queues.is_empty());
+
+  struct Counter(usize);
+
+  impl DiagMsgHandler for Counter {
+    type Output = &'static str;
+
+    fn handle_control(&mut self, _: Control) -> &'static str {
+      "control"
+    }
+
+    fn handle_data(&mut self, _: Data) -> &'static str {
+      "data"
+    }
+
+    fn handle_undispatched(&mut self, _: DiagMsg) -> &'static str {
+      self.0 += 1;
+      "undispatched"
+    }
+  }
+
+  let mut counter = Counter(0);
+  assert_eq!(probe().dispatch(&mut counter), "undispatched");
+  assert_eq!(DiagMsg::Chunk(MsgD { data: vec![1] }).dispatch(&mut counter), "data");
+  assert_eq!(counter.0, 1);
+}
+
+/// Test: `on_unexpected` hooks see every message bypassing its group's handler.
+///
+/// Verifies the reason reported by the router, the closure handlers and the partial
+/// handler for groups without handler and groups left out of dispatch.
+#[test]
+fn test_on_unexpected() {
+  use enum_group_macros::{RouteError, UnexpectedReason};
+  use std::cell::RefCell;
+  use std::rc::Rc;
+
+  define_enum_group! {
+    #[enum_group(router, handlers, partial_handler)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum WatchedMsg {
+      Control {
+        Start(MsgA),
+      },
+      #[group(skip_dispatch)]
+      Diagnostics {
+        Probe(MsgC),
+      },
+      Data {
+        Chunk(MsgD),
+      }
+    }
+  }
+
+  let start = || WatchedMsg::Start(MsgA { value: 1 });
+  let probe = || WatchedMsg::Probe(MsgC { flag: true });
+  let chunk = || WatchedMsg::Chunk(MsgD { data: vec![1] });
+
+  let seen = Rc::new(RefCell::new(Vec::new()));
+  let log = seen.clone();
+  let mut router = WatchedMsgRouter::<(), (), ()>::new()
+    .on_data(|_, _| Ok(()))
+    .on_unexpected(move |msg, reason| log.borrow_mut().push((msg.clone(), reason)));
+  assert_eq!(router.route(&mut (), chunk()), Ok(()));
+  assert_eq!(router.route(&mut (), start()), Err(RouteError::Unhandled(start())));
+  assert_eq!(router.route(&mut (), probe()), Err(RouteError::Unhandled(probe())));
+  assert_eq!(
+    seen.take(),
+    vec![(start(), UnexpectedReason::NoHandler), (probe(), UnexpectedReason::SkippedGroup)]
+  );
+
+  let mut reasons = Vec::new();
+  {
+    let mut handlers =
+      WatchedMsgHandlers::new().on_control(|_| ()).on_unexpected(|_, reason| reasons.push(reason));
+    handlers.handle(start());
+    handlers.handle(chunk());
+    handlers.handle(probe());
+  }
+  assert_eq!(reasons, vec![UnexpectedReason::NoHandler, UnexpectedReason::SkippedGroup]);
+
+  struct Watcher(Vec<(WatchedMsgKind, UnexpectedReason)>);
+
+  impl WatchedMsgPartialHandler for Watcher {
+    type Output = bool;
+
+    fn handle_unhandled(&mut self, _: WatchedMsg) -> bool {
+      false
+    }
+
+    fn on_unexpected(&mut self, msg: &WatchedMsg, reason: UnexpectedReason) {
+      self.0.push((enum_group_macros::EnumGroupKind::kind(msg), reason));
+    }
+
+    fn handle_control(&mut self, _: Control) -> bool {
+      true
+    }
+  }
+
+  let mut watcher = Watcher(Vec::new());
+  assert!(start().dispatch_partial(&mut watcher));
+  assert!(!chunk().dispatch_partial(&mut watcher));
+  assert!(!probe().dispatch_partial(&mut watcher));
+  assert_eq!(
+    watcher.0,
+    vec![
+      (WatchedMsgKind::Chunk, UnexpectedReason::NoHandler),
+      (WatchedMsgKind::Probe, UnexpectedReason::SkippedGroup)
+    ]
+  );
+}
+
+/// Test: `payload_any` erases and recovers payload types.
+///
+/// Verifies `into_payload_any()` pairs the kind with a downcastable payload, and that
+/// `payload_downcast()` returns the message unchanged on a type mismatch.
+#[test]
+fn test_payload_any() {
+  define_enum_group! {
+    #[enum_group(payload_any)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum AnyMsg {
+      Control {
+        Start(MsgA),
+        Stop(MsgB),
+      }
+    }
+  }
+
+  let (kind, payload) = AnyMsg::Start(MsgA { value: 3 }).into_payload_any();
+  assert_eq!(kind, AnyMsgKind::Start);
+  assert_eq!(payload.downcast::<MsgA>().ok().map(|a| a.value), Some(3));
+
+  let stop = AnyMsg::Stop(MsgB { text: "x".to_string() });
+  assert_eq!(stop.clone().payload_downcast::<MsgA>(), Err(stop.clone()));
+  assert_eq!(stop.payload_downcast::<MsgB>(), Ok(MsgB { text: "x".to_string() }));
+}
+
+/// Test: `#[variant(boxed)]` stores the payload in a `Box` on the wire and group enums.
+///
+/// Verifies the enums stay small while the conversions, `examples`, `payload_any` and the
+/// variant handler still take and return the payload itself.
+#[test]
+fn test_boxed_variants() {
+  use enum_group_macros::VariantOf;
+
+  #[derive(Debug, Clone, Default, PartialEq)]
+  struct Snapshot {
+    cells: [[u64; 8]; 8],
+  }
+
+  define_enum_group! {
+    #[enum_group(examples, payload_any, variant_handler)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum StateMsg {
+      Sync {
+        #[variant(boxed)]
+        Full(Snapshot),
+        Delta(u64),
+      }
+    }
+  }
+
+  struct Sizes;
+  impl StateMsgVariantHandler for Sizes {
+    type Output = usize;
+    fn handle_full(&mut self, msg: Snapshot) -> usize {
+      msg.cells.len() * 8
+    }
+    fn handle_delta(&mut self, _: u64) -> usize {
+      1
+    }
+  }
+
+  assert!(std::mem::size_of::<StateMsg>() <= 16);
+  assert!(std::mem::size_of::<Sync>() <= 16);
+  let full = Snapshot::default().into_wire();
+  assert_eq!(full, StateMsg::Full(Box::default()));
+  assert!(matches!(full.clone().into_group(), StateMsgGroup::Sync(Sync::Full(_))));
+  assert_eq!(Snapshot::try_from_wire(full.clone()), Ok(Snapshot::default()));
+  assert_eq!(StateMsg::examples()[0], full);
+  assert_eq!(full.clone().dispatch_variant(&mut Sizes), 64);
+
+  assert_eq!(full.clone().payload_downcast::<u64>(), Err(full.clone()));
+  assert_eq!(full.clone().payload_downcast::<Snapshot>(), Ok(Snapshot::default()));
+  let (_, payload) = full.into_payload_any();
+  assert!(payload.downcast::<Snapshot>().is_ok());
+}
+
+// =============================================================================
+// Section K: Variant Metadata
+// =============================================================================
+
+/// Test: `#[variant(timeout_ms, retries)]` feed the accessors and the metadata table.
+///
+/// Verifies defaults for variants without options, and that the option attribute is not
+/// propagated to the generated enums (it would be an unknown attribute there).
+#[test]
+fn test_variant_timeout_and_retries() {
+  use std::time::Duration;
+
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum RpcMsg {
+      Calls {
+        #[variant(timeout_ms = 500, retries = 3)]
+        Fetch(MsgA),
+        #[variant(retries = 1)]
+        Store(MsgB),
+      },
+      Notifications {
+        Ping(MsgC),
+      }
+    }
+  }
+
+  let fetch = RpcMsg::Fetch(MsgA { value: 1 });
+  let store = RpcMsg::Store(MsgB { text: "x".to_string() });
+  let ping = RpcMsg::Ping(MsgC { flag: true });
+
+  assert_eq!(fetch.timeout(), Some(Duration::from_millis(500)));
+  assert_eq!(fetch.max_retries(), 3);
+  assert_eq!(store.timeout(), None);
+  assert_eq!(store.max_retries(), 1);
+  assert_eq!(ping.timeout(), None);
+  assert_eq!(ping.max_retries(), 0);
+
+  assert_eq!(RpcMsg::METADATA.len(), 3);
+  assert_eq!(
+    *fetch.metadata(),
+    enum_group_macros::VariantMetadata {
+      name: "Fetch",
+      group: "Calls",
+      tag: "Fetch",
+      timeout_ms: Some(500),
+      max_retries: 3,
+      requires_auth: false,
+      required_role: None,
+      meta: &[],
+    }
+  );
+  assert_eq!(ping.metadata().group, "Notifications");
+}
+
+/// Test: `#[group(requires_auth)]` and `#[group(role = ...)]` feed the auth accessors.
+///
+/// Verifies that a role implies authentication and that unannotated groups are open.
+#[test]
+fn test_group_auth_metadata() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum GatewayMsg {
+      #[group(role = "admin")]
+      Admin {
+        Shutdown(MsgA),
+      },
+      #[group(requires_auth)]
+      Account {
+        Profile(MsgB),
+      },
+      Public {
+        Ping(MsgC),
+      }
+    }
+  }
+
+  let shutdown = GatewayMsg::Shutdown(MsgA { value: 0 });
+  let profile = GatewayMsg::Profile(MsgB { text: "me".to_string() });
+  let ping = GatewayMsg::Ping(MsgC { flag: true });
+
+  assert!(shutdown.requires_auth());
+  assert_eq!(shutdown.required_role(), Some("admin"));
+  assert!(profile.requires_auth());
+  assert_eq!(profile.required_role(), None);
+  assert!(!ping.requires_auth());
+  assert_eq!(ping.required_role(), None);
+}
+
+/// Test: `#[meta(key, value)]` on groups and variants.
+///
+/// Verifies lookups fall back to the group, variant entries override group entries, and
+/// the merged pairs are available in the const metadata table.
+#[test]
+fn test_meta_attributes() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum QueueMsg {
+      #[meta(key = "queue", value = "bulk")]
+      #[meta(key = "owner", value = "ingest")]
+      Bulk {
+        Upload(MsgD),
+        #[meta(key = "queue", value = "priority")]
+        Urgent(MsgA),
+      },
+      Control {
+        Ping(MsgC),
+      }
+    }
+  }
+
+  let upload = QueueMsg::Upload(MsgD { data: vec![] });
+  let urgent = QueueMsg::Urgent(MsgA { value: 1 });
+  let ping = QueueMsg::Ping(MsgC { flag: true });
+
+  assert_eq!(upload.meta("queue"), Some("bulk"));
+  assert_eq!(upload.meta("owner"), Some("ingest"));
+  assert_eq!(urgent.meta("queue"), Some("priority"));
+  assert_eq!(urgent.meta("owner"), Some("ingest"));
+  assert_eq!(ping.meta("queue"), None);
+
+  assert_eq!(QueueMsg::METADATA[1].meta, &[("queue", "priority"), ("owner", "ingest")]);
+}
+
+/// Test: `examples` builds one default instance per variant.
+///
+/// Verifies every variant appears exactly once, in declaration order.
+#[test]
+fn test_examples_cover_every_variant() {
+  #[derive(Debug, Clone, Default, PartialEq)]
+  struct Settings {
+    level: u8,
+  }
+
+  define_enum_group! {
+    #[enum_group(examples)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum ExampleMsg {
+      Config {
+        Apply(Settings),
+      },
+      Data {
+        Count(u64),
+        Names(Vec<String>),
+      }
+    }
+  }
+
+  assert_eq!(
+    ExampleMsg::examples(),
+    vec![
+      ExampleMsg::Apply(Settings::default()),
+      ExampleMsg::Count(0),
+      ExampleMsg::Names(Vec::new())
+    ]
+  );
+}
+
+/// Test: `examples(cfg(...))` puts the generated function behind the given predicate.
+///
+/// Verifies an always-true predicate keeps the function, while a never-true one removes it
+/// entirely so payloads without `Default` are accepted.
+#[test]
+fn test_examples_behind_cfg() {
+  define_enum_group! {
+    #[enum_group(examples(cfg(all())))]
+    #[derive(Debug, Clone)]
+    enum EnabledExampleMsg {
+      Data {
+        Count(u64),
+      }
+    }
+  }
+
+  define_enum_group! {
+    #[enum_group(examples(cfg(any())))]
+    #[derive(Debug, Clone)]
+    enum DisabledExampleMsg {
+      Data2 {
+        NoDefault(MsgA),
+      }
+    }
+  }
+
+  assert_eq!(EnabledExampleMsg::examples().len(), 1);
+}
+
+/// Test: Legacy variants are converted into their replacement.
+///
+/// Verifies the legacy form still deserializes, and that `normalize()` and `into_group()`
+/// convert it through `From`, across groups.
+#[test]
+fn test_legacy_of() {
+  use serde::{Deserialize, Serialize};
+
+  #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+  struct LoginV1 {
+    user: String,
+  }
+
+  #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+  struct LoginV2 {
+    user: String,
+    token: Option<String>,
+  }
+
+  impl From<LoginV1> for LoginV2 {
+    fn from(v1: LoginV1) -> Self {
+      LoginV2 { user: v1.user, token: None }
+    }
+  }
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum LegacyMsg {
+      Deprecated {
+        #[variant(legacy_of = Login)]
+        LoginOld(LoginV1),
+      },
+      Auth {
+        Login(LoginV2),
+        Logout(()),
+      }
+    }
+  }
+
+  let old: LegacyMsg = serde_json::from_str(r#"{"LoginOld":{"user":"ann"}}"#).unwrap();
+  let new = LegacyMsg::Login(LoginV2 { user: "ann".to_string(), token: None });
+  assert_eq!(old.clone().normalize(), new);
+  assert_eq!(new.clone().normalize(), new);
+
+  match old.into_group() {
+    LegacyMsgGroup::Auth(Auth::Login(login)) => assert_eq!(login.user, "ann"),
+    other => panic!("legacy message was not normalized: {other:?}"),
+  }
+  let logout = LegacyMsg::Logout(());
+  assert_eq!(logout.clone().normalize(), logout);
+}
+
+// =============================================================================
+// Section L: Third-Party Derives
+// =============================================================================
+
+/// Test: strum derives on every generated enum.
+///
+/// Verifies the outer derive list works on the wire and group enums, and that
+/// `dispatch_attr` / `kind_attr` add derives and strum attributes to the other enums.
+#[test]
+fn test_strum_derives() {
+  use std::str::FromStr;
+  use strum::{EnumDiscriminants, EnumIter, EnumString, IntoEnumIterator, IntoStaticStr};
+
+  #[derive(Debug, Clone, Default, PartialEq)]
+  struct Tick;
+
+  define_enum_group! {
+    #[enum_group(
+      dispatch_attr(derive(IntoStaticStr), strum(serialize_all = "snake_case")),
+      kind_attr(derive(EnumIter)),
+    )]
+    #[derive(Debug, Clone, PartialEq, EnumIter, EnumString, IntoStaticStr, EnumDiscriminants)]
+    #[strum(serialize_all = "snake_case")]
+    enum StrumMsg {
+      ClockEvents {
+        Tick(Tick),
+        Tock(Tick),
+      },
+      Data {
+        Value(u32),
+      }
+    }
+  }
+
+  // Wire enum
+  let name: &'static str = StrumMsg::Value(1).into();
+  assert_eq!(name, "value");
+  assert_eq!(StrumMsg::from_str("tock"), Ok(StrumMsg::Tock(Tick)));
+  assert_eq!(StrumMsg::iter().count(), 3);
+  assert_eq!(StrumMsgDiscriminants::from(&StrumMsg::Tick(Tick)), StrumMsgDiscriminants::Tick);
+
+  // Group enums
+  assert_eq!(ClockEvents::iter().count(), 2);
+  let name: &'static str = Data::Value(2).into();
+  assert_eq!(name, "value");
+
+  // Dispatch enum
+  let group: &'static str = StrumMsg::Tick(Tick).into_group().into();
+  assert_eq!(group, "clock_events");
+
+  // Kind enum
+  assert_eq!(
+    StrumMsgKind::iter().collect::<Vec<_>>(),
+    vec![StrumMsgKind::Tick, StrumMsgKind::Tock, StrumMsgKind::Value]
+  );
+}
+
+// =============================================================================
+// Section M: Recursive Payloads
+// =============================================================================
+
+/// Test: payloads may hold the wire enum behind indirection.
+///
+/// Verifies batch and envelope messages convert and match like any other payload.
+#[test]
+fn test_recursive_payloads() {
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    enum EnvelopeMsg {
+      Envelope {
+        Batch(Vec<EnvelopeMsg>),
+        Wrapped(Box<EnvelopeMsg>),
+        Reply(Option<Box<EnvelopeMsg>>),
+      },
+      Data {
+        A(MsgA),
+        Nested(Box<Data>),
+      }
+    }
+  }
+
+  let msg = EnvelopeMsg::Batch(vec![
+    EnvelopeMsg::A(MsgA { value: 1 }),
+    EnvelopeMsg::Wrapped(Box::new(EnvelopeMsg::Nested(Box::new(Data::A(MsgA { value: 2 }))))),
+  ]);
+  let EnvelopeMsgGroup::Envelope(Envelope::Batch(inner)) = msg.into_group() else {
+    panic!("expected a batch");
+  };
+  assert_eq!(inner.len(), 2);
+  assert!(matches!(
+    inner[1].clone().into_group(),
+    EnvelopeMsgGroup::Envelope(Envelope::Wrapped(_))
+  ));
+}
+
+// =============================================================================
+// Section N: Shared Groups
+// =============================================================================
+
+/// Test: one invocation defines several wire enums sharing a group block.
+///
+/// Verifies the shared group enum is defined once, converts to and from both wire enums,
+/// and takes its own attributes, options and per-wire discriminants.
+#[test]
+fn test_shared_groups() {
+  use enum_group_macros::GroupOf;
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    #[group(requires_auth)]
+    group Common {
+      Ping(MsgA),
+      Pong(MsgB),
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum ClientMsg {
+      use Common = 1,
+      Requests = 2 {
+        Fetch(MsgC),
+      },
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum ServerMsg {
+      Events = 1 {
+        Update(MsgD),
+      },
+      use Common = 9,
+    }
+  }
+
+  let ping = || Common::Ping(MsgA { value: 1 });
+  let client: ClientMsg = ping().into_wire();
+  let server: ServerMsg = ping().into_wire();
+  assert_eq!(client, ClientMsg::Ping(MsgA { value: 1 }));
+  assert_eq!(server, ServerMsg::Ping(MsgA { value: 1 }));
+  assert!(matches!(client.clone().into_group(), ClientMsgGroup::Common(c) if c == ping()));
+  assert!(matches!(server.clone().into_group(), ServerMsgGroup::Common(c) if c == ping()));
+  assert_eq!(<Common as GroupOf<ServerMsg>>::try_from_wire(server.clone()), Ok(ping()));
+
+  assert_eq!(client.group_kind(), ClientMsgGroupKind::Common);
+  assert_eq!(ClientMsg::group_names(), &["Common", "Requests"]);
+  assert_eq!(ServerMsg::group_names(), &["Events", "Common"]);
+  assert!(client.requires_auth() && server.requires_auth());
+  assert!(!ClientMsg::Fetch(MsgC { flag: true }).requires_auth());
+  assert_eq!(client.group_discriminant(), 1);
+  assert_eq!(server.group_discriminant(), 9);
+}
@@ -0,0 +1,104 @@
+//! Tests for the `define_enum_group_pair!` macro.
+//!
+//! This file tests the two generated wire enums and the mappings between their groups.
+
+#![allow(dead_code)] // Generated enum variants are intentionally not fully used in tests
+
+use enum_group_macros::{define_enum_group_pair, match_enum_group};
+
+// =============================================================================
+// Test Helper Types
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpenAccount {
+  pub owner: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountOpened {
+  pub id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Charge {
+  pub cents: u64,
+}
+
+// =============================================================================
+// Shared Test Enum Definition
+// =============================================================================
+
+define_enum_group_pair! {
+  #[derive(Debug, Clone)]
+  pub enum Command {
+    Account {
+      Open(OpenAccount),
+    },
+    Billing {
+      Bill(Charge),
+      Refund(Charge),
+    }
+  }
+
+  #[derive(Debug, Clone)]
+  #[enum_group(group_tags)]
+  pub enum Event {
+    // Groups may be declared in a different order
+    Billing {
+      Charged(Charge),
+    },
+    Account {
+      Opened(AccountOpened),
+    }
+  }
+}
+
+// =============================================================================
+// Section A: Generated Enums
+// =============================================================================
+
+/// Test: Both wire enums get suffixed group enums and the usual conversions.
+///
+/// Verifies `into_group()` and `match_enum_group!` on either enum.
+#[test]
+fn test_pair_enums() {
+  let cmd = Command::Open(OpenAccount { owner: "ann".into() });
+  match cmd.into_group() {
+    CommandGroup::AccountCommand(AccountCommand::Open(open)) => assert_eq!(open.owner, "ann"),
+    other => panic!("unexpected group: {other:?}"),
+  }
+
+  let event = Event::Charged(Charge { cents: 5 });
+  let cents = match_enum_group!(event, Event, {
+    BillingEvent(billing) => match billing {
+      BillingEvent::Charged(charge) => charge.cents,
+    },
+    AccountEvent(_) => 0,
+  });
+  assert_eq!(cents, 5);
+  let _tag = BillingEventTag;
+}
+
+// =============================================================================
+// Section B: Group Mappings
+// =============================================================================
+
+/// Test: Corresponding groups map onto each other and onto the shared group kind.
+///
+/// Verifies `counterpart()` in both directions, the `From` conversions into the shared
+/// kind and the accessors back to either enum's group kind.
+#[test]
+fn test_pair_group_mapping() {
+  assert_eq!(CommandGroupKind::AccountCommand.counterpart(), EventGroupKind::AccountEvent);
+  assert_eq!(EventGroupKind::BillingEvent.counterpart(), CommandGroupKind::BillingCommand);
+
+  let cmd = Command::Refund(Charge { cents: 1 });
+  let shared = CommandEventGroupKind::from(cmd.group_kind());
+  assert_eq!(shared, CommandEventGroupKind::Billing);
+  assert_eq!(shared.event(), EventGroupKind::BillingEvent);
+  assert_eq!(shared.command(), CommandGroupKind::BillingCommand);
+
+  let event = Event::Opened(AccountOpened { id: 1 });
+  assert_eq!(CommandEventGroupKind::from(event.group_kind()), CommandEventGroupKind::Account);
+}
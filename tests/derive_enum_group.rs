@@ -0,0 +1,128 @@
+//! Tests for `#[derive(EnumGroup)]`.
+//!
+//! This file tests the items derived for an ordinary enum whose variants name their group.
+
+#![allow(dead_code)] // Generated enum variants are intentionally not fully used in tests
+
+use enum_group_macros::{match_enum_group, EnumGroup};
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// Test Helper Types
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MsgA {
+  pub value: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MsgB {
+  pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, EnumGroup)]
+pub enum WireMsg {
+  #[group(Protocol)]
+  A(MsgA),
+  #[group(Protocol)]
+  B(MsgB),
+  #[group(Business)]
+  Closed { code: u16 },
+  #[group(Business)]
+  Ping,
+}
+
+// =============================================================================
+// Section A: Generated Items
+// =============================================================================
+
+/// Test: the group enums and the dispatch enum are derived from the `#[group(...)]`
+/// attributes.
+///
+/// Verifies `into_group`, `from_group` and `match_enum_group!` on every variant shape.
+#[test]
+fn test_derived_groups() {
+  let msg = WireMsg::B(MsgB { text: "b".into() });
+  assert!(matches!(msg.clone().into_group(), WireMsgGroup::Protocol(Protocol::B(_))));
+  assert_eq!(WireMsg::from_group(msg.clone().into_group()), msg);
+
+  let closed = WireMsg::Closed { code: 7 };
+  assert!(matches!(
+    closed.clone().into_group(),
+    WireMsgGroup::Business(Business::Closed { code: 7 })
+  ));
+  let group = match_enum_group!(WireMsg::Ping, WireMsg, {
+    Protocol(_) => "protocol",
+    Business(Business::Ping) => "ping",
+    Business(_) => "business",
+  });
+  assert_eq!(group, "ping");
+}
+
+/// Test: the kinds follow the declaration order of the enum.
+#[test]
+fn test_derived_kinds() {
+  assert_eq!(WireMsgGroupKind::ALL, [WireMsgGroupKind::Protocol, WireMsgGroupKind::Business]);
+  assert_eq!(WireMsgGroupKind::Business.variants(), [WireMsgKind::Closed, WireMsgKind::Ping]);
+  assert_eq!(WireMsg::Ping.kind(), WireMsgKind::Ping);
+  assert_eq!(WireMsg::A(MsgA { value: 1 }).group_kind(), WireMsgGroupKind::Protocol);
+}
+
+// =============================================================================
+// Section B: Options And Attributes
+// =============================================================================
+
+/// Test: `#[enum_group(...)]` options apply, and `group_attr(derive(...))` adds derives
+/// to the group enums, which get the outer serde attributes.
+///
+/// Verifies the group enums serialize like the enum and the handler trait dispatches.
+#[test]
+fn test_derived_options() {
+  #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumGroup)]
+  #[enum_group(handler, group_attr(derive(PartialEq, Serialize, Deserialize)))]
+  #[serde(tag = "type", content = "payload", rename_all = "snake_case")]
+  enum TaggedMsg {
+    #[group(Alpha)]
+    #[serde(rename = "first")]
+    A(MsgA),
+    #[group(Beta)]
+    B(MsgB),
+  }
+
+  struct Names;
+  impl TaggedMsgHandler for Names {
+    type Output = &'static str;
+    fn handle_alpha(&mut self, _: Alpha) -> &'static str {
+      "alpha"
+    }
+    fn handle_beta(&mut self, _: Beta) -> &'static str {
+      "beta"
+    }
+  }
+
+  let msg = TaggedMsg::A(MsgA { value: 3 });
+  let json = serde_json::to_string(&msg).unwrap();
+  assert_eq!(json, r#"{"type":"first","payload":{"value":3}}"#);
+  assert_eq!(serde_json::to_string(&Alpha::A(MsgA { value: 3 })).unwrap(), json);
+  assert_eq!(TaggedMsg::TAG_TABLE[0].1, "first");
+  assert_eq!(msg.dispatch(&mut Names), "alpha");
+}
+
+/// Test: generic enums derive generic group enums and dispatch enum.
+#[test]
+fn test_derived_generics() {
+  #[derive(Debug, Clone, EnumGroup)]
+  enum FrameMsg<'a, T: Clone> {
+    #[group(Data)]
+    Borrowed(&'a str),
+    #[group(Data)]
+    Owned(T),
+    #[group(Control)]
+    Stop,
+  }
+
+  let msg: FrameMsg<'_, u8> = FrameMsg::Borrowed("frame");
+  assert!(matches!(msg.into_group(), FrameMsgGroup::Data(Data::Borrowed("frame"))));
+  assert!(matches!(FrameMsg::<u8>::Stop.into_group(), FrameMsgGroup::Control(Control::Stop)));
+}
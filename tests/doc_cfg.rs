@@ -0,0 +1,31 @@
+//! Tests for the `doc-cfg` feature.
+//!
+//! `#[doc(cfg(...))]` is nightly-only, so this file is compiled only with
+//! `cargo +nightly test --features doc-cfg`.
+
+#![cfg(feature = "doc-cfg")]
+#![feature(doc_cfg)]
+#![allow(dead_code)] // Generated enum variants are intentionally not fully used in tests
+
+use enum_group_macros::define_enum_group;
+
+#[derive(Debug, Clone)]
+pub struct MsgA;
+
+/// Test: cfg-gated variants carry a matching `doc(cfg)` attribute.
+///
+/// Verifies the generated attribute is accepted next to the propagated `#[cfg]`.
+#[test]
+fn test_doc_cfg_on_gated_variant() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum DocCfgMsg {
+      Alpha {
+        #[cfg(all())]
+        A(MsgA),
+      }
+    }
+  }
+
+  assert!(matches!(DocCfgMsg::A(MsgA).into_group(), DocCfgMsgGroup::Alpha(Alpha::A(_))));
+}
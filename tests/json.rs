@@ -0,0 +1,571 @@
+//! Tests for the helpers enabled by the `json` feature.
+//!
+//! Run with `cargo test --features json`.
+
+#![cfg(feature = "json")]
+#![allow(dead_code)] // Generated enum variants are intentionally not fully used in tests
+
+use enum_group_macros::{define_enum_group, PeekError, WireFormat};
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// Test Helper Types
+// =============================================================================
+
+/// Payload with a non-trivial default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Login {
+  pub user: String,
+  pub attempts: u32,
+}
+
+/// Payload serialized as a unit struct.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Logout;
+
+// =============================================================================
+// Section A: Sample JSON
+// =============================================================================
+
+/// Test: `sample_json` serializes a default message of every kind.
+///
+/// Verifies the output follows the wire enum's serde layout.
+#[test]
+fn test_sample_json() {
+  define_enum_group! {
+    #[enum_group(sample_json)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(tag = "type", content = "payload")]
+    enum SessionMsg {
+      Auth {
+        SignIn(Login),
+        SignOut(Logout),
+      },
+      Stats {
+        Count(u64),
+      }
+    }
+  }
+
+  assert_eq!(
+    SessionMsg::sample_json(SessionMsgKind::SignIn).as_deref(),
+    Some(r#"{"type":"SignIn","payload":{"user":"","attempts":0}}"#)
+  );
+  assert_eq!(
+    SessionMsg::sample_json(SessionMsgKind::SignOut).as_deref(),
+    Some(r#"{"type":"SignOut","payload":null}"#)
+  );
+  assert_eq!(
+    SessionMsg::sample_json(SessionMsgKind::Count).as_deref(),
+    Some(r#"{"type":"Count","payload":0}"#)
+  );
+}
+
+// =============================================================================
+// Section B: Peeking Kinds
+// =============================================================================
+
+define_enum_group! {
+  #[enum_group(peek_kind)]
+  #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+  enum ExternalMsg {
+    Auth {
+      SignIn(Login),
+      SignOut(Logout),
+    },
+    Stats {
+      Count(u64),
+    }
+  }
+}
+
+define_enum_group! {
+  #[enum_group(peek_kind)]
+  #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+  #[serde(tag = "type", rename_all = "snake_case")]
+  enum InternalMsg {
+    Session {
+      SignIn(Login),
+      SignOut(Logout),
+    }
+  }
+}
+
+/// Test: `peek_kind` reads the key of externally tagged JSON.
+#[test]
+fn test_peek_kind_external() {
+  let json = serde_json::to_vec(&ExternalMsg::SignIn(Login::default())).unwrap();
+  assert_eq!(ExternalMsg::peek_kind(&json, WireFormat::Json), Ok(ExternalMsgKind::SignIn));
+  assert_eq!(
+    ExternalMsg::peek_kind(br#"{"Reboot": {}}"#, WireFormat::Json),
+    Err(PeekError::UnknownTag("Reboot".into()))
+  );
+  assert!(matches!(
+    ExternalMsg::peek_kind(br#"{"SignIn": {"#, WireFormat::Json),
+    Err(PeekError::Malformed(_))
+  ));
+}
+
+/// Test: `peek_kind` finds the tag field of internally tagged JSON wherever it appears.
+#[test]
+fn test_peek_kind_internal() {
+  let json =
+    br#"{"user": "bob", "nested": {"type": "sign_out"}, "type": "sign_in", "attempts": 1}"#;
+  assert_eq!(InternalMsg::peek_kind(json, WireFormat::Json), Ok(InternalMsgKind::SignIn));
+  assert_eq!(
+    InternalMsg::peek_kind(br#"{"user": "bob"}"#, WireFormat::Json),
+    Err(PeekError::MissingTag)
+  );
+}
+
+/// Test: `peek_kind` reads the leading variant index of bincode.
+#[test]
+fn test_peek_kind_bincode() {
+  let bytes = bincode::serialize(&ExternalMsg::Count(9)).unwrap();
+  assert_eq!(ExternalMsg::peek_kind(&bytes, WireFormat::Bincode), Ok(ExternalMsgKind::Count));
+  assert_eq!(ExternalMsg::peek_kind(&[1], WireFormat::BincodeVarint), Ok(ExternalMsgKind::SignOut));
+  assert_eq!(ExternalMsg::peek_kind(&[1, 0], WireFormat::Bincode), Err(PeekError::Truncated));
+  assert_eq!(
+    ExternalMsg::peek_kind(&[7, 0, 0, 0], WireFormat::Bincode),
+    Err(PeekError::UnknownIndex(7))
+  );
+}
+
+/// Test: The variant index reader rejects JSON instead of panicking.
+///
+/// Verifies the runtime helper behind `peek_kind` reports JSON, which has no variant index,
+/// as an unsupported format.
+#[test]
+fn test_variant_index_json_unsupported() {
+  use enum_group_macros::__private::peek::variant_index;
+
+  let err = variant_index(br#"{"Count": 1}"#, WireFormat::Json).unwrap_err();
+  assert_eq!(err, PeekError::UnsupportedFormat(WireFormat::Json));
+  assert_eq!(err.to_string(), "cannot peek the variant index of messages in Json");
+  assert_eq!(variant_index(&[2, 0, 0, 0], WireFormat::Bincode), Ok(2));
+}
+
+// =============================================================================
+// Section C: Serde Tagging Styles
+// =============================================================================
+
+define_enum_group! {
+  #[enum_group(peek_kind)]
+  #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+  #[serde(tag = "type", content = "payload")]
+  enum AdjacentMsg {
+    AdjacentAuth {
+      SignIn(Login),
+      SignOut(Logout),
+    },
+    AdjacentStats {
+      Count(u64),
+    }
+  }
+}
+
+define_enum_group! {
+  #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+  #[serde(untagged)]
+  enum UntaggedMsg {
+    UntaggedAuth {
+      SignIn(Login),
+    },
+    UntaggedStats {
+      Count(u64),
+      Label(String),
+    }
+  }
+}
+
+/// Test: Group enums serialize exactly like the wire enum, in every tagging style.
+///
+/// Verifies a message and its group enum produce the same JSON, which deserializes back
+/// into both.
+#[test]
+fn test_group_enums_share_layout() {
+  fn check<W, G>(msg: W, group: G, expected: &str)
+  where
+    W: Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug,
+    G: Serialize + serde::de::DeserializeOwned,
+  {
+    let json = serde_json::to_string(&msg).unwrap();
+    assert_eq!(json, expected);
+    assert_eq!(serde_json::to_string(&group).unwrap(), expected);
+    assert_eq!(serde_json::from_str::<W>(&json).unwrap(), msg);
+    serde_json::from_str::<G>(&json).unwrap();
+  }
+
+  let login = || Login { user: "ann".into(), attempts: 2 };
+  check(
+    ExternalMsg::SignIn(login()),
+    Auth::SignIn(login()),
+    r#"{"SignIn":{"user":"ann","attempts":2}}"#,
+  );
+  check(
+    InternalMsg::SignIn(login()),
+    Session::SignIn(login()),
+    r#"{"type":"sign_in","user":"ann","attempts":2}"#,
+  );
+  check(AdjacentMsg::Count(3), AdjacentStats::Count(3), r#"{"type":"Count","payload":3}"#);
+  check(UntaggedMsg::Label("x".into()), UntaggedStats::Label("x".into()), r#""x""#);
+  check(
+    UntaggedMsg::SignIn(login()),
+    UntaggedAuth::SignIn(login()),
+    r#"{"user":"ann","attempts":2}"#,
+  );
+}
+
+/// Test: Variants with named fields serialize as struct variants, internally tagged too.
+///
+/// Verifies the group enum shares the layout and the field attributes apply to both.
+#[test]
+fn test_struct_variants_share_layout() {
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "type")]
+    enum ConnectionMsg {
+      Connection {
+        Connected {
+          session_id: u64,
+          #[serde(default, skip_serializing_if = "Option::is_none")]
+          addr: Option<String>,
+        },
+      }
+    }
+  }
+
+  let msg = ConnectionMsg::Connected { session_id: 7, addr: None };
+  let json = serde_json::to_string(&msg).unwrap();
+  assert_eq!(json, r#"{"type":"Connected","session_id":7}"#);
+  let group = Connection::Connected { session_id: 7, addr: None };
+  assert_eq!(serde_json::to_string(&group).unwrap(), json);
+  assert_eq!(serde_json::from_str::<ConnectionMsg>(&json).unwrap(), msg);
+  assert_eq!(serde_json::from_str::<Connection>(&json).unwrap(), group);
+}
+
+/// Test: Unit variants serialize like serde's own unit variants.
+///
+/// Verifies the externally tagged string and the internally tagged object for the wire and
+/// group enums, and that peeking and decoding by tag need nothing but the tag.
+#[test]
+fn test_unit_variants_serde() {
+  define_enum_group! {
+    #[enum_group(peek_kind, decode_by_tag)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum BareSignal {
+      BareControl {
+        Ping,
+        Echo(u32),
+      }
+    }
+  }
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "type")]
+    enum TaggedSignal {
+      TaggedControl {
+        Shutdown,
+      }
+    }
+  }
+
+  assert_eq!(serde_json::to_string(&BareSignal::Ping).unwrap(), r#""Ping""#);
+  assert_eq!(serde_json::to_string(&BareControl::Ping).unwrap(), r#""Ping""#);
+  assert_eq!(serde_json::from_str::<BareSignal>(r#""Ping""#).unwrap(), BareSignal::Ping);
+  assert_eq!(BareSignal::peek_kind(br#""Ping""#, WireFormat::Json), Ok(BareSignalKind::Ping));
+  assert_eq!(BareSignal::try_from(("Ping", &b""[..])).unwrap(), BareSignal::Ping);
+
+  let json = serde_json::to_string(&TaggedSignal::Shutdown).unwrap();
+  assert_eq!(json, r#"{"type":"Shutdown"}"#);
+  assert_eq!(serde_json::from_str::<TaggedControl>(&json).unwrap(), TaggedControl::Shutdown);
+}
+
+/// Test: an `#[unknown]` group catches the messages of newer peers.
+///
+/// Verifies a unit catch-all of an internally tagged enum, a payload capturing the whole
+/// message of an externally tagged one and serializing it back unchanged, and that known
+/// messages are unaffected.
+#[test]
+fn test_unknown_variants() {
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "type")]
+    enum TaggedEvent {
+      TaggedAuth {
+        SignIn(Login),
+      },
+      /// A message of a newer peer.
+      #[unknown]
+      Unknown,
+    }
+  }
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum ExternalEvent {
+      ExternalAuth {
+        SignIn(Login),
+      },
+      #[unknown]
+      Unrecognized(serde_json::Value),
+    }
+  }
+
+  let json = r#"{"type":"SignUp","user":"ann"}"#;
+  assert_eq!(serde_json::from_str::<TaggedEvent>(json).unwrap(), TaggedEvent::Unknown);
+  assert!(matches!(TaggedEvent::Unknown.into_group(), TaggedEventGroup::Unknown(Unknown::Unknown)));
+  assert_eq!(TaggedEventGroupKind::ALL.last(), Some(&TaggedEventGroupKind::Unknown));
+
+  let json = r#"{"SignUp":{"user":"ann"}}"#;
+  let msg = serde_json::from_str::<ExternalEvent>(json).unwrap();
+  assert_eq!(msg, ExternalEvent::Unrecognized(serde_json::json!({"SignUp": {"user": "ann"}})));
+  assert_eq!(serde_json::to_string(&msg).unwrap(), json);
+  assert!(matches!(msg.into_group(), ExternalEventGroup::Unrecognized(_)));
+
+  let login = ExternalEvent::SignIn(Login::default());
+  let json = serde_json::to_string(&login).unwrap();
+  assert_eq!(serde_json::from_str::<ExternalEvent>(&json).unwrap(), login);
+}
+
+/// Test: `peek_kind` supports every tagged layout, in JSON and bincode where it applies.
+///
+/// Verifies adjacently tagged messages are peeked from either encoding, and that the
+/// missing variant index of internally tagged bincode is reported instead of misread.
+#[test]
+fn test_peek_kind_tagging_styles() {
+  let msg = AdjacentMsg::Count(3);
+  let json = serde_json::to_vec(&msg).unwrap();
+  assert_eq!(AdjacentMsg::peek_kind(&json, WireFormat::Json), Ok(AdjacentMsgKind::Count));
+  let bytes = bincode::serialize(&msg).unwrap();
+  assert_eq!(AdjacentMsg::peek_kind(&bytes, WireFormat::Bincode), Ok(AdjacentMsgKind::Count));
+
+  // Adjacently tagged content may precede the tag
+  let json = br#"{"payload": {"user": "ann", "attempts": 0}, "type": "SignIn"}"#;
+  assert_eq!(AdjacentMsg::peek_kind(json, WireFormat::Json), Ok(AdjacentMsgKind::SignIn));
+
+  let bytes = bincode::serialize(&InternalMsg::SignOut(Logout)).unwrap();
+  assert_eq!(
+    InternalMsg::peek_kind(&bytes, WireFormat::Bincode),
+    Err(PeekError::UnsupportedFormat(WireFormat::Bincode))
+  );
+}
+
+/// Test: `peek_kind` reports unknown tags and indices as the `#[unknown]` catch-all.
+///
+/// Verifies the catch-all is left out of `TAG_TABLE`, so a message tagged with its name is
+/// not mistaken for a known one, and that known kinds are still peeked.
+#[test]
+fn test_peek_kind_unknown() {
+  define_enum_group! {
+    #[enum_group(peek_kind)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "type")]
+    enum PeekedEvent {
+      PeekedAuth {
+        SignIn(Login),
+        SignOut(Logout),
+      },
+      #[unknown]
+      Unknown,
+    }
+  }
+
+  assert_eq!(
+    PeekedEvent::TAG_TABLE,
+    [(PeekedEventKind::SignIn, "SignIn"), (PeekedEventKind::SignOut, "SignOut")]
+  );
+  let json = br#"{"type": "SignUp", "user": "ann"}"#;
+  assert_eq!(PeekedEvent::peek_kind(json, WireFormat::Json), Ok(PeekedEventKind::Unknown));
+  let json = serde_json::to_vec(&PeekedEvent::SignOut(Logout)).unwrap();
+  assert_eq!(PeekedEvent::peek_kind(&json, WireFormat::Json), Ok(PeekedEventKind::SignOut));
+
+  define_enum_group! {
+    #[enum_group(peek_kind)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum IndexedEvent {
+      IndexedAuth {
+        SignIn(Login),
+      },
+      #[unknown]
+      Unrecognized(serde_json::Value),
+    }
+  }
+
+  assert_eq!(
+    IndexedEvent::peek_kind(br#"{"SignUp": {}}"#, WireFormat::Json),
+    Ok(IndexedEventKind::Unrecognized)
+  );
+  assert_eq!(
+    IndexedEvent::peek_kind(&[7, 0, 0, 0], WireFormat::Bincode),
+    Ok(IndexedEventKind::Unrecognized)
+  );
+  assert_eq!(
+    IndexedEvent::peek_kind(&[0], WireFormat::BincodeVarint),
+    Ok(IndexedEventKind::SignIn)
+  );
+}
+
+// =============================================================================
+// Section D: Deserializing by Tag
+// =============================================================================
+
+/// Test: `deserialize_by_tag` builds a message from a separate tag and payload.
+///
+/// Verifies renamed tags, every variant of the table and the unknown tag error.
+#[test]
+fn test_deserialize_by_tag() {
+  define_enum_group! {
+    #[enum_group(deserialize_by_tag)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "type", content = "payload", rename_all = "snake_case")]
+    enum FramedMsg {
+      FramedAuth {
+        SignIn(Login),
+        #[serde(rename = "bye")]
+        SignOut(Logout),
+      },
+      FramedStats {
+        Count(u64),
+        Average(f64),
+      }
+    }
+  }
+
+  let payload = serde_json::json!({ "user": "ann", "attempts": 3 });
+  assert_eq!(
+    FramedMsg::deserialize_by_tag("sign_in", &payload).unwrap(),
+    FramedMsg::SignIn(Login { user: "ann".into(), attempts: 3 })
+  );
+  let null = serde_json::Value::Null;
+  assert_eq!(FramedMsg::deserialize_by_tag("bye", &null).unwrap(), FramedMsg::SignOut(Logout));
+  assert_eq!(FramedMsg::deserialize_by_tag("count", &3.into()).unwrap(), FramedMsg::Count(3));
+  assert_eq!(
+    FramedMsg::deserialize_by_tag("average", &1.5.into()).unwrap(),
+    FramedMsg::Average(1.5)
+  );
+
+  let err = FramedMsg::deserialize_by_tag("reboot", &null).unwrap_err();
+  assert!(err.to_string().contains("unknown variant `reboot`"), "{err}");
+  assert!(FramedMsg::deserialize_by_tag("count", &payload).is_err());
+}
+
+/// Test: `decode_by_tag` decodes payload bytes selected by a separate tag.
+///
+/// Verifies the JSON default, a custom bincode decoder and both error cases.
+#[test]
+fn test_decode_by_tag() {
+  use enum_group_macros::TagDecodeError;
+
+  define_enum_group! {
+    #[enum_group(decode_by_tag)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    enum JsonFramedMsg {
+      JsonAuth {
+        SignIn(Login),
+      },
+      JsonStats {
+        Count(u64),
+      }
+    }
+  }
+
+  define_enum_group! {
+    #[enum_group(decode_by_tag(with = bincode::deserialize, error = bincode::Error))]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum BinFramedMsg {
+      BinAuth {
+        SignIn(Login),
+      },
+      BinStats {
+        Count(u64),
+      }
+    }
+  }
+
+  let bytes = br#"{"user":"ann","attempts":2}"#;
+  assert_eq!(
+    JsonFramedMsg::try_from(("sign_in", &bytes[..])).unwrap(),
+    JsonFramedMsg::SignIn(Login { user: "ann".into(), attempts: 2 })
+  );
+  assert_eq!(JsonFramedMsg::try_from(("count", &b"7"[..])).unwrap(), JsonFramedMsg::Count(7));
+  assert!(matches!(
+    JsonFramedMsg::try_from(("reboot", &b"7"[..])),
+    Err(TagDecodeError::UnknownTag(tag)) if tag == "reboot"
+  ));
+  let err = JsonFramedMsg::try_from(("count", &bytes[..])).unwrap_err();
+  assert!(matches!(err, TagDecodeError::Payload(_)));
+  assert!(err.to_string().starts_with("invalid message payload"), "{err}");
+
+  let bytes = bincode::serialize(&Login { user: "bob".into(), attempts: 1 }).unwrap();
+  assert_eq!(
+    BinFramedMsg::try_from(("SignIn", &bytes[..])).unwrap(),
+    BinFramedMsg::SignIn(Login { user: "bob".into(), attempts: 1 })
+  );
+  let bytes = bincode::serialize(&9u64).unwrap();
+  assert_eq!(BinFramedMsg::try_from(("Count", &bytes[..])).unwrap(), BinFramedMsg::Count(9));
+}
+
+/// Test: Tags no variant has decode to the `#[unknown]` catch-all.
+///
+/// Verifies `deserialize_by_tag` hands a payload catch-all the reassembled message, as
+/// deserializing it whole would, and that `decode_by_tag` returns a unit catch-all or
+/// decodes the payload bytes into a payload one, known tags being unaffected.
+#[test]
+fn test_unknown_by_tag() {
+  define_enum_group! {
+    #[enum_group(deserialize_by_tag, decode_by_tag)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "type", content = "payload")]
+    enum FramedEvent {
+      FramedEventAuth {
+        SignIn(Login),
+      },
+      #[unknown]
+      Unrecognized(serde_json::Value),
+    }
+  }
+
+  define_enum_group! {
+    #[enum_group(decode_by_tag)]
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "type")]
+    enum UnitFramedEvent {
+      UnitFramedAuth {
+        SignIn(Login),
+      },
+      #[unknown]
+      Unknown,
+    }
+  }
+
+  let payload = serde_json::json!({ "user": "ann" });
+  let msg = FramedEvent::deserialize_by_tag("SignUp", &payload).unwrap();
+  let whole = r#"{"type": "SignUp", "payload": {"user": "ann"}}"#;
+  assert_eq!(msg, serde_json::from_str::<FramedEvent>(whole).unwrap());
+  assert_eq!(
+    msg,
+    FramedEvent::Unrecognized(
+      serde_json::json!({ "type": "SignUp", "payload": { "user": "ann" } })
+    )
+  );
+  let payload = serde_json::json!({ "user": "bob", "attempts": 1 });
+  assert_eq!(
+    FramedEvent::deserialize_by_tag("SignIn", &payload).unwrap(),
+    FramedEvent::SignIn(Login { user: "bob".into(), attempts: 1 })
+  );
+
+  let bytes = br#"{"user":"ann"}"#;
+  assert_eq!(
+    FramedEvent::try_from(("SignUp", &bytes[..])).unwrap(),
+    FramedEvent::Unrecognized(serde_json::json!({ "user": "ann" }))
+  );
+  assert_eq!(UnitFramedEvent::try_from(("SignUp", &b"{}"[..])).unwrap(), UnitFramedEvent::Unknown);
+  let bytes = br#"{"user":"bob","attempts":1}"#;
+  assert_eq!(
+    UnitFramedEvent::try_from(("SignIn", &bytes[..])).unwrap(),
+    UnitFramedEvent::SignIn(Login { user: "bob".into(), attempts: 1 })
+  );
+}
@@ -4,7 +4,7 @@
 
 #![allow(dead_code)] // Generated enum variants are intentionally not fully used in tests
 
-use enum_group_macros::{define_enum_group, match_enum_group};
+use enum_group_macros::{define_enum_group, match_enum_group, match_enum_group_pair, EnumGroup};
 
 // =============================================================================
 // Test Helper Types
@@ -197,6 +197,43 @@ fn test_trailing_comma_in_arms() {
   assert!(result);
 }
 
+/// Test: a `_` arm catches the remaining groups, including undispatched ones.
+#[test]
+fn test_wildcard_arm() {
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    enum PartlyDispatchedMsg {
+      Work {
+        Job(MsgA),
+      },
+      #[group(skip_dispatch)]
+      Debug {
+        Dump(MsgB),
+      },
+      Idle {
+        Ping(MsgC),
+      }
+    }
+  }
+
+  let describe = |msg: PartlyDispatchedMsg| {
+    match_enum_group!(msg, PartlyDispatchedMsg, {
+      Work(_) => "work",
+      _ => "other",
+    })
+  };
+  assert_eq!(describe(PartlyDispatchedMsg::Job(MsgA { value: 1 })), "work");
+  assert_eq!(describe(PartlyDispatchedMsg::Dump(MsgB { text: "x".into() })), "other");
+  assert_eq!(describe(PartlyDispatchedMsg::Ping(MsgC { flag: true })), "other");
+
+  let debug = match_enum_group!(PartlyDispatchedMsg::Dump(MsgB { text: "y".into() }), PartlyDispatchedMsg, {
+    Work(_) => None,
+    Idle(_) => None,
+    Undispatched(msg) => Some(msg),
+  });
+  assert!(matches!(debug, Some(PartlyDispatchedMsg::Dump(_))));
+}
+
 // =============================================================================
 // Section D: Expression Semantics
 // =============================================================================
@@ -263,3 +300,289 @@ fn test_match_in_function() {
 
   assert_eq!(process_message(TestWireMsg::BetaOne(MsgC { flag: false })), "Processed beta: false");
 }
+
+// =============================================================================
+// Section E: Pairwise Matching
+// =============================================================================
+
+/// Test: match_enum_group_pair! matches the groups of two values at once.
+///
+/// Verifies same-group arms, one-sided wildcards and the catch-all arm.
+#[test]
+fn test_match_pair() {
+  fn correlate(req: TestWireMsg, resp: TestWireMsg) -> &'static str {
+    match_enum_group_pair!((req, resp), TestWireMsg, {
+      (GroupAlpha(_), GroupAlpha(_)) => "alpha",
+      (_, GroupBeta(GroupBeta::BetaOne(c))) if c.flag => "beta flagged",
+      (GroupBeta(_), _) => "beta request",
+      _ => "mismatch",
+    })
+  }
+
+  let alpha = || TestWireMsg::AlphaOne(MsgA { value: 1 });
+  let beta = |flag| TestWireMsg::BetaOne(MsgC { flag });
+
+  assert_eq!(correlate(alpha(), alpha()), "alpha");
+  assert_eq!(correlate(alpha(), beta(true)), "beta flagged");
+  assert_eq!(correlate(beta(false), alpha()), "beta request");
+  assert_eq!(correlate(alpha(), beta(false)), "mismatch");
+}
+
+// =============================================================================
+// Section F: Wrapped Scrutinees
+// =============================================================================
+
+/// Test: A `Result` of the wire enum is matched with an `Err` arm.
+///
+/// Verifies both outcomes reach their arm, in any arm order, and that the `?` form works
+/// without an `Err` arm.
+#[test]
+fn test_match_result() {
+  fn describe(result: Result<TestWireMsg, String>) -> String {
+    match_enum_group!(result, TestWireMsg, {
+      Err(e) => format!("error: {e}"),
+      GroupAlpha(_) => "alpha".to_string(),
+      GroupBeta(_) => "beta".to_string(),
+    })
+  }
+
+  assert_eq!(describe(Ok(TestWireMsg::BetaOne(MsgC { flag: true }))), "beta");
+  assert_eq!(describe(Err("eof".to_string())), "error: eof");
+
+  fn propagate(result: Result<TestWireMsg, String>) -> Result<bool, String> {
+    Ok(match_enum_group!(result?, TestWireMsg, {
+      GroupAlpha(_) => true,
+      GroupBeta(_) => false,
+    }))
+  }
+
+  assert_eq!(propagate(Ok(TestWireMsg::AlphaOne(MsgA { value: 1 }))), Ok(true));
+  assert_eq!(propagate(Err("eof".to_string())), Err("eof".to_string()));
+}
+
+/// Test: An `Option` of the wire enum is matched with a `None` arm.
+///
+/// Verifies a receive loop ends on `None`, and that `Err` and `None` arms combine for a
+/// `Result<Option<_>, _>`.
+#[test]
+fn test_match_option() {
+  let (tx, rx) = std::sync::mpsc::channel();
+  tx.send(TestWireMsg::AlphaOne(MsgA { value: 1 })).unwrap();
+  tx.send(TestWireMsg::BetaOne(MsgC { flag: true })).unwrap();
+  drop(tx);
+
+  let mut seen = Vec::new();
+  loop {
+    let done = match_enum_group!(rx.recv().ok(), TestWireMsg, {
+      GroupAlpha(_) => { seen.push("alpha"); false }
+      GroupBeta(_) => { seen.push("beta"); false }
+      None => true,
+    });
+    if done {
+      break;
+    }
+  }
+  assert_eq!(seen, ["alpha", "beta"]);
+
+  fn describe(next: Result<Option<TestWireMsg>, &str>) -> String {
+    match_enum_group!(next, TestWireMsg, {
+      GroupAlpha(_) => "alpha".to_string(),
+      GroupBeta(_) => "beta".to_string(),
+      None => "closed".to_string(),
+      Err(e) => format!("error: {e}"),
+    })
+  }
+
+  assert_eq!(describe(Ok(Some(TestWireMsg::AlphaOne(MsgA { value: 1 })))), "alpha");
+  assert_eq!(describe(Ok(None)), "closed");
+  assert_eq!(describe(Err("reset")), "error: reset");
+}
+
+/// Test: boxed and shared messages are matched without unwrapping them first.
+///
+/// Verifies `box` unboxes a `Box`, `box UnwrapOrClone(..)` takes a unique `Arc` or `Rc`
+/// and clones a shared one, and `Arc::try_unwrap` hands a shared pointer to the `Err` arm.
+#[test]
+fn test_match_smart_pointers() {
+  use enum_group_macros::UnwrapOrClone;
+  use std::rc::Rc;
+  use std::sync::Arc;
+
+  let boxed = Box::new(TestWireMsg::AlphaOne(MsgA { value: 1 }));
+  let value = match_enum_group!(box boxed, TestWireMsg, {
+    GroupAlpha(_) => 1,
+    GroupBeta(_) => 2,
+  });
+  assert_eq!(value, 1);
+
+  let describe = |arc: Arc<TestWireMsg>| {
+    match_enum_group!(box UnwrapOrClone(arc), TestWireMsg, {
+      GroupAlpha(_) => "alpha",
+      GroupBeta(_) => "beta",
+    })
+  };
+  let shared = Arc::new(TestWireMsg::BetaOne(MsgC { flag: true }));
+  assert_eq!(describe(shared.clone()), "beta");
+  assert_eq!(describe(shared), "beta");
+
+  let rc = Rc::new(TestWireMsg::AlphaTwo(MsgB { text: "rc".into() }));
+  let is_alpha = match_enum_group!(box UnwrapOrClone(rc), TestWireMsg, {
+    GroupAlpha(_) => true,
+    GroupBeta(_) => false,
+  });
+  assert!(is_alpha);
+
+  let try_unique = |arc: Arc<TestWireMsg>| {
+    match_enum_group!(Arc::try_unwrap(arc), TestWireMsg, {
+      GroupAlpha(_) => "alpha",
+      GroupBeta(_) => "beta",
+      Err(_shared) => "shared",
+    })
+  };
+  let shared = Arc::new(TestWireMsg::AlphaOne(MsgA { value: 2 }));
+  let other = shared.clone();
+  assert_eq!(try_unique(shared), "shared");
+  assert_eq!(try_unique(other), "alpha");
+}
+
+/// Test: A value whose type is left to inference is matched as the wire enum.
+///
+/// Verifies `serde_json::from_str(..).unwrap()` needs no annotation, since only `box`
+/// values go through `Scrutinee`.
+#[test]
+fn test_match_inferred_scrutinee() {
+  use serde::Deserialize;
+
+  define_enum_group! {
+    #[derive(Debug, Clone, Deserialize)]
+    enum DecodedMsg {
+      Control {
+        Stop(u8),
+      },
+      Data {
+        Count(u64),
+      }
+    }
+  }
+
+  let decode = |json: &str| {
+    match_enum_group!(serde_json::from_str(json).unwrap(), DecodedMsg, {
+      Control(_) => None,
+      Data(Data::Count(n)) => Some(n),
+    })
+  };
+  assert_eq!(decode(r#"{"Count": 3}"#), Some(3));
+  assert_eq!(decode(r#"{"Stop": 0}"#), None);
+}
+
+// =============================================================================
+// Section G: Async Mode
+// =============================================================================
+
+/// Test: `async` mode awaits the future of the matched arm.
+///
+/// Verifies arms returning different future types combine into one future, together with
+/// an `Err` arm.
+#[test]
+fn test_match_async() {
+  use std::future::Future;
+  use std::task::{Context, Poll, Waker};
+
+  fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+      if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+        return output;
+      }
+    }
+  }
+
+  async fn alpha_value(alpha: GroupAlpha) -> i32 {
+    match alpha {
+      GroupAlpha::AlphaOne(a) => a.value,
+      GroupAlpha::AlphaTwo(_) => -1,
+    }
+  }
+
+  let handle = |result: Result<TestWireMsg, i32>| {
+    match_enum_group!(async result, TestWireMsg, {
+      GroupAlpha(a) => alpha_value(a),
+      GroupBeta(_) => async { 100 },
+      Err(code) => std::future::ready(code),
+    })
+  };
+
+  assert_eq!(block_on(handle(Ok(TestWireMsg::AlphaOne(MsgA { value: 7 })))), 7);
+  assert_eq!(block_on(handle(Ok(TestWireMsg::BetaOne(MsgC { flag: false })))), 100);
+  assert_eq!(block_on(handle(Err(-5))), -5);
+}
+
+/// Test: `async` mode awaits the future returned by an arm set.
+#[test]
+fn test_match_async_arm_set() {
+  use std::future::Future;
+  use std::task::{Context, Poll, Waker};
+
+  fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = std::pin::pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+      if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+        return output;
+      }
+    }
+  }
+
+  let arms = match_enum_group!(TestWireMsg, {
+    GroupAlpha(GroupAlpha::AlphaOne(a)) => std::future::ready(a.value),
+    GroupAlpha(_) => std::future::ready(-1),
+    GroupBeta(_) => std::future::ready(0),
+  });
+  let msg = TestWireMsg::AlphaOne(MsgA { value: 3 });
+  assert_eq!(block_on(match_enum_group!(async msg, TestWireMsg, arms)), 3);
+}
+
+// =============================================================================
+// Section H: Generic Contexts
+// =============================================================================
+
+/// Group-generic utility counting messages before handing them to caller-supplied arms.
+fn counted<T: EnumGroup, R>(msg: T, count: &mut usize, arms: impl FnOnce(T::Group) -> R) -> R {
+  *count += 1;
+  match_enum_group!(msg, T, arms)
+}
+
+/// Test: an arm set built for a concrete wire enum is applied in a generic function.
+///
+/// Verifies the arm set borrows local state and works with both groups.
+#[test]
+fn test_match_arm_set_in_generic_fn() {
+  let mut count = 0;
+  let mut seen = Vec::new();
+
+  for msg in [
+    TestWireMsg::AlphaOne(MsgA { value: 1 }),
+    TestWireMsg::BetaOne(MsgC { flag: true }),
+    TestWireMsg::AlphaTwo(MsgB { text: "two".into() }),
+  ] {
+    let label = counted(
+      msg,
+      &mut count,
+      match_enum_group!(TestWireMsg, {
+        GroupAlpha(a) => {
+          seen.push(format!("{:?}", a));
+          "alpha"
+        },
+        GroupBeta(_) => "beta",
+      }),
+    );
+    seen.push(label.to_string());
+  }
+
+  assert_eq!(count, 3);
+  assert_eq!(seen.len(), 5);
+  assert_eq!(seen[1], "alpha");
+  assert_eq!(seen[2], "beta");
+  assert_eq!(seen[4], "alpha");
+}
@@ -0,0 +1,116 @@
+//! Tests for the `migrate_enum_group!` macro.
+//!
+//! This file tests the conversions generated between two versions of a wire enum.
+
+#![allow(dead_code)] // Generated enum variants are intentionally not fully used in tests
+
+use enum_group_macros::{define_enum_group, migrate_enum_group};
+
+// =============================================================================
+// Test Helper Types
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginV1 {
+  pub user: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginV2 {
+  pub user: String,
+  pub token: Option<String>,
+}
+
+impl From<LoginV1> for LoginV2 {
+  fn from(v1: LoginV1) -> Self {
+    LoginV2 { user: v1.user, token: None }
+  }
+}
+
+mod v1 {
+  use super::*;
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum WireMsg {
+      Auth {
+        Login(LoginV1),
+        Logout(()),
+      },
+      Legacy {
+        Ping(u32),
+      }
+    }
+  }
+}
+
+mod v2 {
+  use super::*;
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum WireMsg {
+      Session {
+        SignIn(LoginV2),
+        SignOut(()),
+      }
+    }
+  }
+}
+
+mod v3 {
+  use super::*;
+
+  define_enum_group! {
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum WireMsg {
+      Session {
+        SignIn(LoginV2),
+        SignOut(()),
+        Heartbeat(u64),
+      }
+    }
+  }
+}
+
+migrate_enum_group!(v1::WireMsg => v2::WireMsg {
+  Login => SignIn,
+  Logout => SignOut,
+  Ping => drop,
+});
+
+migrate_enum_group!(v2::WireMsg => v3::WireMsg {
+  SignIn => SignIn,
+  SignOut => SignOut,
+});
+
+// =============================================================================
+// Section A: Conversions
+// =============================================================================
+
+/// Test: Migrations with dropped variants implement `TryFrom`.
+///
+/// Verifies mapped payloads are converted through `From` and dropped messages are handed
+/// back unchanged.
+#[test]
+fn test_migrate_with_drop() {
+  let login = v1::WireMsg::Login(LoginV1 { user: "ann".into() });
+  assert_eq!(
+    v2::WireMsg::try_from(login),
+    Ok(v2::WireMsg::SignIn(LoginV2 { user: "ann".into(), token: None }))
+  );
+  assert_eq!(v2::WireMsg::try_from(v1::WireMsg::Logout(())), Ok(v2::WireMsg::SignOut(())));
+  assert_eq!(v2::WireMsg::try_from(v1::WireMsg::Ping(3)), Err(v1::WireMsg::Ping(3)));
+}
+
+/// Test: Total migrations implement `From`.
+///
+/// Verifies identical payloads are carried over and `into()` works.
+#[test]
+fn test_migrate_total() {
+  let v3: v3::WireMsg = v2::WireMsg::SignOut(()).into();
+  assert_eq!(v3, v3::WireMsg::SignOut(()));
+
+  let login = LoginV2 { user: "bob".into(), token: Some("t".into()) };
+  assert_eq!(v3::WireMsg::from(v2::WireMsg::SignIn(login.clone())), v3::WireMsg::SignIn(login));
+}
@@ -0,0 +1,86 @@
+//! Tests for the helpers enabled by the `pyo3` feature.
+//!
+//! Run with `cargo test --features pyo3`; needs a Python interpreter to link against.
+
+#![cfg(feature = "pyo3")]
+#![allow(dead_code)] // Generated enum variants are intentionally not fully used in tests
+
+use enum_group_macros::__private::pyo3::prelude::*;
+use enum_group_macros::__private::pyo3::types::PyDict;
+use enum_group_macros::define_enum_group;
+use serde::{Deserialize, Serialize};
+
+// =============================================================================
+// Test Helper Types
+// =============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Login {
+  pub user: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Logout;
+
+define_enum_group! {
+  #[enum_group(pyo3)]
+  #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+  #[serde(tag = "type", content = "payload")]
+  pub enum SessionMsg {
+    Auth {
+      SignIn(Login),
+      SignOut(Logout),
+    },
+    Stats {
+      Count(u64),
+    }
+  }
+}
+
+// =============================================================================
+// Section A: Conversions
+// =============================================================================
+
+/// Test: `to_py` produces the dict of the serde layout and `from_py` reads it back.
+#[test]
+fn test_py_roundtrip() {
+  Python::initialize();
+  Python::attach(|py| {
+    let msg = SessionMsg::SignIn(Login { user: "alice".into() });
+    let obj = msg.to_py(py).unwrap();
+    let dict = obj.cast::<PyDict>().unwrap();
+    assert_eq!(dict.get_item("type").unwrap().unwrap().extract::<String>().unwrap(), "SignIn");
+    assert_eq!(SessionMsg::from_py(&obj).unwrap(), msg);
+  });
+}
+
+/// Test: `from_py` raises `ValueError` for objects matching no variant.
+#[test]
+fn test_py_invalid() {
+  Python::initialize();
+  Python::attach(|py| {
+    let dict = PyDict::new(py);
+    dict.set_item("type", "Unknown").unwrap();
+    let err = SessionMsg::from_py(dict.as_any()).unwrap_err();
+    assert!(err.is_instance_of::<enum_group_macros::__private::pyo3::exceptions::PyValueError>(py));
+  });
+}
+
+// =============================================================================
+// Section B: Kind classes
+// =============================================================================
+
+/// Test: the kind enums are Python classes comparing equal to themselves.
+#[test]
+fn test_py_kind_class() {
+  Python::initialize();
+  Python::attach(|py| {
+    let kind = Bound::new(py, SessionMsgKind::Count).unwrap();
+    let same = Bound::new(py, SessionMsgKind::Count).unwrap();
+    assert!(kind.as_any().eq(&same).unwrap());
+    assert_eq!(kind.as_any().get_type().name().unwrap().to_string(), "SessionMsgKind");
+    let group = Bound::new(py, SessionMsgGroupKind::Auth).unwrap();
+    assert!(group.as_any().repr().unwrap().to_string().contains("Auth"));
+    assert_eq!(kind.as_any().extract::<SessionMsgKind>().unwrap(), SessionMsgKind::Count);
+  });
+}
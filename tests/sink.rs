@@ -0,0 +1,117 @@
+//! Tests for the sink fan-out enabled by the `futures` feature.
+//!
+//! Run with `cargo test --features futures`.
+
+#![cfg(feature = "futures")]
+#![allow(dead_code)] // Generated enum variants are intentionally not fully used in tests
+
+use enum_group_macros::define_enum_group;
+use futures::channel::mpsc;
+use futures::executor::block_on;
+use futures::{SinkExt, StreamExt};
+
+define_enum_group! {
+  #[enum_group(sink_router)]
+  #[derive(Debug, Clone, PartialEq)]
+  enum FanoutMsg {
+    Control {
+      Start(u32),
+      Stop(u32),
+    },
+    Data {
+      Chunk(Vec<u8>),
+    }
+  }
+}
+
+// =============================================================================
+// Section A: Sink Router
+// =============================================================================
+
+/// Test: The sink router forwards each message to the sink of its group.
+///
+/// Verifies routing through `send_all`, in order per group, and that closing the router
+/// closes every inner sink.
+#[test]
+fn test_sink_router() {
+  let (control_tx, control_rx) = mpsc::unbounded();
+  let (data_tx, data_rx) = mpsc::unbounded();
+  let mut router = FanoutMsgSinkRouter::new(control_tx, data_tx);
+
+  block_on(async {
+    let msgs = vec![FanoutMsg::Start(1), FanoutMsg::Chunk(vec![7]), FanoutMsg::Stop(2)];
+    let mut stream = futures::stream::iter(msgs.into_iter().map(Ok));
+    router.send_all(&mut stream).await.unwrap();
+    router.close().await.unwrap();
+  });
+
+  let control: Vec<Control> = block_on(control_rx.collect());
+  let data: Vec<Data> = block_on(data_rx.collect());
+  assert_eq!(control, [Control::Start(1), Control::Stop(2)]);
+  assert_eq!(data, [Data::Chunk(vec![7])]);
+}
+
+/// Test: A full group sink holds back the whole router.
+///
+/// Verifies `poll_ready` stays pending while one bounded channel is full, and resumes once
+/// it is drained.
+#[test]
+fn test_sink_router_backpressure() {
+  use futures::task::noop_waker_ref;
+  use futures::Sink;
+  use std::pin::Pin;
+  use std::task::{Context, Poll};
+
+  let (control_tx, mut control_rx) = mpsc::channel(0);
+  let (data_tx, _data_rx) = mpsc::channel(0);
+  let mut router = FanoutMsgSinkRouter::new(control_tx, data_tx);
+  let mut cx = Context::from_waker(noop_waker_ref());
+
+  assert!(matches!(Pin::new(&mut router).poll_ready(&mut cx), Poll::Ready(Ok(()))));
+  Pin::new(&mut router).start_send(FanoutMsg::Start(1)).unwrap();
+  assert!(Pin::new(&mut router).poll_ready(&mut cx).is_pending());
+
+  assert_eq!(block_on(control_rx.next()), Some(Control::Start(1)));
+  assert!(matches!(Pin::new(&mut router).poll_ready(&mut cx), Poll::Ready(Ok(()))));
+}
+
+/// Test: Messages of groups left out of dispatch reach the `on_unexpected` hook.
+///
+/// Verifies the hook sees them with their reason before they are dropped, while the
+/// other messages still reach their group's sink.
+#[test]
+fn test_sink_router_on_unexpected() {
+  use enum_group_macros::UnexpectedReason;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  define_enum_group! {
+    #[enum_group(sink_router)]
+    #[derive(Debug, Clone, PartialEq)]
+    enum ProbedMsg {
+      Control {
+        Start(u32),
+      },
+      #[group(skip_dispatch)]
+      Diagnostics {
+        Probe(u32),
+      }
+    }
+  }
+
+  static SKIPPED: AtomicUsize = AtomicUsize::new(0);
+
+  let (control_tx, control_rx) = mpsc::unbounded();
+  let mut router = ProbedMsgSinkRouter::new(control_tx).on_unexpected(|msg, reason| {
+    assert_eq!((msg, reason), (&ProbedMsg::Probe(7), UnexpectedReason::SkippedGroup));
+    SKIPPED.fetch_add(1, Ordering::Relaxed);
+  });
+
+  block_on(async {
+    router.send(ProbedMsg::Probe(7)).await.unwrap();
+    router.send(ProbedMsg::Start(1)).await.unwrap();
+    router.close().await.unwrap();
+  });
+
+  assert_eq!(SKIPPED.load(Ordering::Relaxed), 1);
+  assert_eq!(block_on(control_rx.collect::<Vec<_>>()), [Control::Start(1)]);
+}
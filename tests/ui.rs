@@ -0,0 +1,7 @@
+//! Compile-fail tests checking the spans and messages of the macros' diagnostics.
+
+#[test]
+fn ui() {
+  let t = trybuild::TestCases::new();
+  t.compile_fail("tests/ui/*.rs");
+}
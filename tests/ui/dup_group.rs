@@ -0,0 +1,14 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  enum WireMsg {
+    Protocol {
+      Ping(u8),
+    },
+    Protocol {
+      Pong(u16),
+    }
+  }
+}
+
+fn main() {}
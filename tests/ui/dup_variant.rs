@@ -0,0 +1,14 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  enum WireMsg {
+    Protocol {
+      Ping(u8),
+    },
+    Business {
+      Ping(u16),
+    }
+  }
+}
+
+fn main() {}
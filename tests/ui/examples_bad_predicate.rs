@@ -0,0 +1,13 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  #[derive(Debug, Clone)]
+  #[enum_group(examples(test))]
+  enum WireMsg {
+    Protocol {
+      Ping(u8),
+    }
+  }
+}
+
+fn main() {}
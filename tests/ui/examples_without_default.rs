@@ -0,0 +1,17 @@
+use enum_group_macros::define_enum_group;
+
+#[derive(Debug, Clone)]
+pub struct NoDefault(u8);
+
+define_enum_group! {
+  #[derive(Debug, Clone)]
+  #[enum_group(examples)]
+  enum WireMsg {
+    Protocol {
+      Ping(u8),
+      Raw(NoDefault),
+    }
+  }
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  #[derive(Debug, Clone)]
+  enum WireMsg {
+    #[group(copy)]
+    Control {
+      Stop(u8),
+      Rename(String),
+    }
+  }
+}
+
+fn main() {}
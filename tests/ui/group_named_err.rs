@@ -0,0 +1,14 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  enum WireMsg {
+    Ok {
+      Ping(u8),
+    },
+    Err {
+      Failure(String),
+    }
+  }
+}
+
+fn main() {}
@@ -0,0 +1,35 @@
+use enum_group_macros::{define_enum_group, migrate_enum_group};
+
+mod v1 {
+  use super::*;
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    pub enum WireMsg {
+      Protocol {
+        Ping(u8),
+        Pong(u8),
+      }
+    }
+  }
+}
+
+mod v2 {
+  use super::*;
+  define_enum_group! {
+    #[derive(Debug, Clone)]
+    pub enum WireMsg {
+      Protocol {
+        Ping(u8),
+        Pong(u16),
+      }
+    }
+  }
+}
+
+migrate_enum_group!(v1::WireMsg => v2::WireMsg {
+  Ping => Ping,
+  Pong => Pong,
+  Ping => drop,
+});
+
+fn main() {}
@@ -0,0 +1,15 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  #[enum_group(peek_kind)]
+  #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+  #[serde(untagged)]
+  enum WireMsg {
+    Stats {
+      Count(u64),
+      Label(String),
+    }
+  }
+}
+
+fn main() {}
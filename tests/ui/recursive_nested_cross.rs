@@ -0,0 +1,17 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  #[derive(Debug, Clone)]
+  enum WireMsg {
+    Protocol {
+      Handshake {
+        Resume(Option<Business>),
+      },
+    },
+    Business {
+      Replay(Handshake),
+    }
+  }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  #[derive(Debug, Clone)]
+  enum WireMsg {
+    Protocol {
+      Ping(u8),
+      Handshake {
+        Batch(WireMsg),
+      },
+    }
+  }
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+  #[serde(tag = "type")]
+  enum WireMsg {
+    Stats {
+      Count(u64),
+    }
+  }
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+  #[serde(untagged)]
+  enum WireMsg {
+    Stats {
+      Count(u64),
+    },
+    Billing {
+      Charge(u64),
+    }
+  }
+}
+
+fn main() {}
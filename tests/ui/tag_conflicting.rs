@@ -0,0 +1,16 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  #[derive(Debug, Clone)]
+  enum WireMsg {
+    Protocol {
+      #[variant(tag = 1)]
+      #[variant(tag = 2)]
+      Ping(u8),
+      #[variant(tag = 3)]
+      Pong(u8),
+    }
+  }
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  #[derive(Debug, Clone)]
+  enum WireMsg {
+    Protocol {
+      #[variant(tag = 1)]
+      Ping(u8),
+      #[variant(tag = 1)]
+      Pong(u8),
+    }
+  }
+}
+
+fn main() {}
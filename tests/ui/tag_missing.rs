@@ -0,0 +1,14 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  #[derive(Debug, Clone)]
+  enum WireMsg {
+    Protocol {
+      #[variant(tag = 1)]
+      Ping(u8),
+      Pong(u8),
+    }
+  }
+}
+
+fn main() {}
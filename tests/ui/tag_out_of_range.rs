@@ -0,0 +1,13 @@
+use enum_group_macros::define_enum_group;
+
+define_enum_group! {
+  #[derive(Debug, Clone)]
+  enum WireMsg {
+    Protocol {
+      #[variant(tag = 70000)]
+      Ping(u8),
+    }
+  }
+}
+
+fn main() {}